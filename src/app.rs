@@ -1,31 +1,83 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::SystemTime};
 
 use clap::Parser;
 use iced::{
+    task::Handle,
     widget::{container, text},
     Task,
 };
+use tracing::{debug, error, info, warn};
 
 use crate::{
+    error::{Error, Remedy},
+    log_receiver, logging,
     message::Message,
     models::{
-        region::load_region_names,
-        resource::{list_resources, Resource},
-        workspace::{Project, Workspace},
+        caller_identity::load_caller_identity,
+        client_pool,
+        endpoint::{set_workspace_endpoint, WorkspaceEndpoint},
+        error_report::ErrorReport,
+        mfa::{forget_mfa_session, mfa_challenge, session_expiry, start_mfa_session},
+        profile::{load_shared_config, set_profile_file_overrides, ProfileFileOverrides},
+        region::{
+            default_region, default_region_endpoints, load_region_names, measure_nearest_region,
+            FALLBACK_REGION,
+        },
+        request_policy::{set_profile_account, set_request_settings},
+        s3_object::{
+            apply_version_action, download_object, file_name_of_key, list_object_versions,
+            list_objects, load_object_preview, upload_path, S3ObjectEntry,
+        },
+        s3_presign::{presign_object_url, PresignMethod, PresignRequest},
+        session::{ExploreSession, Session},
+        sso::{
+            complete_device_authorization, needs_login, sso_cache_dir, start_device_authorization,
+            SsoLogin, SsoLoginQueue,
+        },
+        transfer::{Transfer, TransferEvent, TransferKind},
+        workspace::{
+            settings::ProtectionMode, store::remember_workspace, Project, ResourceDescriptor,
+            Workspace,
+        },
     },
+    providers::registry,
     state::State,
-    view::{main_tab::MainTab, pane_type::PaneType},
+    view::{
+        main_tab::MainTab,
+        mfa_prompt::MfaPrompt,
+        pane_type::PaneType,
+        resource_details::s3_bucket_details::{S3BucketDetails, S3PendingAction},
+        workspace_switcher::WorkspaceAction,
+    },
 };
 
 #[derive(Default, Parser)]
 pub struct AppFlags {
+    /// Opens this workspace rather than the last opened one.
     #[clap(short, long)]
     workspace_file: Option<PathBuf>,
+
+    /// Overrides AWS_CONFIG_FILE and ~/.aws/config.
+    #[clap(long)]
+    aws_config_file: Option<PathBuf>,
+
+    /// Overrides AWS_SHARED_CREDENTIALS_FILE and ~/.aws/credentials.
+    #[clap(long)]
+    aws_shared_credentials_file: Option<PathBuf>,
+
+    /// Mirrors the log console to a rotating file in the app's data
+    /// directory.
+    #[clap(long)]
+    log_file: bool,
 }
 
 pub struct AwsomeApp {
     state: Option<State>,
     main_tab: MainTab,
+    /// Aborts polling for the token of the SSO login in progress.
+    sso_login_handle: Option<Handle>,
+    /// The messages to send again once the SSO login has finished.
+    sso_logins: SsoLoginQueue<Message>,
 }
 
 impl Default for AwsomeApp {
@@ -33,12 +85,14 @@ impl Default for AwsomeApp {
         AwsomeApp {
             state: None,
             main_tab: MainTab::new(),
+            sso_login_handle: None,
+            sso_logins: SsoLoginQueue::default(),
         }
     }
 }
 
 impl AwsomeApp {
-    fn loading_view(&self) -> iced::Element<Message> {
+    fn loading_view(&self) -> iced::Element<'_, Message> {
         container(
             text("Loading...")
                 .align_x(iced::alignment::Horizontal::Center)
@@ -52,11 +106,38 @@ impl AwsomeApp {
 
     pub fn new() -> (Self, Task<Message>) {
         let flags = AppFlags::parse();
+        set_profile_file_overrides(ProfileFileOverrides {
+            config: flags.aws_config_file,
+            credentials: flags.aws_shared_credentials_file,
+        });
+
+        let mut main_tab = MainTab::new();
+        let log_file = match flags.log_file {
+            true => match logging::set_log_file(true) {
+                Ok(path) => {
+                    main_tab.footer.log_console.set_log_file(path);
+                    Task::none()
+                }
+                Err(e) => {
+                    error!(error = ?e, "Error while opening the log file");
+                    Task::done(Message::ErrorOccurred(e))
+                }
+            },
+            false => Task::none(),
+        };
+        let select_profile = match std::env::var("AWS_PROFILE") {
+            Ok(profile) if main_tab.explore_tab.has_profile(&profile) => {
+                Task::done(Message::ProfileSelected(profile))
+            }
+            _ => Task::none(),
+        };
 
         (
             AwsomeApp {
                 state: None,
-                main_tab: MainTab::new(),
+                main_tab,
+                sso_login_handle: None,
+                sso_logins: SsoLoginQueue::default(),
             },
             Task::batch([
                 iced::font::load(include_bytes!(concat!(
@@ -68,6 +149,8 @@ impl AwsomeApp {
                     Workspace::load(flags.workspace_file),
                     Message::WorkspaceLoaded,
                 ),
+                select_profile,
+                log_file,
             ]),
         )
     }
@@ -78,7 +161,7 @@ impl AwsomeApp {
                 match result {
                     Ok(_) => {}
                     Err(e) => {
-                        eprintln!("Error while loading font: {:?}", e);
+                        error!(error = ?e, "Error while loading font");
                     }
                 }
                 Task::none()
@@ -87,30 +170,188 @@ impl AwsomeApp {
                 let workspace = match workspace {
                     Ok(workspace) => workspace,
                     Err(e) => {
-                        eprintln!("Error while loading workspace: {:?}", e);
+                        error!(error = ?e, "Error while loading workspace");
+                        // Switching to another workspace keeps the current one.
+                        if self.state.is_some() {
+                            return Task::done(Message::ErrorOccurred(e));
+                        }
                         self.state = Some(State::new());
-                        return Task::done(Message::ErrorOccurred(e));
+                        self.main_tab.workspace_switcher.refresh();
+                        return Task::batch([
+                            Task::done(Message::ErrorOccurred(e)),
+                            measure_nearest_region_if_needed(),
+                        ]);
                     }
                 };
 
+                if let Some(path) = workspace.get_path() {
+                    if let Err(e) = remember_workspace(path) {
+                        warn!(error = ?e, "Error while remembering workspace");
+                    }
+                }
+                self.main_tab.workspace_switcher.refresh();
+                set_workspace_endpoint(WorkspaceEndpoint::from(&workspace.settings));
+                set_request_settings(workspace.settings.requests.clone());
+
+                // Transfers, errors and caller identities outlive a switch.
+                if let Some(state) = &self.state {
+                    // Reloading the same file keeps the views as they are.
+                    let switched = state.workspace.get_path() != workspace.get_path();
+                    if switched {
+                        self.save_session();
+                    }
+                    if let Some(state) = &mut self.state {
+                        state.workspace = workspace;
+                        state.set_external_change(None);
+                    }
+                    self.update_projects();
+                    self.update_protection();
+                    return match switched {
+                        true => self.restore_session(false),
+                        false => Task::none(),
+                    };
+                }
+
                 let state = match State::from_workspace(workspace) {
                     Ok(state) => state,
                     Err(e) => {
-                        eprintln!("Error while loading state: {:?}", e);
+                        error!(error = ?e, "Error while loading state");
                         self.state = Some(State::new());
-                        return Task::done(Message::ErrorOccurred(e));
+                        return Task::batch([
+                            Task::done(Message::ErrorOccurred(e)),
+                            measure_nearest_region_if_needed(),
+                        ]);
                     }
                 };
 
-                self.main_tab
-                    .projects_tab
-                    .set_projects(state.workspace.projects.clone());
                 self.state = Some(state);
+                self.update_projects();
+                self.update_protection();
+                Task::batch([
+                    measure_nearest_region_if_needed(),
+                    self.restore_session(true),
+                ])
+            }
+            Message::WorkspaceSelected(path) => {
+                Task::perform(Workspace::load(Some(path)), Message::WorkspaceLoaded)
+            }
+            Message::WorkspaceInputStarted(action) => {
+                self.main_tab.workspace_switcher.start_input(action)
+            }
+            Message::WorkspaceInputChanged(value) => {
+                self.main_tab.workspace_switcher.set_input(value);
+                Task::none()
+            }
+            Message::WorkspaceInputSubmitted => {
+                let Some((action, value)) = self.main_tab.workspace_switcher.finish_input() else {
+                    return Task::none();
+                };
+                let created = match action {
+                    WorkspaceAction::Open => {
+                        return Task::perform(
+                            Workspace::load(Some(PathBuf::from(value))),
+                            Message::WorkspaceLoaded,
+                        );
+                    }
+                    WorkspaceAction::New => Workspace::create(&value),
+                    WorkspaceAction::Duplicate => match &self.state {
+                        Some(state) => state.workspace.duplicate(&value),
+                        None => return Task::none(),
+                    },
+                };
+                match created {
+                    Ok(workspace) => Task::done(Message::WorkspaceLoaded(Ok(workspace))),
+                    Err(e) => {
+                        error!(workspace = value, error = ?e, "Error while creating workspace");
+                        Task::done(Message::ErrorOccurred(e))
+                    }
+                }
+            }
+            Message::WorkspaceInputCancelled => {
+                self.main_tab.workspace_switcher.cancel_input();
+                Task::none()
+            }
+            Message::WorkspaceFileCheckTicked => {
+                if self
+                    .state
+                    .as_mut()
+                    .is_some_and(|s| s.take_session_changed())
+                {
+                    self.save_session();
+                }
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if !state.workspace.take_external_change() {
+                    return Task::none();
+                }
+                let Some(path) = state.workspace.get_path() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    Workspace::load(Some(path.to_path_buf())),
+                    Message::WorkspaceChangedOnDisk,
+                )
+            }
+            Message::WorkspaceChangedOnDisk(workspace) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                match workspace {
+                    Ok(workspace) if workspace.has_same_content(&state.workspace) => {
+                        state.workspace.accept_external_change(&workspace);
+                        Task::none()
+                    }
+                    Ok(workspace) => {
+                        info!("Workspace file has been changed outside the app");
+                        state.set_external_change(Some(workspace));
+                        Task::none()
+                    }
+                    Err(e) => {
+                        warn!(error = ?e, "Error while reading the changed workspace file");
+                        Task::done(Message::ErrorOccurred(e))
+                    }
+                }
+            }
+            Message::WorkspaceReloadRequested => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                match state.take_external_change() {
+                    Some(workspace) => Task::done(Message::WorkspaceLoaded(Ok(workspace))),
+                    None => Task::none(),
+                }
+            }
+            Message::WorkspaceMergeRequested => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                let Some(theirs) = state.take_external_change() else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.merge(theirs) {
+                    error!(error = ?e, "Error while merging workspace");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                // Applies the merged projects and settings like a reload.
+                Task::done(Message::WorkspaceLoaded(Ok(state.workspace.clone())))
+            }
+            Message::WorkspaceExternalChangeDismissed => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                state.set_external_change(None);
+                // Their version is overwritten with ours.
+                if let Err(e) = state.workspace.overwrite() {
+                    error!(error = ?e, "Error while saving workspace");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
                 Task::none()
             }
             Message::SideDrawerToggled => {
                 if let Some(state) = &mut self.state {
                     state.toggle_side_drawer();
+                    state.mark_session_changed();
                 }
                 Task::none()
             }
@@ -118,6 +359,7 @@ impl AwsomeApp {
                 if let Some(state) = &mut self.state {
                     state.set_active_pane(PaneType::Explore);
                     state.close_side_drawer();
+                    state.mark_session_changed();
                 }
                 Task::none()
             }
@@ -125,56 +367,233 @@ impl AwsomeApp {
                 if let Some(state) = &mut self.state {
                     state.set_active_pane(PaneType::Projects);
                     state.close_side_drawer();
+                    state.mark_session_changed();
                 }
                 Task::none()
             }
             Message::ProfileSelected(profile) => {
+                self.mark_session_changed();
                 self.main_tab
                     .explore_tab
                     .set_selected_profile(profile.clone());
+                self.main_tab
+                    .explore_tab
+                    .set_session_expiry(session_expiry(&profile));
+                if let Some(task) =
+                    self.login_if_needed(&profile, Message::ProfileSelected(profile.clone()))
+                {
+                    return task;
+                }
                 let nearest_region = self
                     .state
                     .as_ref()
-                    .map(|s| s.get_nearest_region().to_string())
-                    .unwrap_or("us-east-1".to_string());
+                    .and_then(|s| s.get_nearest_region())
+                    .map(|r| r.to_string());
+                let region = default_region(self.main_tab.explore_tab.get_profile(&profile))
+                    .or(nearest_region);
+                if let Some(region) = &region {
+                    self.main_tab
+                        .explore_tab
+                        .set_selected_region(region.clone());
+                }
+                let region = region.unwrap_or(FALLBACK_REGION.to_string());
+
+                let identity = self
+                    .state
+                    .as_ref()
+                    .and_then(|s| s.get_caller_identity(&profile))
+                    .cloned();
+                let load_identity = match identity {
+                    Some(_) => Task::none(),
+                    None => self.load_caller_identity(profile.clone(), region.clone()),
+                };
+                self.main_tab.explore_tab.set_caller_identity(identity);
+
+                Task::batch([
+                    Task::perform(
+                        load_region_names(profile.clone(), region),
+                        move |res| match res {
+                            Ok(regions) => Message::RegionsLoaded(regions),
+                            Err(e) => {
+                                error!(profile, error = ?e, "Error while loading regions");
+                                Message::OperationFailed(
+                                    Box::new(Message::ProfileSelected(profile.clone())),
+                                    e,
+                                )
+                            }
+                        },
+                    ),
+                    load_identity,
+                ])
+            }
+            Message::CallerIdentityLoaded(profile, identity) => {
+                set_profile_account(&profile, &identity.account);
+                if let Some(state) = &mut self.state {
+                    state.set_caller_identity(profile.clone(), identity.clone());
+                }
+                if self.main_tab.explore_tab.get_selected_profile().as_ref() == Some(&profile) {
+                    self.main_tab
+                        .explore_tab
+                        .set_caller_identity(Some(identity.clone()));
+                }
+                self.main_tab
+                    .projects_tab
+                    .resources_table
+                    .set_caller_identity(&profile, identity);
+                self.update_protection();
+                Task::none()
+            }
+            Message::NearestRegionMeasured(region) => {
+                if let Some(state) = &mut self.state {
+                    state.set_nearest_region(region.clone());
+                }
+                // A profile picked before the measurement finished may still
+                // be waiting for a region.
+                let explore_tab = &mut self.main_tab.explore_tab;
+                if let (Some(region), Some(_), None) = (
+                    region,
+                    explore_tab.get_selected_profile(),
+                    explore_tab.get_selected_region(),
+                ) {
+                    explore_tab.set_selected_region(region);
+                }
+                Task::none()
+            }
+            Message::SsoLoginStarted(authorization) => {
+                let cache_dir = match sso_cache_dir() {
+                    Ok(cache_dir) => cache_dir,
+                    Err(e) => {
+                        error!(error = ?e, "Error while locating the SSO cache");
+                        return Task::done(Message::SsoLoginFinished(Err(e)));
+                    }
+                };
+                self.main_tab.set_sso_login(Some(*authorization.clone()));
+                let (task, handle) = Task::perform(
+                    complete_device_authorization(*authorization, cache_dir),
+                    Message::SsoLoginFinished,
+                )
+                .abortable();
+                if let Some(handle) = self.sso_login_handle.replace(handle) {
+                    handle.abort();
+                }
+                task
+            }
+            Message::SsoLoginFinished(res) => {
+                self.main_tab.set_sso_login(None);
+                self.sso_login_handle = None;
+                let retries = self.sso_logins.finish();
+                match res {
+                    Ok(()) => {
+                        // Configs that failed to resolve credentials before
+                        // the login would keep failing.
+                        client_pool::clear();
+                        Task::batch(retries.into_iter().map(Task::done))
+                    }
+                    Err(e) => {
+                        error!(error = ?e, "Error while logging in with SSO");
+                        Task::done(Message::ErrorOccurred(e))
+                    }
+                }
+            }
+            Message::SsoLoginCancelled => {
+                self.main_tab.set_sso_login(None);
+                self.sso_logins.finish();
+                if let Some(handle) = self.sso_login_handle.take() {
+                    handle.abort();
+                }
+                Task::none()
+            }
+            Message::MfaCodeChanged(code) => {
+                if let Some(prompt) = self.main_tab.get_mfa_prompt_mut() {
+                    prompt.set_code(code);
+                }
+                Task::none()
+            }
+            Message::MfaCodeSubmitted => {
+                let Some(prompt) = self.main_tab.get_mfa_prompt_mut() else {
+                    return Task::none();
+                };
+                prompt.set_submitting(true);
+                let profile = prompt.get_profile().to_string();
                 Task::perform(
-                    load_region_names(profile.clone(), nearest_region),
-                    |res| match res {
-                        Ok(regions) => Message::RegionsLoaded(regions),
-                        Err(e) => {
-                            eprintln!("Error while loading regions: {:?}", e);
-                            Message::ErrorOccurred(e)
-                        }
-                    },
+                    start_mfa_session(profile.clone(), prompt.get_code().to_string()),
+                    move |res| Message::MfaSessionStarted(profile.clone(), res),
                 )
             }
+            Message::MfaPromptCancelled => {
+                self.main_tab.set_mfa_prompt(None);
+                Task::none()
+            }
+            Message::MfaSessionStarted(profile, res) => {
+                let expiry = match res {
+                    Ok(expiry) => expiry,
+                    Err(e) => {
+                        warn!(profile, error = ?e, "Error while starting the MFA session");
+                        if let Some(prompt) = self.main_tab.get_mfa_prompt_mut() {
+                            prompt.set_error(e.to_string());
+                        }
+                        return Task::none();
+                    }
+                };
+                if self.main_tab.explore_tab.get_selected_profile().as_ref() == Some(&profile) {
+                    self.main_tab.explore_tab.set_session_expiry(expiry);
+                }
+                match self.main_tab.take_mfa_prompt() {
+                    Some(prompt) => Task::done(prompt.into_retry()),
+                    None => Task::none(),
+                }
+            }
+            Message::SessionClockTicked => Task::none(),
             Message::RegionsLoaded(regions) => {
                 self.main_tab.explore_tab.set_regions(regions);
                 Task::none()
             }
             Message::RegionSelected(region) => {
                 self.main_tab.explore_tab.set_selected_region(region);
+                self.mark_session_changed();
                 Task::none()
             }
-            Message::ServiceSelected(_index, service) => {
+            Message::ServiceSelected(index, service) => {
                 self.main_tab.explore_tab.set_selected_service(service);
+                self.mark_session_changed();
                 self.main_tab.explore_tab.set_resources(vec![]);
 
                 let Some(profile) = self.main_tab.explore_tab.get_selected_profile() else {
                     return Task::none();
                 };
+                if let Some(task) =
+                    self.login_if_needed(&profile, Message::ServiceSelected(index, service))
+                {
+                    return task;
+                }
                 let Some(region) = self.main_tab.explore_tab.get_selected_region() else {
                     return Task::none();
                 };
 
+                let all_regions = self.main_tab.explore_tab.is_all_regions();
+
                 self.main_tab.explore_tab.set_loading_resources(true);
-                Task::perform(list_resources(profile, region, service), |res| match res {
-                    Ok(resources) => Message::ResourcesLoaded(resources),
-                    Err(e) => {
-                        eprintln!("Error while loading resource: {:?}", e);
-                        Message::ErrorOccurred(e)
-                    }
-                })
+                Task::perform(
+                    registry().list(profile, region, service, all_regions),
+                    move |res| match res {
+                        Ok(resources) => Message::ResourcesLoaded(resources),
+                        Err(e) => {
+                            error!(%service, error = ?e, "Error while loading resources");
+                            Message::OperationFailed(
+                                Box::new(Message::ServiceSelected(index, service)),
+                                e,
+                            )
+                        }
+                    },
+                )
+            }
+            Message::AllRegionsToggled(all_regions) => {
+                self.main_tab.explore_tab.set_all_regions(all_regions);
+                self.mark_session_changed();
+                match self.main_tab.explore_tab.get_selected_service() {
+                    Some(service) => Task::done(Message::ServiceSelected(0, service)),
+                    None => Task::none(),
+                }
             }
             Message::ResourcesLoaded(resources) => {
                 self.main_tab.explore_tab.set_loading_resources(false);
@@ -182,11 +601,14 @@ impl AwsomeApp {
                 Task::none()
             }
             Message::ResourceSelected(_index, resource) => {
+                let task = registry().open(&resource);
                 self.main_tab.explore_tab.set_selected_resource(resource);
-                Task::none()
+                self.update_protection();
+                task
             }
             Message::ExploreTabPaneResized(event) => {
                 self.main_tab.explore_tab.resize_pane(event);
+                self.mark_session_changed();
                 Task::none()
             }
             Message::ProjectSelected(_index, project) => {
@@ -198,6 +620,17 @@ impl AwsomeApp {
                     .projects_tab
                     .project_service_selector
                     .set_selected_project(Some(project.clone()));
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                let project_id = project.get_id().to_string();
+                if let Err(e) = state
+                    .workspace
+                    .set_selected_project(Some(project_id.clone()), None)
+                {
+                    error!(project = project_id, error = ?e, "Error while setting selected project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
                 Task::none()
             }
             Message::ProjectServiceSelected(_index, project, service) => {
@@ -205,11 +638,29 @@ impl AwsomeApp {
                     .projects_tab
                     .project_service_selector
                     .set_selected_service(Some(project.clone()), Some(service.clone()));
+                if let Some(state) = &mut self.state {
+                    let project_id = project.get_id().to_string();
+                    if let Err(e) = state
+                        .workspace
+                        .set_selected_project(Some(project_id.clone()), Some(service.clone()))
+                    {
+                        error!(project = project_id, error = ?e, "Error while setting selected service");
+                        return Task::done(Message::ErrorOccurred(e));
+                    }
+                }
+                let project = self.bound_project(&project);
                 self.main_tab
                     .projects_tab
                     .resources_table
                     .set_selected_project_and_service(Some(project), Some(service));
-                Task::none()
+                Task::batch(
+                    self.main_tab
+                        .projects_tab
+                        .resources_table
+                        .get_profiles_without_identity()
+                        .into_iter()
+                        .map(|(profile, region)| self.load_caller_identity(profile, region)),
+                )
             }
             //Message::ProjectResourceSelected(_index, resource) => {
             //    self.main_tab
@@ -233,31 +684,41 @@ impl AwsomeApp {
                     .projects_tab
                     .resources_table
                     .confirm_column_resizing();
+                self.mark_session_changed();
                 Task::none()
             }
             Message::ResourcesTableCellClicked(col, row, res) => {
-                eprintln!("col: {}, row: {}, res: {:?}", col, row, res);
+                debug!(col, row, resource = ?res, "Resource clicked");
+                if let Some(task) = self.login_if_needed(
+                    &res.profile,
+                    Message::ResourcesTableCellClicked(col, row, res.clone()),
+                ) {
+                    return task;
+                }
                 let Some(ref mut state) = &mut self.state else {
                     return Task::none();
                 };
                 if let Err(e) = state.workspace.set_selected_resource(Some(res.clone())) {
-                    eprintln!("Error while setting selected resource: {:?}", e);
+                    error!(resource = ?res, error = ?e, "Error while setting selected resource");
                     return Task::done(Message::ErrorOccurred(e));
                 }
-                Task::perform(Resource::load(res), |res| match res {
+                let retry = Message::ResourcesTableCellClicked(col, row, res.clone());
+                Task::perform(registry().load(res), move |res| match res {
                     Ok(details) => Message::ResourceDetailsLoaded(details),
                     Err(e) => {
-                        eprintln!("Error while loading resource details: {:?}", e);
-                        Message::ErrorOccurred(e)
+                        error!(error = ?e, "Error while loading resource details");
+                        Message::OperationFailed(Box::new(retry.clone()), e)
                     }
                 })
             }
             Message::ResourceDetailsLoaded(details) => {
+                let task = registry().open(&details);
                 self.main_tab
                     .projects_tab
                     .resource_details
                     .set_resource(Some(details));
-                Task::none()
+                self.update_protection();
+                task
             }
 
             Message::AddProject => {
@@ -266,12 +727,10 @@ impl AwsomeApp {
                 };
                 let new_project = Project::new("New Project");
                 if let Err(e) = state.workspace.add_project(new_project.clone()) {
-                    eprintln!("Error while adding project: {:?}", e);
+                    error!(error = ?e, "Error while adding project");
                     return Task::done(Message::ErrorOccurred(e));
                 }
-                self.main_tab
-                    .projects_tab
-                    .set_projects(state.workspace.projects.clone());
+                self.update_projects();
                 self.main_tab
                     .projects_tab
                     .project_service_selector
@@ -286,42 +745,996 @@ impl AwsomeApp {
                     .set_resource(None);
                 Task::none()
             }
+            Message::ProjectRenameStarted(id) => self
+                .main_tab
+                .projects_tab
+                .project_service_selector
+                .start_renaming(&id),
+            Message::ProjectNameChanged(name) => {
+                self.main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .set_new_name(name);
+                Task::none()
+            }
+            Message::ProjectRenameSubmitted => {
+                let Some((id, name)) = self
+                    .main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .finish_renaming()
+                else {
+                    return Task::none();
+                };
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.rename_project(&id, &name) {
+                    error!(project = id, error = ?e, "Error while renaming project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                self.update_projects();
+                Task::none()
+            }
+            Message::ProjectRenameCancelled => {
+                self.main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .cancel_renaming();
+                Task::none()
+            }
+            Message::ProjectDeleteRequested(id) => {
+                self.main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .set_deleting(Some(id));
+                Task::none()
+            }
+            Message::ProjectEnvironmentSelected(id, environment) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.set_selected_environment(&id, &environment) {
+                    error!(project = id, environment, error = ?e, "Error while selecting environment");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                self.update_projects();
+                self.update_protection();
+                Task::batch(
+                    self.main_tab
+                        .projects_tab
+                        .resources_table
+                        .get_profiles_without_identity()
+                        .into_iter()
+                        .map(|(profile, region)| self.load_caller_identity(profile, region)),
+                )
+            }
+            Message::ProjectDeleteConfirmed(id) => {
+                self.main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .set_deleting(None);
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.delete_project(&id) {
+                    error!(project = id, error = ?e, "Error while deleting project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                self.update_projects();
+                Task::none()
+            }
+            Message::ProjectDeleteCancelled => {
+                self.main_tab
+                    .projects_tab
+                    .project_service_selector
+                    .set_deleting(None);
+                Task::none()
+            }
+            Message::ProjectMoved(id, index) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.move_project(&id, index) {
+                    error!(project = id, error = ?e, "Error while moving project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                self.update_projects();
+                Task::none()
+            }
+            Message::AddToProject(id, resource) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state
+                    .workspace
+                    .add_resource_to_project(&id, resource.clone())
+                {
+                    error!(project = id, ?resource, error = ?e, "Error while adding to project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                info!(project = id, ?resource, "Added to project");
+                self.update_projects();
+                Task::none()
+            }
+            Message::RemoveFromProject(id, resource) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                if let Err(e) = state.workspace.remove_resource_from_project(&id, &resource) {
+                    error!(project = id, ?resource, error = ?e, "Error while removing from project");
+                    return Task::done(Message::ErrorOccurred(e));
+                }
+                self.update_projects();
+                Task::none()
+            }
 
+            Message::S3BucketTabSelected(bucket, tab) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_tab(tab);
+                }
+                Task::none()
+            }
+            Message::S3BucketConfigLoaded(bucket, config) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_config(*config.clone());
+                }
+                Task::none()
+            }
+            Message::S3PrefixSelected(bucket, prefix) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_prefix(prefix.clone());
+                }
+                self.list_s3_entries(&bucket, &prefix)
+                    .then(move |res| match res {
+                        Ok(entries) => Task::done(Message::S3ObjectsLoaded(
+                            bucket.clone(),
+                            prefix.clone(),
+                            entries,
+                        )),
+                        Err(e) => {
+                            error!(bucket = %bucket.id, prefix, error = ?e, "Error while listing objects");
+                            Task::batch([
+                                Task::done(Message::S3ObjectsLoaded(
+                                    bucket.clone(),
+                                    prefix.clone(),
+                                    vec![],
+                                )),
+                                Task::done(Message::OperationFailed(
+                                    Box::new(Message::S3PrefixSelected(
+                                        bucket.clone(),
+                                        prefix.clone(),
+                                    )),
+                                    e,
+                                )),
+                            ])
+                        }
+                    })
+            }
+            Message::S3ObjectsLoaded(bucket, prefix, entries) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_entries(&prefix, entries.clone());
+                }
+                Task::none()
+            }
+            Message::S3ObjectSelected(bucket, key) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_selected_key(Some(key.clone()));
+                }
+                Task::perform(
+                    load_object_preview(bucket.clone(), key.clone()),
+                    move |res| match res {
+                        Ok(preview) => Message::S3ObjectPreviewLoaded(bucket.clone(), preview),
+                        Err(e) => {
+                            error!(bucket = %bucket.id, key, error = ?e, "Error while loading object preview");
+                            Message::OperationFailed(
+                                Box::new(Message::S3ObjectSelected(bucket.clone(), key.clone())),
+                                e,
+                            )
+                        }
+                    },
+                )
+            }
+            Message::S3ObjectPreviewLoaded(bucket, preview) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_preview(preview.clone());
+                }
+                Task::none()
+            }
+            Message::S3DownloadDirectoryChanged(bucket, directory) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_download_directory(directory.clone());
+                }
+                Task::none()
+            }
+            Message::S3DownloadRequested(bucket, key, version_id) => {
+                let Some(details) = self.s3_bucket_details(&bucket) else {
+                    return Task::none();
+                };
+                let destination =
+                    PathBuf::from(details.get_download_directory()).join(file_name_of_key(&key));
+                let transfer = Transfer::new(
+                    TransferKind::Download,
+                    format!(
+                        "s3://{}/{}{} -> {}",
+                        bucket.id,
+                        key,
+                        version_id
+                            .as_ref()
+                            .map(|v| format!(" ({})", v))
+                            .unwrap_or_default(),
+                        destination.display()
+                    ),
+                );
+                let stream = transfer.run(move |progress| {
+                    download_object(bucket, key, version_id, destination, progress)
+                });
+                self.start_transfer(transfer, stream)
+            }
+            Message::S3PresignMethodSelected(bucket, method) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_presign_method(method);
+                }
+                Task::none()
+            }
+            Message::S3PresignExpirySelected(bucket, expiry) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_presign_expiry(expiry);
+                }
+                Task::none()
+            }
+            Message::S3PresignContentTypeChanged(bucket, content_type) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_presign_content_type(content_type.clone());
+                }
+                Task::none()
+            }
+            Message::S3PresignRequested(bucket) => {
+                let Some(request) = self
+                    .s3_bucket_details(&bucket)
+                    .and_then(|d| d.get_presign_request())
+                else {
+                    return Task::none();
+                };
+                // A PUT URL lets anyone holding it write to the bucket.
+                if request.method == PresignMethod::Put {
+                    match self.protection_for(&bucket.profile) {
+                        None => {}
+                        Some(ProtectionMode::Confirm) => {
+                            for details in self.s3_bucket_details_mut(&bucket) {
+                                details.set_pending_action(Some(S3PendingAction::PresignPut(
+                                    request.clone(),
+                                )));
+                            }
+                            return Task::none();
+                        }
+                        Some(ProtectionMode::Unverified | ProtectionMode::ReadOnly) => {
+                            return Task::done(Message::ErrorOccurred(Error::Protected(
+                                bucket.profile,
+                            )));
+                        }
+                    }
+                }
+                presign(bucket, request)
+            }
+            Message::S3PresignedUrlGenerated(bucket, url) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_presigned_url(*url.clone());
+                }
+                iced::clipboard::write(url.url)
+            }
+            Message::S3UploadPathChanged(bucket, path) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_upload_path(path.clone());
+                }
+                Task::none()
+            }
+            Message::S3UploadContentTypeChanged(bucket, content_type) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_upload_content_type(content_type.clone());
+                }
+                Task::none()
+            }
+            Message::S3UploadStorageClassSelected(bucket, storage_class) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_upload_storage_class(storage_class.clone());
+                }
+                Task::none()
+            }
+            Message::S3UploadEncryptionSelected(bucket, encryption) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_upload_encryption(encryption);
+                }
+                Task::none()
+            }
+            Message::S3UploadKmsKeyIdChanged(bucket, key_id) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_upload_kms_key_id(key_id.clone());
+                }
+                Task::none()
+            }
+            Message::S3UploadRequested(bucket) => match self.protection_for(&bucket.profile) {
+                None => self.upload(bucket),
+                Some(ProtectionMode::Confirm) => {
+                    for details in self.s3_bucket_details_mut(&bucket) {
+                        details.set_pending_action(Some(S3PendingAction::Upload));
+                    }
+                    Task::none()
+                }
+                Some(ProtectionMode::Unverified | ProtectionMode::ReadOnly) => {
+                    Task::done(Message::ErrorOccurred(Error::Protected(bucket.profile)))
+                }
+            },
+            Message::S3ShowVersionsToggled(bucket, show) => {
+                let Some(prefix) = self
+                    .s3_bucket_details(&bucket)
+                    .map(|d| d.get_prefix().to_string())
+                else {
+                    return Task::none();
+                };
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_show_versions(show);
+                    details.set_pending_action(None);
+                }
+                Task::done(Message::S3PrefixSelected(bucket, prefix))
+            }
+            Message::S3VersionActionRequested(bucket, action) => {
+                if self
+                    .protection_for(&bucket.profile)
+                    .is_some_and(|p| p.blocks_changes())
+                {
+                    return Task::done(Message::ErrorOccurred(Error::Protected(bucket.profile)));
+                }
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_pending_action(Some(S3PendingAction::Version(action.clone())));
+                }
+                Task::none()
+            }
+            Message::S3ConfirmationInputChanged(bucket, input) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_confirmation_input(input.clone());
+                }
+                Task::none()
+            }
+            Message::S3ActionCancelled(bucket) => {
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    details.set_pending_action(None);
+                }
+                Task::none()
+            }
+            Message::S3ActionConfirmed(bucket) => {
+                // The protection may have changed since the action was
+                // requested, e.g. once the profile's account became known.
+                self.update_protection();
+                let mut action = None;
+                for details in self.s3_bucket_details_mut(&bucket) {
+                    action = details.take_confirmed_action().or(action);
+                }
+                let action = match action {
+                    None => return Task::none(),
+                    Some(S3PendingAction::Upload) => return self.upload(bucket),
+                    Some(S3PendingAction::PresignPut(request)) => return presign(bucket, request),
+                    Some(S3PendingAction::Version(action)) => action,
+                };
+                Task::perform(
+                    apply_version_action(bucket.clone(), action.clone()),
+                    move |res| match res {
+                        Ok(()) => Message::S3VersionActionCompleted(bucket.clone(), action.clone()),
+                        Err(e) => {
+                            error!(bucket = %bucket.id, %action, error = ?e, "Error while applying a version action");
+                            Message::OperationFailed(
+                                Box::new(Message::S3VersionActionRequested(
+                                    bucket.clone(),
+                                    action.clone(),
+                                )),
+                                e,
+                            )
+                        }
+                    },
+                )
+            }
+            Message::S3VersionActionCompleted(bucket, action) => {
+                info!(bucket = %bucket.id, %action, "Version action completed");
+                let Some(prefix) = self
+                    .s3_bucket_details(&bucket)
+                    .map(|d| d.get_prefix().to_string())
+                else {
+                    return Task::none();
+                };
+                Task::done(Message::S3PrefixSelected(bucket, prefix))
+            }
+
+            Message::TransferUpdated(id, event) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                state.update_transfer(&id, event.clone());
+                match event {
+                    TransferEvent::Failed(e) => {
+                        error!(transfer = ?id, error = ?e, "Error while transferring");
+                        Task::done(Message::ErrorOccurred(e))
+                    }
+                    _ => Task::none(),
+                }
+            }
+            Message::TransferCancelRequested(id) => {
+                if let Some(state) = &mut self.state {
+                    state.cancel_transfer(&id);
+                }
+                Task::none()
+            }
+            Message::TransferDismissed(id) => {
+                if let Some(state) = &mut self.state {
+                    state.dismiss_transfer(&id);
+                }
+                Task::none()
+            }
+
+            Message::CopyToClipboard(contents) => iced::clipboard::write(contents),
             Message::ErrorOccurred(e) => {
+                self.report_error(e, None);
+                Task::none()
+            }
+            Message::OperationFailed(retry, e) => {
+                self.report_error(e, Some(*retry));
+                Task::none()
+            }
+            Message::ErrorsPanelToggled => {
+                if let Some(state) = &mut self.state {
+                    state.set_errors_panel_open(!state.is_errors_panel_open());
+                }
+                Task::none()
+            }
+            Message::ErrorsCleared => {
+                if let Some(state) = &mut self.state {
+                    state.clear_error_reports();
+                }
+                Task::none()
+            }
+            Message::ErrorDetailsToggled(id) => {
                 let Some(state) = &mut self.state else {
                     return Task::none();
                 };
-                state.append_log(format!("Error: {:?}", e));
+                if let Some(report) = state.get_error_report_mut(&id) {
+                    report.expanded = !report.expanded;
+                    report.toast_closed = true;
+                    state.set_errors_panel_open(true);
+                }
                 Task::none()
             }
+            Message::ErrorToastClosed(id) => {
+                if let Some(report) = self
+                    .state
+                    .as_mut()
+                    .and_then(|s| s.get_error_report_mut(&id))
+                {
+                    report.toast_closed = true;
+                }
+                Task::none()
+            }
+            Message::ErrorDismissed(id) => {
+                if let Some(state) = &mut self.state {
+                    state.take_error_report(&id);
+                }
+                Task::none()
+            }
+            Message::ErrorRetried(id) => {
+                match self
+                    .state
+                    .as_mut()
+                    .and_then(|s| s.take_error_report(&id))
+                    .and_then(|r| r.retry)
+                {
+                    Some(retry) => Task::done(retry),
+                    None => Task::none(),
+                }
+            }
+            Message::ErrorRemedyRequested(id) => {
+                let Some(state) = &mut self.state else {
+                    return Task::none();
+                };
+                let Some(report) = state.get_error_report_mut(&id) else {
+                    return Task::none();
+                };
+                match report.remedy() {
+                    Some(Remedy::LogIn) => {
+                        let Some(profile) = report.profile().map(|p| p.to_string()) else {
+                            return Task::none();
+                        };
+                        let Some(report) = state.take_error_report(&id) else {
+                            return Task::none();
+                        };
+                        self.log_in(&profile, report.retry.unwrap_or(Message::DoNothing))
+                    }
+                    Some(Remedy::ShowMissingAction(_)) => {
+                        report.expanded = true;
+                        report.toast_closed = true;
+                        state.set_errors_panel_open(true);
+                        Task::none()
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::ToastsTicked => Task::none(),
 
             Message::DoNothing => Task::none(),
             Message::DoNothingOnToggle(_) => Task::none(),
-            //Message::LogReceiverReady(sender) => {
-            //    info!("[Message::LogReceiverReady] sender: {:?}", sender);
-            //    if let Some(state) = &mut self.state {
-            //        state.append_log("Log receiver ready".to_string());
-            //        state.set_log_sender(sender);
-            //    }
-            //    Task::none()
-            //}
-            //Message::LogReceived(log) => {
-            //    if let Some(state) = &mut self.state {
-            //        state.append_log(log);
-            //    }
-            //    Task::none()
-            //}
+
+            Message::LogsReceived(entries) => self.main_tab.footer.log_console.push(entries),
+            Message::LogLevelFilterChanged(level) => {
+                self.main_tab.footer.log_console.set_level(level);
+                Task::none()
+            }
+            Message::LogModuleFilterChanged(modules) => {
+                self.main_tab.footer.log_console.set_modules(modules);
+                Task::none()
+            }
+            Message::LogSearchChanged(search) => {
+                self.main_tab.footer.log_console.set_search(search);
+                Task::none()
+            }
+            Message::LogsPauseToggled => self.main_tab.footer.log_console.toggle_paused(),
+            Message::LogsAutoScrollToggled(auto_scroll) => self
+                .main_tab
+                .footer
+                .log_console
+                .set_auto_scroll(auto_scroll),
+            Message::LogFileToggled(enabled) => match logging::set_log_file(enabled) {
+                Ok(path) => {
+                    if let Some(path) = &path {
+                        info!(path = %path.display(), "Writing logs to file");
+                    }
+                    self.main_tab.footer.log_console.set_log_file(path);
+                    Task::none()
+                }
+                Err(e) => {
+                    error!(error = ?e, "Error while opening the log file");
+                    Task::done(Message::ErrorOccurred(e))
+                }
+            },
+            Message::LogsCleared => {
+                self.main_tab.footer.log_console.clear();
+                Task::none()
+            }
+        }
+    }
+
+    /// Asks the user to log in first if `profile` needs an SSO token or an MFA
+    /// code, and sends `retry` afterwards. `None` if the profile can be used
+    /// as is.
+    fn login_if_needed(&mut self, profile: &str, retry: Message) -> Option<Task<Message>> {
+        if let Some(task) = self.sso_login_if_needed(profile, retry.clone()) {
+            return Some(task);
+        }
+        let challenge = mfa_challenge(profile)?;
+        if self.main_tab.get_mfa_prompt_mut().is_none() {
+            self.main_tab
+                .set_mfa_prompt(Some(MfaPrompt::new(challenge, retry)));
+        }
+        Some(Task::none())
+    }
+
+    /// Logs in again even though the cached credentials look valid, e.g.
+    /// because AWS has rejected them.
+    fn log_in(&mut self, profile: &str, retry: Message) -> Task<Message> {
+        if let Some(task) = self.sso_login(profile, retry.clone(), true) {
+            return task;
+        }
+        forget_mfa_session(profile);
+        self.login_if_needed(profile, retry.clone())
+            .unwrap_or_else(|| Task::done(retry))
+    }
+
+    fn sso_login_if_needed(&mut self, profile: &str, retry: Message) -> Option<Task<Message>> {
+        self.sso_login(profile, retry, false)
+    }
+
+    fn sso_login(&mut self, profile: &str, retry: Message, force: bool) -> Option<Task<Message>> {
+        let config = load_shared_config().ok()?;
+        let login = SsoLogin::for_profile(&config, profile)?;
+        let cache_dir = sso_cache_dir().ok()?;
+        if !force && !needs_login(&login, &cache_dir, SystemTime::now()) {
+            return None;
+        }
+        // Only one login at a time. Requests made meanwhile are sent again
+        // once it has finished, and log in themselves if they still need to.
+        if !self.sso_logins.request(retry) {
+            return Some(Task::none());
+        }
+        let profile = profile.to_string();
+        Some(Task::perform(
+            start_device_authorization(login, cache_dir),
+            move |res| match res {
+                Ok(authorization) => Message::SsoLoginStarted(Box::new(authorization)),
+                Err(e) => {
+                    error!(profile, error = ?e, "Error while starting the SSO login");
+                    Message::SsoLoginFinished(Err(e))
+                }
+            },
+        ))
+    }
+
+    /// Shows the workspace's projects again after they have changed. The
+    /// resource details are cleared once the shown resource has left the
+    /// selected project.
+    fn update_projects(&mut self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let projects = state.workspace.projects.clone();
+        let selected_environments = state.workspace.appearance.selected_environments.clone();
+        self.main_tab.explore_tab.set_projects(projects.clone());
+
+        let project = self
+            .main_tab
+            .projects_tab
+            .project_service_selector
+            .get_selected_project()
+            .map(|p| self.bound_project(&p));
+        let projects_tab = &mut self.main_tab.projects_tab;
+        projects_tab.set_projects(projects);
+        projects_tab.set_selected_environments(selected_environments);
+        let service = projects_tab.project_service_selector.get_selected_service();
+        let shown = projects_tab
+            .resource_details
+            .get_resource()
+            .map(|r| r.descriptor.clone());
+        if let Some(shown) = shown {
+            let still_listed = project.as_ref().is_some_and(|p| {
+                // The loaded descriptor may point at another region, e.g.
+                // that of a bucket.
+                p.resources.iter().any(|r| {
+                    r.profile == shown.profile && r.service == shown.service && r.id == shown.id
+                })
+            });
+            if !still_listed {
+                projects_tab.resource_details.set_resource(None);
+            }
+        }
+        projects_tab
+            .resources_table
+            .set_selected_project_and_service(project, service);
+    }
+
+    /// The project as shown, bound to its selected environment.
+    fn bound_project(&self, project: &Project) -> Project {
+        let environment = self
+            .state
+            .as_ref()
+            .and_then(|s| s.workspace.get_selected_environment(project.get_id()));
+        project.bound_to(environment)
+    }
+
+    fn mark_session_changed(&mut self) {
+        if let Some(state) = &mut self.state {
+            state.mark_session_changed();
+        }
+    }
+
+    /// The session of the workspace, as the views currently are.
+    fn current_session(&self) -> Option<(PathBuf, Session)> {
+        let state = self.state.as_ref()?;
+        let path = state.workspace.get_path()?.to_path_buf();
+        let explore_tab = &self.main_tab.explore_tab;
+        let session = Session {
+            active_pane: state.get_active_pane(),
+            side_drawer_open: state.is_side_drawer_open(),
+            explore_split_ratios: explore_tab.get_split_ratios(),
+            explore: ExploreSession {
+                profile: explore_tab.get_selected_profile(),
+                region: explore_tab.get_selected_region(),
+                service: explore_tab.get_selected_service().cloned(),
+                all_regions: explore_tab.is_all_regions(),
+            },
+            column_widths: self
+                .main_tab
+                .projects_tab
+                .resources_table
+                .get_column_widths(),
+        };
+        Some((path, session))
+    }
+
+    fn save_session(&self) {
+        let Some((path, session)) = self.current_session() else {
+            return;
+        };
+        if let Err(e) = session.save(&path) {
+            warn!(error = ?e, "Error while saving session");
+        }
+    }
+
+    /// Puts the views back where they were left in the workspace. On
+    /// startup, a profile chosen by `AWS_PROFILE` wins over the saved one.
+    fn restore_session(&mut self, startup: bool) -> Task<Message> {
+        let Some(state) = &mut self.state else {
+            return Task::none();
+        };
+        let Some(path) = state.workspace.get_path() else {
+            return Task::none();
+        };
+        let session = Session::load(path);
+        state.set_active_pane(session.active_pane);
+        state.set_side_drawer_open(session.side_drawer_open);
+        self.main_tab
+            .explore_tab
+            .set_split_ratios(&session.explore_split_ratios);
+        self.main_tab
+            .projects_tab
+            .resources_table
+            .set_column_widths(session.column_widths);
+
+        let profile_from_env = startup
+            && std::env::var("AWS_PROFILE")
+                .is_ok_and(|p| self.main_tab.explore_tab.has_profile(&p));
+        let explore = match profile_from_env {
+            true => Task::none(),
+            false => self.restore_explore(session.explore),
+        };
+        Task::batch([explore, self.restore_project_selection()])
+    }
+
+    /// Selects the profile, region and service again, in this order, as
+    /// selecting a profile resets the region.
+    fn restore_explore(&mut self, explore: ExploreSession) -> Task<Message> {
+        let Some(profile) = explore
+            .profile
+            .filter(|p| self.main_tab.explore_tab.has_profile(p))
+        else {
+            return Task::none();
+        };
+        self.main_tab
+            .explore_tab
+            .set_all_regions(explore.all_regions);
+        let mut task = Task::done(Message::ProfileSelected(profile));
+        if let Some(region) = explore.region {
+            task = task.chain(Task::done(Message::RegionSelected(region)));
+        }
+        let service = explore
+            .service
+            .and_then(|s| registry().services().into_iter().find(|r| **r == s));
+        if let Some(service) = service {
+            task = task.chain(Task::done(Message::ServiceSelected(0, service)));
+        }
+        task
+    }
+
+    /// Selects the project, service and resource of the workspace's
+    /// `appearance` in the Projects tab, as far as they still exist.
+    fn restore_project_selection(&mut self) -> Task<Message> {
+        let Some(state) = &self.state else {
+            return Task::none();
+        };
+        let appearance = &state.workspace.appearance;
+        let projects = &state.workspace.projects;
+        let Some(index) = appearance
+            .selected_project_id
+            .as_ref()
+            .and_then(|id| projects.iter().position(|p| p.get_id() == id))
+        else {
+            return Task::none();
+        };
+        let project = projects[index].clone();
+        let service = appearance
+            .selected_service
+            .clone()
+            .filter(|s| project.resources.iter().any(|r| r.service == *s));
+        let resource = appearance.selected_resource.clone();
+
+        let selector = &mut self.main_tab.projects_tab.project_service_selector;
+        selector.open_project(project.get_id());
+        selector.set_selected_project(Some(project.clone()));
+        let Some(service) = service else {
+            return Task::none();
+        };
+        let bound = project.bound_to(state.workspace.get_selected_environment(project.get_id()));
+        let row = resource.as_ref().and_then(|resource| {
+            bound
+                .resources
+                .iter()
+                .filter(|r| r.service == service)
+                .position(|r| r == resource)
+        });
+        let mut task = Task::done(Message::ProjectServiceSelected(index, project, service));
+        if let (Some(row), Some(resource)) = (row, resource) {
+            task = task.chain(Task::done(Message::ResourcesTableCellClicked(
+                0, row, resource,
+            )));
+        }
+        task
+    }
+
+    fn report_error(&mut self, error: Error, retry: Option<Message>) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+        state.add_error_report(ErrorReport::new(error, retry));
+    }
+
+    fn protection_for(&self, profile: &str) -> Option<ProtectionMode> {
+        self.state.as_ref().and_then(|s| s.protection_for(profile))
+    }
+
+    /// Re-applies the workspace's protection settings to the details views.
+    fn update_protection(&mut self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let protection_for = |profile: &str| state.protection_for(profile);
+        self.main_tab
+            .explore_tab
+            .get_resource_details_mut()
+            .set_protection(protection_for);
+        self.main_tab
+            .projects_tab
+            .resource_details
+            .set_protection(protection_for);
+    }
+
+    fn upload(&mut self, bucket: ResourceDescriptor) -> Task<Message> {
+        let Some(details) = self.s3_bucket_details(&bucket) else {
+            return Task::none();
+        };
+        let local_path = PathBuf::from(details.get_upload_path());
+        let prefix = details.get_prefix().to_string();
+        let options = details.get_upload_options();
+        let transfer = Transfer::new(
+            TransferKind::Upload,
+            format!("{} -> s3://{}/{}", local_path.display(), bucket.id, prefix),
+        );
+        let stream = {
+            let bucket = bucket.clone();
+            let prefix = prefix.clone();
+            transfer.run(move |progress| upload_path(bucket, local_path, prefix, options, progress))
+        };
+        // Refresh the listing once the upload has finished, so that the
+        // new objects show up if the user is still looking at the prefix.
+        let refresh = self
+            .list_s3_entries(&bucket, &prefix)
+            .then(move |res| match res {
+                Ok(entries) => Task::done(Message::S3ObjectsLoaded(
+                    bucket.clone(),
+                    prefix.clone(),
+                    entries,
+                )),
+                Err(_) => Task::none(),
+            });
+        self.start_transfer(transfer, stream).chain(refresh)
+    }
+
+    fn load_caller_identity(&self, profile: String, region: String) -> Task<Message> {
+        Task::perform(
+            load_caller_identity(profile.clone(), region),
+            move |res| match res {
+                Ok(identity) => Message::CallerIdentityLoaded(profile.clone(), identity),
+                Err(e) => {
+                    error!(profile, error = ?e, "Error while loading the caller identity");
+                    Message::ErrorOccurred(e)
+                }
+            },
+        )
+    }
+
+    /// Lists `prefix` as plain objects or, if the bucket's details view has
+    /// versions turned on, as object versions.
+    fn list_s3_entries(
+        &self,
+        bucket: &ResourceDescriptor,
+        prefix: &str,
+    ) -> Task<Result<Vec<S3ObjectEntry>, Error>> {
+        let show_versions = self
+            .s3_bucket_details(bucket)
+            .is_some_and(|d| d.is_showing_versions());
+        if show_versions {
+            Task::perform(
+                list_object_versions(bucket.clone(), prefix.to_string()),
+                |res| res,
+            )
+        } else {
+            Task::perform(list_objects(bucket.clone(), prefix.to_string()), |res| res)
         }
     }
 
-    pub fn view(&self) -> iced::Element<Message> {
+    fn s3_bucket_details(&self, bucket: &ResourceDescriptor) -> Option<&S3BucketDetails> {
+        self.main_tab
+            .explore_tab
+            .get_resource_details()
+            .s3_bucket_details(bucket)
+            .or_else(|| {
+                self.main_tab
+                    .projects_tab
+                    .resource_details
+                    .s3_bucket_details(bucket)
+            })
+    }
+
+    fn s3_bucket_details_mut(&mut self, bucket: &ResourceDescriptor) -> Vec<&mut S3BucketDetails> {
+        [
+            self.main_tab.explore_tab.get_resource_details_mut(),
+            &mut self.main_tab.projects_tab.resource_details,
+        ]
+        .into_iter()
+        .filter_map(|details| details.s3_bucket_details_mut(bucket))
+        .collect()
+    }
+
+    fn start_transfer(
+        &mut self,
+        transfer: Transfer,
+        stream: impl iced::futures::Stream<Item = TransferEvent> + Send + 'static,
+    ) -> Task<Message> {
+        let Some(state) = &mut self.state else {
+            return Task::none();
+        };
+        let id = transfer.id.clone();
+        state.add_transfer(transfer);
+        Task::run(stream, move |event| {
+            Message::TransferUpdated(id.clone(), event)
+        })
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
         match &self.state {
             None => self.loading_view(),
             Some(state) => self.main_tab.view(state),
         }
     }
 
-    //pub fn subscription(&self) -> iced::Subscription<Message> {
-    //    iced::Subscription::run(log_receiver::start)
-    //}
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        let session_clock = match self.main_tab.explore_tab.get_session_expiry() {
+            Some(_) => iced::time::every(std::time::Duration::from_secs(1))
+                .map(|_| Message::SessionClockTicked),
+            None => iced::Subscription::none(),
+        };
+        let has_toasts = self
+            .state
+            .as_ref()
+            .is_some_and(|s| !s.get_visible_toasts(SystemTime::now()).is_empty());
+        let toast_clock = match has_toasts {
+            true => {
+                iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::ToastsTicked)
+            }
+            false => iced::Subscription::none(),
+        };
+        let has_workspace_file = self
+            .state
+            .as_ref()
+            .is_some_and(|s| s.workspace.get_path().is_some());
+        let workspace_file_clock = match has_workspace_file {
+            true => iced::time::every(std::time::Duration::from_secs(2))
+                .map(|_| Message::WorkspaceFileCheckTicked),
+            false => iced::Subscription::none(),
+        };
+        iced::Subscription::batch([
+            iced::Subscription::run(log_receiver::start),
+            session_clock,
+            toast_clock,
+            workspace_file_clock,
+        ])
+    }
+}
+
+/// The nearest region is only a fallback for profiles without a region, so
+/// there is nothing to measure when `AWS_REGION` covers them all.
+fn measure_nearest_region_if_needed() -> Task<Message> {
+    if default_region(None).is_some() {
+        return Task::none();
+    }
+    Task::perform(
+        measure_nearest_region(default_region_endpoints()),
+        Message::NearestRegionMeasured,
+    )
+}
+
+fn presign(bucket: ResourceDescriptor, request: PresignRequest) -> Task<Message> {
+    Task::perform(
+        presign_object_url(bucket.clone(), request),
+        move |res| match res {
+            Ok(url) => Message::S3PresignedUrlGenerated(bucket.clone(), Box::new(url)),
+            Err(e) => {
+                error!(bucket = %bucket.id, error = ?e, "Error while presigning a URL");
+                Message::OperationFailed(Box::new(Message::S3PresignRequested(bucket.clone())), e)
+            }
+        },
+    )
 }
@@ -1,58 +1,594 @@
-use std::convert::From;
+use std::{convert::From, path::PathBuf};
 
-use aws_smithy_runtime_api::client::result::SdkError;
+use aws_sdk_s3::{error::ProvideErrorMetadata, operation::RequestId};
+use aws_smithy_runtime_api::{client::result::SdkError, http::Response};
+
+use crate::models::workspace::ResourceDescriptor;
 
 #[derive(Clone, Debug)]
 pub enum Error {
-    Unknown,
     UnableToLoadAwsConfig,
+    /// The SDK failed before or without getting an answer from the service,
+    /// e.g. because of a timeout or a connection failure.
     AwsSdk(String),
+    /// The service answered with an error.
+    AwsService(AwsServiceError),
+    /// The service answered, but without what the app needs from the answer.
+    InvalidResponse {
+        operation: String,
+        message: String,
+    },
+    /// The app refused the request before sending it, e.g. because the
+    /// service would not accept it.
+    Unsupported(String),
+    Io(IoError),
+    Yaml(YamlError),
     InvalidResourceDescriptor,
     ResourceNotFound,
+    /// A mutating operation was refused because the profile is read-only.
+    Protected(String),
+    SsoLogin(String),
+    /// The profile's settings are missing or cannot be used as they are.
+    InvalidProfile {
+        profile: String,
+        message: String,
+    },
+    /// `source` occurred while performing `operation`, e.g.
+    /// `lambda:GetFunction`, on `resource` if there was one. Operations on no
+    /// particular resource, e.g. listings, only have a location.
+    Operation {
+        operation: String,
+        location: Option<(String, String)>,
+        resource: Option<Box<ResourceDescriptor>>,
+        source: Box<Error>,
+    },
+}
+
+/// What the user can do about an error, besides retrying.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Remedy {
+    /// The credentials of the profile have expired.
+    LogIn,
+    /// The IAM action that was denied.
+    ShowMissingAction(String),
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AwsServiceError {
+    /// e.g. `AccessDeniedException`.
+    pub code: Option<String>,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub status: Option<u16>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IoError {
+    pub path: Option<PathBuf>,
+    pub kind: std::io::ErrorKind,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct YamlError {
+    pub path: Option<PathBuf>,
+    /// 1-based, as shown by editors.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Error {
+    pub fn io(path: impl Into<PathBuf>, e: std::io::Error) -> Self {
+        Error::Io(IoError {
+            path: Some(path.into()),
+            kind: e.kind(),
+            message: e.to_string(),
+        })
+    }
+
+    pub fn yaml(path: impl Into<PathBuf>, e: serde_yaml::Error) -> Self {
+        match Error::from(e) {
+            Error::Yaml(yaml) => Error::Yaml(YamlError {
+                path: Some(path.into()),
+                ..yaml
+            }),
+            other => other,
+        }
+    }
+
+    /// Wraps the error with the operation and resource it occurred on.
+    pub fn during(self, operation: &str, resource: Option<&ResourceDescriptor>) -> Self {
+        Error::Operation {
+            operation: operation.to_string(),
+            location: resource.map(|r| (r.profile.clone(), r.region.clone())),
+            resource: resource.map(|r| Box::new(r.clone())),
+            source: Box::new(self),
+        }
+    }
+
+    /// Wraps the error with an operation on no particular resource.
+    pub fn during_in(self, operation: &str, profile: &str, region: &str) -> Self {
+        Error::Operation {
+            operation: operation.to_string(),
+            location: Some((profile.to_string(), region.to_string())),
+            resource: None,
+            source: Box::new(self),
+        }
+    }
+
+    /// The innermost error, without the operations wrapped around it.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Operation { source, .. } => source.root_cause(),
+            _ => self,
+        }
+    }
+
+    pub fn operation(&self) -> Option<&str> {
+        match self {
+            Error::Operation { operation, .. } => Some(operation),
+            _ => None,
+        }
+    }
+
+    /// The profile and region the error occurred in.
+    pub fn location(&self) -> Option<(&str, &str)> {
+        match self {
+            Error::Operation {
+                location: Some((profile, region)),
+                ..
+            } => Some((profile, region)),
+            Error::Operation { source, .. } => source.location(),
+            _ => None,
+        }
+    }
+
+    /// The error code of the service, e.g. `AccessDeniedException`.
+    pub fn code(&self) -> Option<&str> {
+        match self.root_cause() {
+            Error::AwsService(e) => e.code.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn is_access_denied(&self) -> bool {
+        self.code()
+            .is_some_and(|code| code.starts_with("AccessDenied") || code == "UnauthorizedOperation")
+    }
+
+    fn is_expired_credentials(&self) -> bool {
+        match self.root_cause() {
+            Error::AwsService(e) => matches!(
+                e.code.as_deref(),
+                Some(
+                    "ExpiredToken"
+                        | "ExpiredTokenException"
+                        | "RequestExpired"
+                        | "UnrecognizedClientException"
+                        | "InvalidClientTokenId"
+                )
+            ),
+            // Credential providers fail before anything is sent, so there
+            // is no code, only the provider's description.
+            Error::AwsSdk(msg) => {
+                let msg = msg.to_lowercase();
+                msg.contains("sso") && (msg.contains("expired") || msg.contains("invalid"))
+            }
+            _ => false,
+        }
+    }
+
+    /// The IAM action that was denied, from the service's message if it
+    /// names one, e.g. `... is not authorized to perform: lambda:GetFunction
+    /// on resource ...`, or else the operation itself.
+    pub fn missing_action(&self) -> Option<String> {
+        if !self.is_access_denied() {
+            return None;
+        }
+        let from_message = match self.root_cause() {
+            Error::AwsService(e) => e.message.as_deref().and_then(|m| {
+                let (_, rest) = m.split_once("perform: ")?;
+                rest.split_whitespace().next().map(str::to_string)
+            }),
+            _ => None,
+        };
+        from_message.or_else(|| self.operation().map(str::to_string))
+    }
+
+    pub fn remedy(&self) -> Option<Remedy> {
+        if self.is_expired_credentials() {
+            return Some(Remedy::LogIn);
+        }
+        self.missing_action().map(Remedy::ShowMissingAction)
+    }
+
+    /// A sentence for the user, without the raw details.
+    pub fn friendly_message(&self) -> String {
+        if self.is_expired_credentials() {
+            return "The credentials have expired. Log in again.".to_string();
+        }
+        if let Some(action) = self.missing_action() {
+            return format!("You are not allowed to perform {}.", action);
+        }
+        match self.root_cause() {
+            Error::AwsService(e) => match e.code.as_deref() {
+                Some(code) if code.contains("Throttl") || code == "TooManyRequestsException" => {
+                    "AWS is throttling the requests. Try again in a moment.".to_string()
+                }
+                Some(code) if code.contains("NotFound") || code.starts_with("NoSuch") => {
+                    "The resource does not exist anymore.".to_string()
+                }
+                _ => e
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| "AWS answered with an error.".to_string()),
+            },
+            Error::AwsSdk(_) => "AWS could not be reached.".to_string(),
+            Error::InvalidResponse { operation, .. } => {
+                format!("AWS answered {} in an unexpected way.", operation)
+            }
+            Error::Unsupported(message) => message.clone(),
+            Error::Io(e) => match &e.path {
+                Some(path) => format!("Unable to access {}: {}", path.display(), e.message),
+                None => e.message.clone(),
+            },
+            Error::Yaml(e) => match (&e.path, e.line, e.column) {
+                (Some(path), Some(line), Some(column)) => format!(
+                    "{} is not valid at line {}, column {}.",
+                    path.display(),
+                    line,
+                    column
+                ),
+                _ => format!("The file is not valid: {}", e.message),
+            },
+            e => e.to_string(),
+        }
+    }
+}
+
+/// `?` with context: `client.get_function()...send().await.during(...)?`.
+pub trait ResultExt<T> {
+    fn during(self, operation: &str, resource: Option<&ResourceDescriptor>) -> Result<T, Error>;
+
+    fn during_in(self, operation: &str, profile: &str, region: &str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for Result<T, E> {
+    fn during(self, operation: &str, resource: Option<&ResourceDescriptor>) -> Result<T, Error> {
+        self.map_err(|e| e.into().during(operation, resource))
+    }
+
+    fn during_in(self, operation: &str, profile: &str, region: &str) -> Result<T, Error> {
+        self.map_err(|e| e.into().during_in(operation, profile, region))
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::Unknown => write!(f, "Unknown error"),
             Error::UnableToLoadAwsConfig => write!(f, "Unable to load AWS config"),
             Error::AwsSdk(msg) => write!(f, "AWS SDK error: {}", msg),
+            Error::AwsService(e) => write!(f, "{}", e),
+            Error::InvalidResponse { operation, message } => {
+                write!(f, "Unexpected response to {}: {}", operation, message)
+            }
+            Error::Unsupported(message) => write!(f, "{}", message),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Yaml(e) => write!(f, "{}", e),
             Error::InvalidResourceDescriptor => write!(f, "Invalid resource descriptor"),
             Error::ResourceNotFound => write!(f, "Resource not found"),
+            Error::Protected(profile) => write!(f, "The profile {} is read-only", profile),
+            Error::SsoLogin(msg) => write!(f, "SSO login failed: {}", msg),
+            Error::InvalidProfile { profile, message } => {
+                write!(f, "The profile {} {}", profile, message)
+            }
+            Error::Operation {
+                operation,
+                location,
+                resource,
+                source,
+            } => match (resource, location) {
+                (Some(rd), _) => write!(
+                    f,
+                    "{} on {} ({}, {}) failed: {}",
+                    operation, rd, rd.profile, rd.region, source
+                ),
+                (None, Some((profile, region))) => write!(
+                    f,
+                    "{} ({}, {}) failed: {}",
+                    operation, profile, region, source
+                ),
+                (None, None) => write!(f, "{} failed: {}", operation, source),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AwsServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code.as_deref().unwrap_or("Service error"))?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        let details: Vec<String> = [
+            self.status.map(|s| format!("HTTP {}", s)),
+            self.request_id
+                .as_ref()
+                .map(|id| format!("request ID {}", id)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
         }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::fmt::Display for YamlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}", path.display())?;
+            if let (Some(line), Some(column)) = (self.line, self.column) {
+                write!(f, ":{}:{}", line, column)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
     }
 }
 
 impl From<std::io::Error> for Error {
-    fn from(_e: std::io::Error) -> Self {
-        Error::Unknown
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(IoError {
+            path: None,
+            kind: e.kind(),
+            message: e.to_string(),
+        })
     }
 }
 
 impl From<serde_yaml::Error> for Error {
-    fn from(_e: serde_yaml::Error) -> Self {
-        Error::Unknown
-    }
-}
-
-impl<E: std::fmt::Debug, R: std::fmt::Debug>
-    std::convert::From<aws_smithy_runtime_api::client::result::SdkError<E, R>> for Error
-{
-    fn from(e: SdkError<E, R>) -> Error {
-        let err_message = format!("{:?}", e);
-        eprintln!("{}", err_message);
-        match e {
-            SdkError::ConstructionFailure(_) => Error::AwsSdk(err_message),
-            SdkError::TimeoutError(_) => Error::AwsSdk(err_message),
-            SdkError::DispatchFailure(_) => Error::AwsSdk(err_message),
-            SdkError::ResponseError(_) => Error::AwsSdk(err_message),
-
-            SdkError::ServiceError(_service_err) => {
-                //let err = service_err.into_err();
-                Error::Unknown
-            }
+    fn from(e: serde_yaml::Error) -> Self {
+        let location = e.location();
+        Error::Yaml(YamlError {
+            path: None,
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl From<aws_sdk_s3::primitives::ByteStreamError> for Error {
+    fn from(e: aws_sdk_s3::primitives::ByteStreamError) -> Self {
+        Error::AwsSdk(format!("{:?}", e))
+    }
+}
+
+impl<E: ProvideErrorMetadata + std::fmt::Debug> From<SdkError<E, Response>> for Error {
+    fn from(e: SdkError<E, Response>) -> Error {
+        tracing::debug!(error = ?e, "AWS SDK error");
+        match &e {
+            SdkError::ServiceError(service_err) => Error::AwsService(AwsServiceError {
+                code: e.code().map(str::to_string),
+                message: e.message().map(str::to_string),
+                request_id: e.request_id().map(str::to_string),
+                status: Some(service_err.raw().status().as_u16()),
+            }),
+            _ => Error::AwsSdk(format!("{:?}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_lambda::{
+        error::ErrorMetadata, operation::get_function::GetFunctionError, primitives::SdkBody,
+    };
+    use aws_smithy_runtime_api::http::StatusCode;
 
-            _ => Error::Unknown,
+    use super::*;
+    use crate::providers::lambda;
+
+    fn function() -> ResourceDescriptor {
+        ResourceDescriptor {
+            profile: "dev".to_string(),
+            region: "eu-west-1".to_string(),
+            service: lambda::SERVICE.clone(),
+            id: "my-function".to_string(),
         }
     }
+
+    fn access_denied() -> SdkError<GetFunctionError, Response> {
+        let mut raw = Response::new(StatusCode::try_from(403).unwrap(), SdkBody::empty());
+        raw.headers_mut().insert("x-amzn-requestid", "req-1234");
+        let err = GetFunctionError::generic(
+            ErrorMetadata::builder()
+                .code("AccessDeniedException")
+                .message("not authorized to perform: lambda:GetFunction")
+                .build(),
+        );
+        SdkError::service_error(err, raw)
+    }
+
+    #[test]
+    fn io_errors_keep_the_kind_and_the_path() {
+        let e = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+
+        let Error::Io(io) = Error::from(e) else {
+            panic!("not an IO error");
+        };
+        assert_eq!(io.kind, std::io::ErrorKind::PermissionDenied);
+        assert_eq!(io.path, None);
+
+        let e = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let Error::Io(io) = Error::io("/tmp/workspace.yaml", e) else {
+            panic!("not an IO error");
+        };
+        assert_eq!(io.kind, std::io::ErrorKind::NotFound);
+        assert_eq!(io.path, Some(PathBuf::from("/tmp/workspace.yaml")));
+        assert!(io.to_string().starts_with("/tmp/workspace.yaml: "));
+    }
+
+    #[test]
+    fn yaml_errors_keep_the_location() {
+        let e = serde_yaml::from_str::<Vec<u32>>("- 1\n- 2\n- x\n").unwrap_err();
+
+        let Error::Yaml(yaml) = Error::yaml("workspace.yaml", e) else {
+            panic!("not a YAML error");
+        };
+        assert_eq!(yaml.path, Some(PathBuf::from("workspace.yaml")));
+        assert_eq!(yaml.line, Some(3));
+        assert_eq!(yaml.column, Some(3));
+        assert!(yaml.to_string().starts_with("workspace.yaml:3:3: "));
+    }
+
+    #[test]
+    fn service_errors_keep_code_message_request_id_and_status() {
+        let Error::AwsService(e) = Error::from(access_denied()) else {
+            panic!("not a service error");
+        };
+
+        assert_eq!(
+            e,
+            AwsServiceError {
+                code: Some("AccessDeniedException".to_string()),
+                message: Some("not authorized to perform: lambda:GetFunction".to_string()),
+                request_id: Some("req-1234".to_string()),
+                status: Some(403),
+            }
+        );
+        assert_eq!(
+            e.to_string(),
+            "AccessDeniedException: not authorized to perform: lambda:GetFunction \
+             (HTTP 403, request ID req-1234)"
+        );
+    }
+
+    #[test]
+    fn other_sdk_errors_keep_the_description() {
+        let e: SdkError<GetFunctionError, Response> = SdkError::timeout_error("too slow");
+
+        assert!(matches!(Error::from(e), Error::AwsSdk(msg) if msg.contains("too slow")));
+    }
+
+    #[test]
+    fn byte_stream_errors_keep_the_description() {
+        let e = aws_sdk_s3::primitives::ByteStreamError::from(std::io::Error::other("truncated"));
+
+        assert!(matches!(Error::from(e), Error::AwsSdk(msg) if msg.contains("truncated")));
+    }
+
+    #[test]
+    fn operations_keep_the_resource() {
+        let res: Result<(), _> = Err(access_denied());
+
+        let e = res
+            .during("lambda:GetFunction", Some(&function()))
+            .unwrap_err();
+
+        let Error::Operation {
+            operation,
+            resource,
+            source,
+            ..
+        } = &e
+        else {
+            panic!("not an operation error");
+        };
+        assert_eq!(operation, "lambda:GetFunction");
+        assert_eq!(resource.as_deref(), Some(&function()));
+        assert!(matches!(**source, Error::AwsService(_)));
+        assert!(e.to_string().starts_with(
+            "lambda:GetFunction on my-function (dev, eu-west-1) failed: AccessDenied"
+        ));
+    }
+
+    #[test]
+    fn listings_keep_the_location() {
+        let res: Result<(), _> = Err(access_denied());
+
+        let e = res
+            .during_in("lambda:ListFunctions", "dev", "eu-west-1")
+            .unwrap_err();
+
+        assert_eq!(e.operation(), Some("lambda:ListFunctions"));
+        assert_eq!(e.location(), Some(("dev", "eu-west-1")));
+        assert_eq!(e.code(), Some("AccessDeniedException"));
+    }
+
+    #[test]
+    fn access_denied_names_the_missing_action() {
+        let e = Error::from(access_denied()).during("lambda:GetFunction", Some(&function()));
+        assert_eq!(
+            e.remedy(),
+            Some(Remedy::ShowMissingAction("lambda:GetFunction".to_string()))
+        );
+
+        // S3 does not name the action, so the operation stands in for it.
+        let e = Error::AwsService(AwsServiceError {
+            code: Some("AccessDenied".to_string()),
+            message: Some("Access Denied".to_string()),
+            ..Default::default()
+        })
+        .during_in("s3:ListBuckets", "dev", "eu-west-1");
+        assert_eq!(e.missing_action().as_deref(), Some("s3:ListBuckets"));
+        assert_eq!(
+            e.friendly_message(),
+            "You are not allowed to perform s3:ListBuckets."
+        );
+    }
+
+    #[test]
+    fn expired_credentials_ask_for_a_login() {
+        let expired = Error::AwsService(AwsServiceError {
+            code: Some("ExpiredTokenException".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(expired.remedy(), Some(Remedy::LogIn));
+
+        let sso = Error::AwsSdk(
+            "The SSO session associated with this profile has expired or is otherwise invalid"
+                .to_string(),
+        )
+        .during_in("lambda:ListFunctions", "dev", "eu-west-1");
+        assert_eq!(sso.remedy(), Some(Remedy::LogIn));
+
+        assert_eq!(Error::AwsSdk("timeout".to_string()).remedy(), None);
+    }
+
+    #[test]
+    fn local_failures_are_not_sdk_errors() {
+        let unsupported = Error::Unsupported("Too large to copy".to_string()).during_in(
+            "s3:CopyObject",
+            "dev",
+            "eu-west-1",
+        );
+        assert_eq!(unsupported.friendly_message(), "Too large to copy");
+
+        let invalid = Error::InvalidResponse {
+            operation: "s3:CreateMultipartUpload".to_string(),
+            message: "no upload ID".to_string(),
+        };
+        assert_eq!(
+            invalid.friendly_message(),
+            "AWS answered s3:CreateMultipartUpload in an unexpected way."
+        );
+        assert_eq!(
+            invalid.to_string(),
+            "Unexpected response to s3:CreateMultipartUpload: no upload ID"
+        );
+    }
 }
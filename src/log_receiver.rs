@@ -1,26 +1,21 @@
 use iced::{
-    futures::{channel::mpsc, SinkExt, Stream},
+    futures::{SinkExt, Stream},
     stream,
 };
-use tracing::info;
 
-use crate::message::Message;
+use crate::{logging, message::Message};
+
+/// Entries that arrive together are delivered in one message, so that a
+/// burst of events does not redraw the console for each of them.
+const MAX_ENTRIES_PER_MESSAGE: usize = 256;
 
 pub fn start() -> impl Stream<Item = Message> {
     stream::channel(100, |mut output| async move {
-        // Create channel
-        let (tx, mut rx) = mpsc::channel(100);
-
-        // Send the sender back to application
-        info!("sending the sender back to app: {:?}", tx);
-        let _ = output.send(Message::LogReceiverReady(tx)).await;
-
         loop {
-            use iced_futures::futures::StreamExt;
-
-            info!("selecting next log");
-            let log = rx.select_next_some().await;
-            let _ = output.send(Message::LogReceived(log)).await;
+            let entries = logging::next_entries(MAX_ENTRIES_PER_MESSAGE).await;
+            if output.send(Message::LogsReceived(entries)).await.is_err() {
+                return;
+            }
         }
     })
 }
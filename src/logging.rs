@@ -0,0 +1,267 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::Targets,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer,
+};
+
+use crate::{
+    error::Error,
+    models::{
+        log_entry::LogEntry,
+        log_file::{log_dir, RotatingFile},
+    },
+};
+
+/// Entries beyond this that the log console has not taken yet are dropped,
+/// oldest first.
+const BUFFERED_ENTRIES: usize = 5000;
+
+/// Read by the log console's subscription.
+static BUFFER: Lazy<Arc<LogBuffer>> = Lazy::new(|| Arc::new(LogBuffer::new(BUFFERED_ENTRIES)));
+/// `None` unless logs are mirrored to a file.
+static LOG_FILE: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+/// Installs the layer that feeds the log console. Events are kept from DEBUG
+/// on for this crate and from INFO on for the SDK and everything else.
+pub fn init() {
+    let filter = Targets::new()
+        .with_default(Level::INFO)
+        .with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG);
+    let subscriber =
+        tracing_subscriber::registry().with(ConsoleLayer::new(BUFFER.clone()).with_filter(filter));
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("A tracing subscriber was already installed");
+    }
+}
+
+/// Waits for the entries recorded since the last call, at most `max` of them.
+pub async fn next_entries(max: usize) -> Vec<LogEntry> {
+    BUFFER.next_entries(max).await
+}
+
+/// Starts or stops mirroring the logs to a rotating file in the app's data
+/// directory. Returns the path of the file, if any.
+pub fn set_log_file(enabled: bool) -> Result<Option<PathBuf>, Error> {
+    let file = match enabled {
+        true => Some(RotatingFile::open(&log_dir()?)?),
+        false => None,
+    };
+    let path = file.as_ref().map(|f| f.path().to_path_buf());
+    *LOG_FILE.lock().unwrap() = file;
+    Ok(path)
+}
+
+/// A ring buffer between the layer and the log console, so that events are
+/// neither lost when the console's subscription restarts nor pile up while
+/// it does not keep up.
+struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    capacity: usize,
+    notify: Notify,
+}
+
+impl LogBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            capacity,
+            notify: Notify::new(),
+        }
+    }
+
+    fn push(&self, entry: LogEntry) {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+        self.notify.notify_one();
+    }
+
+    async fn next_entries(&self, max: usize) -> Vec<LogEntry> {
+        loop {
+            {
+                let mut entries = self.entries.lock().unwrap();
+                if !entries.is_empty() {
+                    let n = entries.len().min(max);
+                    return entries.drain(..n).collect();
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// The fields of a span, kept in its extensions while it exists.
+#[derive(Default)]
+struct SpanFields(Vec<(String, String)>);
+
+impl SpanFields {
+    fn set(&mut self, field: &Field, value: String) {
+        match self.0.iter_mut().find(|(name, _)| name == field.name()) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((field.name().to_string(), value)),
+        }
+    }
+
+    /// E.g. `upload{bucket=logs key=a}`.
+    fn label(&self, name: &str) -> String {
+        if self.0.is_empty() {
+            return name.to_string();
+        }
+        let fields: Vec<_> = self.0.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        format!("{}{{{}}}", name, fields.join(" "))
+    }
+}
+
+impl Visit for SpanFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.set(field, format!("{:?}", value));
+    }
+}
+
+struct ConsoleLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl ConsoleLayer {
+    fn new(buffer: Arc<LogBuffer>) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S> Layer<S> for ConsoleLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = SpanFields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SpanFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| match span.extensions().get::<SpanFields>() {
+                        Some(fields) => fields.label(span.name()),
+                        None => span.name().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let entry = LogEntry::from_event(event, spans);
+        // Warnings and errors still show up on the terminal, e.g. when the
+        // app fails before the console is shown.
+        if entry.level <= Level::WARN {
+            eprintln!("{}", entry.to_line());
+        }
+        if let Some(file) = LOG_FILE.lock().unwrap().as_mut() {
+            if let Err(e) = file.write_line(&entry.to_line()) {
+                eprintln!("Unable to write to {}: {:?}", file.path().display(), e);
+            }
+        }
+        self.buffer.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::{debug, info, info_span};
+
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn record(buffer: &Arc<LogBuffer>, f: impl FnOnce()) {
+        let filter = Targets::new()
+            .with_default(Level::INFO)
+            .with_target("awsome::logging::tests::verbose", Level::DEBUG);
+        let subscriber = tracing_subscriber::registry()
+            .with(ConsoleLayer::new(buffer.clone()).with_filter(filter));
+        tracing::subscriber::with_default(subscriber, f);
+    }
+
+    #[test]
+    fn records_spans_and_filters_by_target() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        record(&buffer, || {
+            let span = info_span!("upload", bucket = "logs", key = tracing::field::Empty);
+            let _entered = span.enter();
+            span.record("key", "a.txt");
+            info!(part = 1, "Uploaded");
+            debug!("Hidden");
+            debug!(target: "awsome::logging::tests::verbose", "Shown");
+        });
+
+        let entries = block_on(buffer.next_entries(10));
+        let texts: Vec<_> = entries.iter().map(|e| e.text()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "upload{bucket=logs key=a.txt}: Uploaded part=1",
+                "upload{bucket=logs key=a.txt}: Shown",
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_the_oldest_entries_beyond_the_capacity() {
+        let buffer = Arc::new(LogBuffer::new(3));
+        record(&buffer, || {
+            for i in 0..5 {
+                info!(i, "Event");
+            }
+        });
+
+        let first = block_on(buffer.next_entries(2));
+        let rest = block_on(buffer.next_entries(10));
+        let fields: Vec<_> = first
+            .iter()
+            .chain(&rest)
+            .map(|e| e.fields[0].1.clone())
+            .collect();
+        assert_eq!(fields, vec!["2", "3", "4"]);
+    }
+}
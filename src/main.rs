@@ -1,14 +1,18 @@
 mod app;
 mod error;
-//mod log_receiver;
+mod log_receiver;
+mod logging;
 mod message;
 mod models;
+mod providers;
 mod state;
 mod view;
 
 use crate::app::AwsomeApp;
 
 fn main() -> iced::Result {
+    logging::init();
+
     iced::application("Awsome", AwsomeApp::update, AwsomeApp::view)
         .settings(iced::Settings {
             default_font: iced::Font {
@@ -19,6 +23,6 @@ fn main() -> iced::Result {
             antialiasing: true,
             ..iced::Settings::default()
         })
-        //.subscription(AwsomeApp::subscription)
+        .subscription(AwsomeApp::subscription)
         .run_with(AwsomeApp::new)
 }
@@ -1,24 +1,63 @@
+use std::{path::PathBuf, time::SystemTime};
+
+use aws_sdk_s3::types::StorageClass;
+
 use crate::{
     error::Error,
     models::{
+        caller_identity::CallerIdentity,
+        error_report::ErrorReportId,
+        log_entry::LogEntry,
         resource::Resource,
+        s3_bucket_config::S3BucketConfig,
+        s3_object::{S3ObjectEntry, S3ObjectPreview, S3VersionAction, ServerSideEncryptionOption},
+        s3_presign::{PresignExpiry, PresignMethod, PresignedUrl},
         service::Service,
+        sso::SsoDeviceAuthorization,
+        transfer::{TransferEvent, TransferId},
         workspace::{Project, ResourceDescriptor, Workspace},
     },
+    view::{resource_details::s3_bucket_details::S3BucketTab, workspace_switcher::WorkspaceAction},
 };
 
 #[derive(Debug, Clone)]
 pub enum Message {
     FontLoaded(Result<(), iced::font::Error>),
     WorkspaceLoaded(Result<Workspace, Error>),
+    WorkspaceSelected(PathBuf),
+    WorkspaceInputStarted(WorkspaceAction),
+    WorkspaceInputChanged(String),
+    WorkspaceInputSubmitted,
+    WorkspaceInputCancelled,
+    WorkspaceFileCheckTicked,
+    WorkspaceChangedOnDisk(Result<Workspace, Error>),
+    WorkspaceReloadRequested,
+    WorkspaceMergeRequested,
+    WorkspaceExternalChangeDismissed,
     SideDrawerToggled,
     ActivateExploreTab,
     ActivateProjectsTab,
 
     ProfileSelected(String),
+    CallerIdentityLoaded(String, CallerIdentity),
+    NearestRegionMeasured(Option<String>),
+    /// The user has to approve the login. The boxed message is sent again
+    /// once the login has finished.
+    SsoLoginStarted(Box<SsoDeviceAuthorization>),
+    /// Also sent when the login could not be started.
+    SsoLoginFinished(Result<(), Error>),
+    SsoLoginCancelled,
+    MfaCodeChanged(String),
+    MfaCodeSubmitted,
+    MfaPromptCancelled,
+    /// The profile and when its session expires.
+    MfaSessionStarted(String, Result<Option<SystemTime>, Error>),
+    /// Refreshes the remaining session time.
+    SessionClockTicked,
     RegionsLoaded(Vec<String>),
     RegionSelected(String),
     ServiceSelected(usize, &'static Service),
+    AllRegionsToggled(bool),
     ResourcesLoaded(Vec<Resource>),
     ResourceSelected(usize, Resource),
 
@@ -34,11 +73,77 @@ pub enum Message {
     ResourceDetailsLoaded(Resource),
 
     AddProject,
+    ProjectRenameStarted(String),
+    ProjectNameChanged(String),
+    ProjectRenameSubmitted,
+    ProjectRenameCancelled,
+    ProjectDeleteRequested(String),
+    ProjectDeleteConfirmed(String),
+    ProjectDeleteCancelled,
+    /// Moves the project with the ID to the index.
+    ProjectMoved(String, usize),
+    /// The ID of the project and the name of the environment to show.
+    ProjectEnvironmentSelected(String, String),
+    AddToProject(String, ResourceDescriptor),
+    RemoveFromProject(String, ResourceDescriptor),
+
+    S3BucketTabSelected(ResourceDescriptor, S3BucketTab),
+    S3BucketConfigLoaded(ResourceDescriptor, Box<S3BucketConfig>),
+    S3PrefixSelected(ResourceDescriptor, String),
+    S3ObjectsLoaded(ResourceDescriptor, String, Vec<S3ObjectEntry>),
+    S3ObjectSelected(ResourceDescriptor, String),
+    S3ObjectPreviewLoaded(ResourceDescriptor, S3ObjectPreview),
+    S3DownloadDirectoryChanged(ResourceDescriptor, String),
+    /// Object key and, when downloading an older version, its version ID.
+    S3DownloadRequested(ResourceDescriptor, String, Option<String>),
+    S3PresignMethodSelected(ResourceDescriptor, PresignMethod),
+    S3PresignExpirySelected(ResourceDescriptor, PresignExpiry),
+    S3PresignContentTypeChanged(ResourceDescriptor, String),
+    S3PresignRequested(ResourceDescriptor),
+    S3PresignedUrlGenerated(ResourceDescriptor, Box<PresignedUrl>),
+    S3UploadPathChanged(ResourceDescriptor, String),
+    S3UploadContentTypeChanged(ResourceDescriptor, String),
+    S3UploadStorageClassSelected(ResourceDescriptor, StorageClass),
+    S3UploadEncryptionSelected(ResourceDescriptor, ServerSideEncryptionOption),
+    S3UploadKmsKeyIdChanged(ResourceDescriptor, String),
+    S3UploadRequested(ResourceDescriptor),
+    S3ShowVersionsToggled(ResourceDescriptor, bool),
+    S3VersionActionRequested(ResourceDescriptor, S3VersionAction),
+    /// Typed into the confirmation of an action on a protected bucket.
+    S3ConfirmationInputChanged(ResourceDescriptor, String),
+    S3ActionConfirmed(ResourceDescriptor),
+    S3ActionCancelled(ResourceDescriptor),
+    S3VersionActionCompleted(ResourceDescriptor, S3VersionAction),
+
+    TransferUpdated(TransferId, TransferEvent),
+    TransferCancelRequested(TransferId),
+    TransferDismissed(TransferId),
+
+    CopyToClipboard(String),
 
     ErrorOccurred(Error),
+    /// An operation failed that can be retried by sending the boxed message
+    /// again.
+    OperationFailed(Box<Message>, Error),
+    ErrorsPanelToggled,
+    ErrorsCleared,
+    ErrorDetailsToggled(ErrorReportId),
+    ErrorToastClosed(ErrorReportId),
+    ErrorDismissed(ErrorReportId),
+    ErrorRetried(ErrorReportId),
+    ErrorRemedyRequested(ErrorReportId),
+    /// Hides toasts that have timed out.
+    ToastsTicked,
+
+    LogsReceived(Vec<LogEntry>),
+    LogLevelFilterChanged(tracing::Level),
+    LogModuleFilterChanged(String),
+    LogSearchChanged(String),
+    LogsPauseToggled,
+    LogsAutoScrollToggled(bool),
+    LogFileToggled(bool),
+    LogsCleared,
 
-    //    LogReceiverReady(iced::futures::channel::mpsc::Sender<String>),
-    //    LogReceived(String),
     DoNothing,
     #[allow(dead_code)]
     DoNothingOnToggle(bool),
@@ -0,0 +1,91 @@
+use crate::{
+    error::Error,
+    models::client_pool::{iam_client, sts_client},
+};
+
+/// Who the credentials of a profile belong to.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CallerIdentity {
+    pub account: String,
+    /// The user or assumed-role ARN.
+    pub arn: String,
+    /// `None` if the account has no alias or we may not list it.
+    pub account_alias: Option<String>,
+}
+
+impl CallerIdentity {
+    /// The alias if there is one, as it is what people recognize, else the
+    /// account ID.
+    pub fn account_label(&self) -> &str {
+        self.account_alias.as_deref().unwrap_or(&self.account)
+    }
+}
+
+pub async fn load_caller_identity(
+    profile: String,
+    region: String,
+) -> Result<CallerIdentity, Error> {
+    let sts = sts_client(&profile, &region).await;
+    let iam = iam_client(&profile, &region).await;
+
+    let (identity, aliases) = iced::futures::join!(
+        sts.get_caller_identity().send(),
+        iam.list_account_aliases().send(),
+    );
+    let identity = identity?;
+
+    Ok(CallerIdentity {
+        account: identity.account().unwrap_or_default().to_string(),
+        arn: identity.arn().unwrap_or_default().to_string(),
+        account_alias: account_alias(&profile, aliases.map(|out| out.account_aliases().to_vec())),
+    })
+}
+
+/// An account has at most one alias. Not being allowed to list it is common
+/// and only means that the account ID is shown instead.
+fn account_alias<E: std::fmt::Debug>(
+    profile: &str,
+    aliases: Result<Vec<String>, E>,
+) -> Option<String> {
+    match aliases {
+        Ok(aliases) => aliases.into_iter().find(|a| !a.is_empty()),
+        Err(e) => {
+            tracing::debug!(profile, error = ?e, "Unable to list the account aliases");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(account_alias: Option<&str>) -> CallerIdentity {
+        CallerIdentity {
+            account: "123456789012".to_string(),
+            arn: "arn:aws:iam::123456789012:user/alice".to_string(),
+            account_alias: account_alias.map(|a| a.to_string()),
+        }
+    }
+
+    #[test]
+    fn labels_accounts_by_alias_or_id() {
+        assert_eq!(identity(Some("acme-prod")).account_label(), "acme-prod");
+        assert_eq!(identity(None).account_label(), "123456789012");
+    }
+
+    #[test]
+    fn falls_back_to_no_alias() {
+        let alias = |aliases: Result<Vec<&str>, &str>| {
+            account_alias(
+                "dev",
+                aliases.map(|a| a.into_iter().map(|a| a.to_string()).collect()),
+            )
+        };
+
+        assert_eq!(alias(Ok(vec!["acme-prod"])).as_deref(), Some("acme-prod"));
+        assert_eq!(alias(Ok(vec![])), None);
+        assert_eq!(alias(Ok(vec![""])), None);
+        assert_eq!(alias(Err("AccessDenied")), None);
+    }
+}
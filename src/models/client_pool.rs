@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use aws_config::SdkConfig;
+use once_cell::sync::Lazy;
+use tokio::sync::OnceCell;
+
+use crate::models::{
+    endpoint::s3_force_path_style,
+    mfa::session_expiry,
+    profile::sdk_config_loader,
+    request_policy::{limit_concurrency, RetryLogger},
+};
+
+/// SDK configs by profile, region and service, so that the shared config
+/// files are read and credentials are resolved once rather than per call.
+///
+/// Clients built from a cached config share its identity cache, which renews
+/// expiring credentials by itself. Only the credentials of MFA sessions are
+/// static, so configs built with them are replaced once the session changes.
+static POOL: Lazy<Mutex<HashMap<ClientKey, Arc<PooledConfig>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct ClientKey {
+    profile: String,
+    region: String,
+    /// The service's key in `[services ...]` sections, e.g. `s3`.
+    service: &'static str,
+}
+
+struct PooledConfig {
+    config: OnceCell<LoadedConfig>,
+    /// Expiry of the MFA session the config was built with.
+    session_expiry: Option<SystemTime>,
+}
+
+/// What is resolved from the shared config files when an entry is loaded.
+#[derive(Clone, Debug)]
+struct LoadedConfig {
+    sdk_config: SdkConfig,
+    /// Only resolved for S3 configs.
+    s3_force_path_style: bool,
+}
+
+impl PooledConfig {
+    fn is_current(&self, session_expiry: Option<SystemTime>) -> bool {
+        self.session_expiry == session_expiry
+            && session_expiry.is_none_or(|expiry| expiry > SystemTime::now())
+    }
+}
+
+/// Returns the cached config for the key, loading it on first use.
+/// Concurrent callers wait for the same load. The config carries the retry,
+/// timeout and concurrency settings of the workspace.
+pub async fn sdk_config(profile: &str, region: &str, service: &'static str) -> SdkConfig {
+    loaded_config(profile, region, service).await.sdk_config
+}
+
+async fn loaded_config(profile: &str, region: &str, service: &'static str) -> LoadedConfig {
+    let key = ClientKey {
+        profile: profile.to_string(),
+        region: region.to_string(),
+        service,
+    };
+    pooled_entry(key, session_expiry(profile))
+        .config
+        .get_or_init(|| async {
+            let cfg = sdk_config_loader(profile, region, service).load().await;
+            LoadedConfig {
+                sdk_config: limit_concurrency(profile, cfg),
+                s3_force_path_style: service == "s3" && s3_force_path_style(profile),
+            }
+        })
+        .await
+        .clone()
+}
+
+/// The entry of the key, replaced if it was built for another MFA session.
+fn pooled_entry(key: ClientKey, session_expiry: Option<SystemTime>) -> Arc<PooledConfig> {
+    let mut pool = POOL.lock().unwrap();
+    match pool.get(&key) {
+        Some(entry) if entry.is_current(session_expiry) => entry.clone(),
+        _ => {
+            let entry = Arc::new(PooledConfig {
+                config: OnceCell::new(),
+                session_expiry,
+            });
+            pool.insert(key, entry.clone());
+            entry
+        }
+    }
+}
+
+/// Drops the configs of a profile, e.g. after logging in again.
+pub fn invalidate_profile(profile: &str) {
+    POOL.lock().unwrap().retain(|key, _| key.profile != profile);
+}
+
+/// Drops every config, e.g. when the endpoint settings have changed.
+pub fn clear() {
+    POOL.lock().unwrap().clear();
+}
+
+/// Requests about a bucket must use a client for the bucket's own region, or
+/// S3 answers them with a redirect.
+pub async fn s3_client(profile: &str, region: &str) -> aws_sdk_s3::Client {
+    let (_, builder) = s3_config(profile, region).await;
+    aws_sdk_s3::Client::from_conf(builder.build())
+}
+
+/// The pooled config, and a builder of S3 clients from it.
+pub async fn s3_config(profile: &str, region: &str) -> (SdkConfig, aws_sdk_s3::config::Builder) {
+    let loaded = loaded_config(profile, region, "s3").await;
+    let builder = aws_sdk_s3::config::Builder::from(&loaded.sdk_config)
+        .force_path_style(loaded.s3_force_path_style)
+        .interceptor(RetryLogger::new(profile));
+    (loaded.sdk_config, builder)
+}
+
+pub async fn lambda_client(profile: &str, region: &str) -> aws_sdk_lambda::Client {
+    let cfg = sdk_config(profile, region, "lambda").await;
+    aws_sdk_lambda::Client::from_conf(
+        aws_sdk_lambda::config::Builder::from(&cfg)
+            .interceptor(RetryLogger::new(profile))
+            .build(),
+    )
+}
+
+pub async fn ec2_client(profile: &str, region: &str) -> aws_sdk_ec2::Client {
+    let cfg = sdk_config(profile, region, "ec2").await;
+    aws_sdk_ec2::Client::from_conf(
+        aws_sdk_ec2::config::Builder::from(&cfg)
+            .interceptor(RetryLogger::new(profile))
+            .build(),
+    )
+}
+
+pub async fn sts_client(profile: &str, region: &str) -> aws_sdk_sts::Client {
+    let cfg = sdk_config(profile, region, "sts").await;
+    aws_sdk_sts::Client::from_conf(
+        aws_sdk_sts::config::Builder::from(&cfg)
+            .interceptor(RetryLogger::new(profile))
+            .build(),
+    )
+}
+
+/// IAM is a global service. The SDK signs for and sends to the endpoint of
+/// the region's partition, whichever region the profile uses.
+pub async fn iam_client(profile: &str, region: &str) -> aws_sdk_iam::Client {
+    let cfg = sdk_config(profile, region, "iam").await;
+    aws_sdk_iam::Client::from_conf(
+        aws_sdk_iam::config::Builder::from(&cfg)
+            .interceptor(RetryLogger::new(profile))
+            .build(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+
+    /// The pool is global, so every test uses profiles of its own.
+    fn key(profile: &str, service: &'static str) -> ClientKey {
+        ClientKey {
+            profile: profile.to_string(),
+            region: "eu-west-1".to_string(),
+            service,
+        }
+    }
+
+    fn is_pooled(key: &ClientKey) -> bool {
+        POOL.lock().unwrap().contains_key(key)
+    }
+
+    #[test]
+    fn concurrent_callers_share_one_load() {
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            LoadedConfig {
+                sdk_config: SdkConfig::builder().build(),
+                s3_force_path_style: true,
+            }
+        };
+        let first = pooled_entry(key("shared", "s3"), None);
+        let second = pooled_entry(key("shared", "s3"), None);
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let (a, b) = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                iced::futures::join!(
+                    first.config.get_or_init(load),
+                    second.config.get_or_init(load)
+                )
+            });
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(a.s3_force_path_style && b.s3_force_path_style);
+    }
+
+    #[test]
+    fn invalidating_a_profile_keeps_the_others() {
+        pooled_entry(key("invalidated", "s3"), None);
+        pooled_entry(key("invalidated", "lambda"), None);
+        pooled_entry(key("kept", "s3"), None);
+
+        invalidate_profile("invalidated");
+
+        assert!(!is_pooled(&key("invalidated", "s3")));
+        assert!(!is_pooled(&key("invalidated", "lambda")));
+        assert!(is_pooled(&key("kept", "s3")));
+    }
+
+    #[test]
+    fn a_new_mfa_session_replaces_the_entry() {
+        let now = SystemTime::now();
+        let first_session = Some(now + Duration::from_secs(3600));
+        let second_session = Some(now + Duration::from_secs(7200));
+
+        let first = pooled_entry(key("mfa", "s3"), first_session);
+        assert!(Arc::ptr_eq(
+            &first,
+            &pooled_entry(key("mfa", "s3"), first_session)
+        ));
+
+        let second = pooled_entry(key("mfa", "s3"), second_session);
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&second, &pooled_entry(key("mfa", "s3"), None)));
+
+        // An expired session is not reused either.
+        let expired = Some(now - Duration::from_secs(1));
+        let stale = pooled_entry(key("mfa-expired", "s3"), expired);
+        assert!(!Arc::ptr_eq(
+            &stale,
+            &pooled_entry(key("mfa-expired", "s3"), expired)
+        ));
+    }
+}
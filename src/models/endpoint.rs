@@ -0,0 +1,230 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+use crate::models::{
+    client_pool,
+    profile::{load_shared_config, SharedConfig},
+    workspace::settings::Settings,
+};
+
+/// Set from the workspace settings, and applied to every profile.
+static WORKSPACE_ENDPOINT: Lazy<RwLock<WorkspaceEndpoint>> =
+    Lazy::new(|| RwLock::new(WorkspaceEndpoint::default()));
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WorkspaceEndpoint {
+    pub url: Option<String>,
+    pub s3_force_path_style: bool,
+}
+
+impl From<&Settings> for WorkspaceEndpoint {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            url: settings.endpoint_url.clone().filter(|u| !u.is_empty()),
+            s3_force_path_style: settings.s3_force_path_style,
+        }
+    }
+}
+
+/// Must be called whenever a workspace is loaded.
+pub fn set_workspace_endpoint(endpoint: WorkspaceEndpoint) {
+    let mut current = WORKSPACE_ENDPOINT.write().unwrap();
+    if *current != endpoint {
+        *current = endpoint;
+        // Pooled configs were built for the previous endpoint.
+        client_pool::clear();
+    }
+}
+
+fn workspace_endpoint() -> WorkspaceEndpoint {
+    WORKSPACE_ENDPOINT.read().unwrap().clone()
+}
+
+/// The endpoint to send requests for `service` to, if anything overrides the
+/// default AWS endpoint. `service` is the service's key in `[services ...]`
+/// sections, e.g. `s3` or `lambda`.
+pub fn endpoint_url(config: &SharedConfig, profile: &str, service: &str) -> Option<String> {
+    resolve_endpoint_url(config, profile, service, &workspace_endpoint(), |name| {
+        std::env::var(name).ok()
+    })
+}
+
+/// Whether buckets are addressed as `endpoint/bucket` rather than
+/// `bucket.endpoint`. Most emulators only support the former.
+pub fn s3_force_path_style(profile: &str) -> bool {
+    let config = load_shared_config().unwrap_or_default();
+    resolve_s3_force_path_style(&config, profile, &workspace_endpoint())
+}
+
+/// The workspace override comes first, as it is the most specific to what
+/// the user is doing right now. The rest follows the precedence of the SDKs:
+/// service-specific environment variable, global environment variable,
+/// `[services ...]` section, and the profile's own `endpoint_url`.
+fn resolve_endpoint_url(
+    config: &SharedConfig,
+    profile: &str,
+    service: &str,
+    workspace: &WorkspaceEndpoint,
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    if let Some(url) = &workspace.url {
+        return Some(url.clone());
+    }
+
+    let profile = config.profile(profile);
+    let ignored = env("AWS_IGNORE_CONFIGURED_ENDPOINT_URLS")
+        .or_else(|| {
+            profile
+                .and_then(|p| p.properties.get("ignore_configured_endpoint_urls"))
+                .map(|p| p.value.clone())
+        })
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    if ignored {
+        return None;
+    }
+
+    let service_env_var = format!(
+        "AWS_ENDPOINT_URL_{}",
+        service.to_uppercase().replace([' ', '-'], "_")
+    );
+    env(&service_env_var)
+        .or_else(|| env("AWS_ENDPOINT_URL"))
+        .filter(|url| !url.is_empty())
+        .or_else(|| {
+            let services = profile?.services.as_ref()?;
+            let url = config.services(services)?.endpoint_url(service)?;
+            Some(url.to_string())
+        })
+        .or_else(|| profile?.endpoint_url.clone())
+}
+
+fn resolve_s3_force_path_style(
+    config: &SharedConfig,
+    profile: &str,
+    workspace: &WorkspaceEndpoint,
+) -> bool {
+    workspace.s3_force_path_style
+        || config
+            .profile(profile)
+            .and_then(|p| p.properties.get("s3"))
+            .and_then(|s3| s3.sub_properties.get("addressing_style"))
+            .is_some_and(|style| style == "path")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config() -> SharedConfig {
+        let fixture = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/aws/services_config"
+        ))
+        .unwrap();
+        SharedConfig::parse(&fixture, "")
+    }
+
+    fn resolve(
+        service: &str,
+        workspace: &WorkspaceEndpoint,
+        env: &[(&str, &str)],
+    ) -> Option<String> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        resolve_endpoint_url(&config(), "local", service, workspace, |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    #[test]
+    fn services_section_comes_before_profile_endpoint() {
+        let workspace = WorkspaceEndpoint::default();
+
+        assert_eq!(
+            resolve("s3", &workspace, &[]).as_deref(),
+            Some("http://localhost:9000")
+        );
+        assert_eq!(
+            resolve("lambda", &workspace, &[]).as_deref(),
+            Some("http://localhost:4566")
+        );
+    }
+
+    #[test]
+    fn environment_comes_before_the_config_file() {
+        let workspace = WorkspaceEndpoint::default();
+        let env = [
+            ("AWS_ENDPOINT_URL", "http://global:1"),
+            ("AWS_ENDPOINT_URL_S3", "http://s3:2"),
+        ];
+
+        assert_eq!(
+            resolve("s3", &workspace, &env).as_deref(),
+            Some("http://s3:2")
+        );
+        assert_eq!(
+            resolve("lambda", &workspace, &env).as_deref(),
+            Some("http://global:1")
+        );
+    }
+
+    #[test]
+    fn workspace_override_comes_first() {
+        let workspace = WorkspaceEndpoint {
+            url: Some("http://localstack:4566".to_string()),
+            s3_force_path_style: false,
+        };
+        let env = [
+            ("AWS_ENDPOINT_URL_S3", "http://s3:2"),
+            ("AWS_IGNORE_CONFIGURED_ENDPOINT_URLS", "true"),
+        ];
+
+        assert_eq!(
+            resolve("s3", &workspace, &env).as_deref(),
+            Some("http://localstack:4566")
+        );
+    }
+
+    #[test]
+    fn configured_endpoints_may_be_ignored() {
+        let workspace = WorkspaceEndpoint::default();
+        let env = [("AWS_IGNORE_CONFIGURED_ENDPOINT_URLS", "true")];
+
+        assert_eq!(resolve("s3", &workspace, &env), None);
+    }
+
+    #[test]
+    fn unknown_profiles_have_no_endpoint() {
+        let endpoint = resolve_endpoint_url(
+            &config(),
+            "missing",
+            "s3",
+            &WorkspaceEndpoint::default(),
+            |_| None,
+        );
+
+        assert_eq!(endpoint, None);
+    }
+
+    #[test]
+    fn path_style_comes_from_the_profile_or_the_workspace() {
+        let mut workspace = WorkspaceEndpoint::default();
+        // The profile's own `s3` settings ask for virtual-hosted addressing.
+        assert!(!resolve_s3_force_path_style(&config(), "local", &workspace));
+
+        workspace.s3_force_path_style = true;
+        assert!(resolve_s3_force_path_style(&config(), "local", &workspace));
+
+        let config = SharedConfig::parse("[profile p]\ns3 =\n  addressing_style = path\n", "");
+        assert!(resolve_s3_force_path_style(
+            &config,
+            "p",
+            &WorkspaceEndpoint::default()
+        ));
+    }
+}
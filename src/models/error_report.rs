@@ -0,0 +1,91 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    error::{Error, Remedy},
+    message::Message,
+};
+
+/// How long a new error is shown as a toast.
+pub const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ErrorReportId(String);
+
+impl ErrorReportId {
+    pub fn new() -> Self {
+        Self(ulid::Ulid::new().to_string())
+    }
+}
+
+/// An error as listed in the errors panel.
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    pub id: ErrorReportId,
+    pub time: SystemTime,
+    pub error: Error,
+    /// Sent again to retry the failed operation.
+    pub retry: Option<Message>,
+    /// Whether the raw details are shown.
+    pub expanded: bool,
+    /// Whether the toast was closed before it timed out.
+    pub toast_closed: bool,
+}
+
+impl ErrorReport {
+    pub fn new(error: Error, retry: Option<Message>) -> Self {
+        Self {
+            id: ErrorReportId::new(),
+            time: SystemTime::now(),
+            error,
+            retry,
+            expanded: false,
+            toast_closed: false,
+        }
+    }
+
+    pub fn is_toast_visible(&self, now: SystemTime) -> bool {
+        !self.toast_closed && now < self.time + TOAST_DURATION
+    }
+
+    /// `profile (region)`, if the error occurred in one.
+    pub fn location_label(&self) -> Option<String> {
+        self.error
+            .location()
+            .map(|(profile, region)| format!("{} ({})", profile, region))
+    }
+
+    pub fn remedy(&self) -> Option<Remedy> {
+        self.error.remedy()
+    }
+
+    /// The profile to log in to for the `LogIn` remedy.
+    pub fn profile(&self) -> Option<&str> {
+        self.error.location().map(|(profile, _)| profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toasts_time_out_or_close() {
+        let mut report = ErrorReport::new(Error::ResourceNotFound, None);
+        let now = report.time;
+
+        assert!(report.is_toast_visible(now));
+        assert!(!report.is_toast_visible(now + TOAST_DURATION));
+
+        report.toast_closed = true;
+        assert!(!report.is_toast_visible(now));
+    }
+
+    #[test]
+    fn reports_carry_the_location_of_the_operation() {
+        let error = Error::ResourceNotFound.during_in("lambda:ListFunctions", "dev", "eu-west-1");
+        let report = ErrorReport::new(error, Some(Message::DoNothing));
+
+        assert_eq!(report.location_label().as_deref(), Some("dev (eu-west-1)"));
+        assert_eq!(report.profile(), Some("dev"));
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    error::{Error, ResultExt},
+    models::{client_pool::lambda_client, service::Service, workspace::ResourceDescriptor},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LambdaFunctionInfo(pub aws_sdk_lambda::types::FunctionConfiguration);
+
+pub async fn load_lambda_function(rd: &ResourceDescriptor) -> Result<LambdaFunctionInfo, Error> {
+    let client = lambda_client(&rd.profile, &rd.region).await;
+    let out = client
+        .get_function()
+        .function_name(rd.id.clone())
+        .send()
+        .await
+        .during("lambda:GetFunction", Some(rd))?;
+
+    let Some(function_config) = out.configuration else {
+        return Err(Error::ResourceNotFound);
+    };
+    Ok(LambdaFunctionInfo(function_config))
+}
+
+/// Functions with their descriptors, sorted by name.
+pub async fn list_lambda_functions(
+    profile: &str,
+    region: &str,
+    service: &Service,
+) -> Result<Vec<(ResourceDescriptor, LambdaFunctionInfo)>, Error> {
+    let client = lambda_client(profile, region).await;
+    let result: Result<Vec<_>, _> = client
+        .list_functions()
+        .into_paginator()
+        .items()
+        .send()
+        .collect()
+        .await;
+
+    let mut functions = result.during_in("lambda:ListFunctions", profile, region)?;
+    functions.sort_by_key(|f| f.function_name.clone());
+    Ok(functions
+        .into_iter()
+        .map(|f| {
+            let descriptor = ResourceDescriptor {
+                profile: profile.to_string(),
+                region: region.to_string(),
+                service: service.clone(),
+                id: f.function_name.clone().unwrap_or_default(),
+            };
+            (descriptor, LambdaFunctionInfo(f))
+        })
+        .collect())
+}
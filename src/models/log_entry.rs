@@ -0,0 +1,227 @@
+use std::time::SystemTime;
+
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level,
+};
+
+/// A `tracing` event as shown in the log console.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub time: SystemTime,
+    pub level: Level,
+    /// The module the event was emitted from, e.g. `awsome::app`.
+    pub target: String,
+    pub message: String,
+    /// The structured fields of the event besides the message.
+    pub fields: Vec<(String, String)>,
+    /// The spans the event was emitted in, outermost first, e.g.
+    /// `upload{bucket=logs}`.
+    pub spans: Vec<String>,
+}
+
+impl LogEntry {
+    pub fn from_event(event: &Event<'_>, spans: Vec<String>) -> Self {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        Self {
+            time: SystemTime::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+            spans,
+        }
+    }
+
+    /// The spans, the message and the fields, e.g. `upload{bucket=logs}:
+    /// Error while listing objects prefix=a/ error=...`.
+    pub fn text(&self) -> String {
+        let mut text = self.message.clone();
+        for (name, value) in &self.fields {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&format!("{}={}", name, value));
+        }
+        match self.spans.is_empty() {
+            true => text,
+            false => format!("{}: {}", self.spans.join(":"), text),
+        }
+    }
+
+    /// One line of the log file.
+    pub fn to_line(&self) -> String {
+        let time = DateTime::from(self.time)
+            .fmt(DateTimeFormat::DateTime)
+            .unwrap_or_default();
+        format!(
+            "{} {:>5} {}: {}",
+            time,
+            self.level,
+            self.target,
+            self.text()
+        )
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &Field, value: String) {
+        match field.name() {
+            "message" => self.message = value,
+            name => self.fields.push((name.to_string(), value)),
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.push(field, format!("{:?}", value));
+    }
+}
+
+/// What the log console shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogFilter {
+    /// The most verbose level shown.
+    pub level: Level,
+    /// Comma-separated parts of module paths, e.g. `awsome::models, -s3`.
+    /// Modules that contain one of them are shown, and those that contain
+    /// one prefixed with `-` are hidden.
+    pub modules: String,
+    /// Shown entries contain this, ignoring case.
+    pub search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            modules: String::new(),
+            search: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        entry.level <= self.level && self.matches_module(&entry.target) && self.matches_text(entry)
+    }
+
+    fn matches_module(&self, target: &str) -> bool {
+        let mut included = None;
+        for module in self.modules.split(',').map(str::trim) {
+            match module.strip_prefix('-') {
+                Some(excluded) if !excluded.is_empty() && target.contains(excluded) => {
+                    return false;
+                }
+                Some(_) => {}
+                None if module.is_empty() => {}
+                None => included = Some(included.unwrap_or(false) || target.contains(module)),
+            }
+        }
+        included.unwrap_or(true)
+    }
+
+    fn matches_text(&self, entry: &LogEntry) -> bool {
+        if self.search.is_empty() {
+            return true;
+        }
+        entry
+            .text()
+            .to_lowercase()
+            .contains(&self.search.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, target: &str, message: &str) -> LogEntry {
+        LogEntry {
+            time: SystemTime::UNIX_EPOCH,
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+            fields: vec![("bucket".to_string(), "Logs".to_string())],
+            spans: vec![],
+        }
+    }
+
+    #[test]
+    fn formats_the_fields_after_the_message() {
+        let e = entry(Level::WARN, "awsome::app", "Error while listing objects");
+
+        assert_eq!(e.text(), "Error while listing objects bucket=Logs");
+        assert_eq!(
+            e.to_line(),
+            "1970-01-01T00:00:00Z  WARN awsome::app: Error while listing objects bucket=Logs"
+        );
+
+        let in_spans = LogEntry {
+            spans: vec!["upload{key=a}".to_string(), "part".to_string()],
+            ..e
+        };
+        assert_eq!(
+            in_spans.text(),
+            "upload{key=a}:part: Error while listing objects bucket=Logs"
+        );
+    }
+
+    #[test]
+    fn filters_by_level() {
+        let filter = LogFilter::default();
+
+        assert!(filter.matches(&entry(Level::ERROR, "awsome::app", "")));
+        assert!(filter.matches(&entry(Level::INFO, "awsome::app", "")));
+        assert!(!filter.matches(&entry(Level::DEBUG, "awsome::app", "")));
+    }
+
+    #[test]
+    fn filters_by_module() {
+        let filter = LogFilter {
+            modules: "awsome::models, aws_config, -models::region".to_string(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&entry(Level::INFO, "awsome::models::sso", "")));
+        assert!(filter.matches(&entry(Level::INFO, "aws_config::profile", "")));
+        assert!(!filter.matches(&entry(Level::INFO, "awsome::models::region", "")));
+        assert!(!filter.matches(&entry(Level::INFO, "awsome::app", "")));
+
+        let only_excluded = LogFilter {
+            modules: "-aws_config".to_string(),
+            ..Default::default()
+        };
+        assert!(only_excluded.matches(&entry(Level::INFO, "awsome::app", "")));
+        assert!(!only_excluded.matches(&entry(Level::INFO, "aws_config::profile", "")));
+    }
+
+    #[test]
+    fn searches_the_message_and_fields_ignoring_case() {
+        let filter = LogFilter {
+            search: "LOGS".to_string(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&entry(Level::INFO, "awsome::app", "Listing objects")));
+
+        let filter = LogFilter {
+            search: "regions".to_string(),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&entry(Level::INFO, "awsome::app", "Listing objects")));
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, IoError};
+
+const LOG_FILE_NAME: &str = "awsome.log";
+/// The current file is rotated once it would grow beyond this.
+const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Rotated files kept besides the current one, as `awsome.log.1` (newest)
+/// to `awsome.log.N`.
+const ROTATED_LOG_FILES: usize = 5;
+
+/// The platform's data directory of the app, e.g.
+/// `~/.local/share/awsome/logs` on Linux.
+pub fn log_dir() -> Result<PathBuf, Error> {
+    let Some(project_dirs) = directories::ProjectDirs::from("", "", "awsome") else {
+        return Err(Error::Io(IoError {
+            path: None,
+            kind: io::ErrorKind::NotFound,
+            message: "Unable to determine the data directory".to_string(),
+        }));
+    };
+    Ok(project_dirs.data_dir().join("logs"))
+}
+
+/// Appends lines to `awsome.log` and moves it aside once it gets too large.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    pub fn open(dir: &Path) -> Result<Self, Error> {
+        Self::open_with_limits(dir, MAX_LOG_FILE_BYTES, ROTATED_LOG_FILES)
+    }
+
+    fn open_with_limits(dir: &Path, max_bytes: u64, keep: usize) -> Result<Self, Error> {
+        fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+        let path = dir.join(LOG_FILE_NAME);
+        let file = open_for_append(&path).map_err(|e| Error::io(&path, e))?;
+        let size = file.metadata().map_err(|e| Error::io(&path, e))?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        if self.keep > 0 {
+            let _ = fs::remove_file(rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                if rotated(n).exists() {
+                    fs::rename(rotated(n), rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+        self.file = open_for_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_once_the_file_is_full() {
+        let dir = std::env::temp_dir().join(format!("awsome-logs-{}", ulid::Ulid::new()));
+        let mut file = RotatingFile::open_with_limits(&dir, 16, 2).unwrap();
+
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("awsome.log"), "fourth line\n");
+        assert_eq!(read("awsome.log.1"), "third line\n");
+        assert_eq!(read("awsome.log.2"), "second line\n");
+        assert!(!dir.join("awsome.log.3").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn keeps_appending_to_an_existing_file() {
+        let dir = std::env::temp_dir().join(format!("awsome-logs-{}", ulid::Ulid::new()));
+        RotatingFile::open_with_limits(&dir, 1024, 1)
+            .unwrap()
+            .write_line("before restart")
+            .unwrap();
+
+        let mut file = RotatingFile::open_with_limits(&dir, 1024, 1).unwrap();
+        file.write_line("after restart").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(file.path()).unwrap(),
+            "before restart\nafter restart\n"
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use aws_credential_types::{
+    provider::{error::CredentialsError, future, ProvideCredentials},
+    Credentials,
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+    error::{Error, ResultExt},
+    models::{
+        client_pool::{invalidate_profile, sts_client},
+        profile::{load_shared_config, Profile, SharedConfig},
+        region::{default_region, FALLBACK_REGION},
+    },
+};
+
+/// Sessions that expire within this margin are treated as expired, so that a
+/// request does not fail halfway through.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Session credentials of assume-role profiles that required an MFA code,
+/// by profile name. They are only kept in memory.
+static SESSIONS: Lazy<Mutex<HashMap<String, Credentials>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What to ask the user for before a profile can be used.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MfaChallenge {
+    pub profile: String,
+    pub mfa_serial: String,
+}
+
+/// `Some` if the profile assumes a role with MFA and there is no session yet.
+pub fn mfa_challenge(profile: &str) -> Option<MfaChallenge> {
+    mfa_challenge_in(&load_shared_config().ok()?, profile)
+}
+
+/// Profiles whose MFA session cannot be started are not challenged, as the
+/// code would be asked for in vain.
+fn mfa_challenge_in(config: &SharedConfig, profile: &str) -> Option<MfaChallenge> {
+    if !requires_mfa(config, profile) || session_credentials(profile).is_some() {
+        return None;
+    }
+    let p = config.profile(profile)?;
+    mfa_source_profile(p).ok()?;
+    Some(MfaChallenge {
+        profile: profile.to_string(),
+        mfa_serial: p.mfa_serial.clone()?,
+    })
+}
+
+/// The profile whose credentials assume the role. The default chain cannot
+/// resolve `credential_source` without running into the MFA requirement
+/// again, so only source profiles are supported.
+fn mfa_source_profile(profile: &Profile) -> Result<&str, Error> {
+    profile.source_profile.as_deref().ok_or_else(|| {
+        let message = match &profile.credential_source {
+            Some(_) => "uses credential_source, which cannot be combined with mfa_serial. Use source_profile instead.",
+            None => "has no source_profile to assume its role with.",
+        };
+        Error::InvalidProfile {
+            profile: profile.name.clone(),
+            message: message.to_string(),
+        }
+    })
+}
+
+/// Whether the profile assumes a role that requires an MFA code, which the
+/// default credentials chain cannot ask for.
+pub fn requires_mfa(config: &SharedConfig, profile: &str) -> bool {
+    config
+        .profile(profile)
+        .is_some_and(|p| p.is_assume_role() && p.mfa_serial.is_some())
+}
+
+/// Provides the credentials of profiles that require MFA from the session
+/// started with the code the user entered. Without a session, it fails
+/// rather than letting the default chain run into the MFA requirement.
+#[derive(Debug)]
+pub struct MfaSessionProvider {
+    profile: String,
+    /// Why no session can be started for the profile, if it cannot.
+    unsupported: Option<Error>,
+}
+
+impl MfaSessionProvider {
+    pub fn new(profile: &Profile) -> Self {
+        Self {
+            profile: profile.name.clone(),
+            unsupported: mfa_source_profile(profile).err(),
+        }
+    }
+
+    fn credentials(&self) -> aws_credential_types::provider::Result {
+        if let Some(e) = &self.unsupported {
+            return Err(CredentialsError::invalid_configuration(e.to_string()));
+        }
+        session_credentials(&self.profile).ok_or_else(|| {
+            CredentialsError::not_loaded(format!(
+                "Profile {} requires an MFA code and has no session yet",
+                self.profile
+            ))
+        })
+    }
+}
+
+impl ProvideCredentials for MfaSessionProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::ready(self.credentials())
+    }
+}
+
+/// The cached session credentials of a profile, unless they have expired.
+pub fn session_credentials(profile: &str) -> Option<Credentials> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let credentials = sessions.get(profile)?;
+    if credentials
+        .expiry()
+        .is_some_and(|expiry| expiry <= SystemTime::now() + EXPIRY_MARGIN)
+    {
+        sessions.remove(profile);
+        return None;
+    }
+    Some(credentials.clone())
+}
+
+pub fn session_expiry(profile: &str) -> Option<SystemTime> {
+    session_credentials(profile).and_then(|c| c.expiry())
+}
+
+/// Assumes the profile's role with the source profile's credentials and the
+/// given TOTP code, and caches the session credentials. Returns when they
+/// expire.
+pub async fn start_mfa_session(
+    profile: String,
+    token_code: String,
+) -> Result<Option<SystemTime>, Error> {
+    let config = load_shared_config()?;
+    let Some(p) = config.profile(&profile) else {
+        return Err(Error::InvalidProfile {
+            profile,
+            message: "is not in the AWS config anymore.".to_string(),
+        });
+    };
+    let (Some(role_arn), Some(mfa_serial)) = (&p.role_arn, &p.mfa_serial) else {
+        return Err(Error::InvalidProfile {
+            profile,
+            message: "does not assume a role with mfa_serial.".to_string(),
+        });
+    };
+    let source_profile = mfa_source_profile(p)?;
+
+    let region = default_region(Some(p)).unwrap_or(FALLBACK_REGION.to_string());
+    let out = sts_client(source_profile, &region)
+        .await
+        .assume_role()
+        .role_arn(role_arn)
+        .role_session_name(role_session_name(p))
+        .set_external_id(p.external_id.clone())
+        .set_duration_seconds(p.duration_seconds.map(|d| d as i32))
+        .serial_number(mfa_serial)
+        .token_code(token_code.trim())
+        .send()
+        .await
+        .during_in("sts:AssumeRole", &profile, &region)?;
+    let Some(c) = out.credentials() else {
+        return Err(Error::InvalidResponse {
+            operation: "sts:AssumeRole".to_string(),
+            message: "no credentials".to_string(),
+        });
+    };
+
+    let expiry = SystemTime::try_from(*c.expiration()).ok();
+    let credentials = Credentials::new(
+        c.access_key_id(),
+        c.secret_access_key(),
+        Some(c.session_token().to_string()),
+        expiry,
+        "mfa",
+    );
+    SESSIONS
+        .lock()
+        .unwrap()
+        .insert(profile.clone(), credentials);
+    invalidate_profile(&profile);
+    Ok(expiry)
+}
+
+/// Drops the session of a profile, so that the next use asks for a new code.
+pub fn forget_mfa_session(profile: &str) {
+    SESSIONS.lock().unwrap().remove(profile);
+    invalidate_profile(profile);
+}
+
+fn role_session_name(profile: &Profile) -> String {
+    profile.role_session_name.clone().unwrap_or_else(|| {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        format!("awsome-{}", now.as_secs())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SharedConfig {
+        let fixture = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/aws/assume_role_config"
+        ))
+        .unwrap();
+        SharedConfig::parse(&fixture, "")
+    }
+
+    /// Sessions are global, so every test uses profiles of its own.
+    fn start_session(profile: &str, expiry: Option<SystemTime>) {
+        SESSIONS.lock().unwrap().insert(
+            profile.to_string(),
+            Credentials::new("AKIA", "secret", Some("token".to_string()), expiry, "test"),
+        );
+    }
+
+    #[test]
+    fn challenges_assume_role_profiles_with_mfa_until_there_is_a_session() {
+        let mut config = config();
+        let mut admin = config.profile("admin").unwrap().clone();
+        admin.name = "challenged-admin".to_string();
+        config.profiles.push(admin);
+
+        assert_eq!(
+            mfa_challenge_in(&config, "challenged-admin"),
+            Some(MfaChallenge {
+                profile: "challenged-admin".to_string(),
+                mfa_serial: "arn:aws:iam::123456789012:mfa/alice".to_string(),
+            })
+        );
+        assert_eq!(mfa_challenge_in(&config, "from-instance"), None);
+        assert_eq!(mfa_challenge_in(&config, "base"), None);
+        assert_eq!(mfa_challenge_in(&config, "missing"), None);
+
+        start_session(
+            "challenged-admin",
+            Some(SystemTime::now() + Duration::from_secs(3600)),
+        );
+        assert_eq!(mfa_challenge_in(&config, "challenged-admin"), None);
+    }
+
+    #[test]
+    fn sessions_expiring_within_the_margin_are_dropped() {
+        let now = SystemTime::now();
+        start_session("expiring", Some(now + EXPIRY_MARGIN / 2));
+        start_session("valid", Some(now + EXPIRY_MARGIN * 10));
+        start_session("unlimited", None);
+
+        assert!(session_credentials("expiring").is_none());
+        assert!(!SESSIONS.lock().unwrap().contains_key("expiring"));
+        assert!(session_credentials("valid").is_some());
+        assert!(session_expiry("valid").is_some());
+        assert!(session_credentials("unlimited").is_some());
+        assert_eq!(session_expiry("unlimited"), None);
+    }
+
+    #[test]
+    fn forgetting_a_session_asks_for_a_new_code() {
+        start_session(
+            "forgotten",
+            Some(SystemTime::now() + Duration::from_secs(3600)),
+        );
+        assert!(session_credentials("forgotten").is_some());
+
+        forget_mfa_session("forgotten");
+        assert!(session_credentials("forgotten").is_none());
+    }
+
+    #[test]
+    fn provider_uses_the_session() {
+        let provider = MfaSessionProvider::new(&Profile {
+            name: "provided".to_string(),
+            source_profile: Some("base".to_string()),
+            ..Default::default()
+        });
+        let provide = || iced::futures::executor::block_on(provider.provide_credentials());
+
+        assert!(matches!(
+            provide(),
+            Err(CredentialsError::CredentialsNotLoaded(_))
+        ));
+
+        start_session(
+            "provided",
+            Some(SystemTime::now() + Duration::from_secs(3600)),
+        );
+        assert_eq!(provide().unwrap().access_key_id(), "AKIA");
+        assert!(requires_mfa(&config(), "admin"));
+        assert!(!requires_mfa(&config(), "from-instance"));
+    }
+
+    #[test]
+    fn credential_source_profiles_are_not_challenged() {
+        let config = config();
+        let p = config.profile("instance-mfa").unwrap();
+
+        assert!(requires_mfa(&config, "instance-mfa"));
+        assert_eq!(mfa_challenge_in(&config, "instance-mfa"), None);
+        let Err(e) = mfa_source_profile(p) else {
+            panic!("credential_source is not supported");
+        };
+        assert!(e
+            .to_string()
+            .starts_with("The profile instance-mfa uses credential_source"));
+
+        let provider = MfaSessionProvider::new(p);
+        let provided = iced::futures::executor::block_on(provider.provide_credentials());
+        assert!(matches!(
+            provided,
+            Err(CredentialsError::InvalidConfiguration(_))
+        ));
+    }
+}
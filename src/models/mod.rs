@@ -1,5 +1,21 @@
+pub mod caller_identity;
+pub mod client_pool;
+pub mod endpoint;
+pub mod error_report;
+pub mod lambda_function;
+pub mod log_entry;
+pub mod log_file;
+pub mod mfa;
 pub mod profile;
 pub mod region;
+pub mod request_policy;
 pub mod resource;
+pub mod s3_bucket;
+pub mod s3_bucket_config;
+pub mod s3_object;
+pub mod s3_presign;
 pub mod service;
+pub mod session;
+pub mod sso;
+pub mod transfer;
 pub mod workspace;
@@ -1,59 +1,363 @@
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use aws_config::{BehaviorVersion, Region};
+use aws_runtime::env_config::file::{EnvConfigFileKind, EnvConfigFiles};
+use once_cell::sync::OnceCell;
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    models::{
+        endpoint::endpoint_url,
+        mfa::{requires_mfa, MfaSessionProvider},
+        request_policy,
+    },
+};
 
-static PROFILE_PATTERN_IN_AWS_CONFIG: Lazy<Mutex<Regex>> = Lazy::new(|| {
-    let re = Regex::new(r#"^\[profile\s+(.+)\]"#).unwrap();
-    Mutex::new(re)
-});
+/// A property value. A property whose value is empty may be followed by an
+/// indented "sub-section" of its own properties:
+///
+/// ```text
+/// s3 =
+///   addressing_style = path
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Property {
+    pub value: String,
+    pub sub_properties: BTreeMap<String, String>,
+}
 
-static PROFILE_PATTERN_IN_AWS_CREDENTIALS: Lazy<Mutex<Regex>> = Lazy::new(|| {
-    let re = Regex::new(r#"^\[(.+)\]"#).unwrap();
-    Mutex::new(re)
-});
+type Properties = BTreeMap<String, Property>;
 
-pub fn load_profile_names() -> Result<Vec<String>, Error> {
-    let aws_config_path = get_aws_config_path()?;
-    let aws_config_file_contents = std::fs::read_to_string(aws_config_path)?;
-    let mut profiles_in_aws_config =
-        collect_profile_names_from_aws_config(&aws_config_file_contents)?;
+/// Properties that are never kept in memory once the files are parsed.
+const SECRET_PROPERTIES: &[&str] = &["aws_secret_access_key", "aws_session_token"];
 
-    let aws_credentials_path = get_aws_credentials_path()?;
-    let aws_credentials_file_contents = std::fs::read_to_string(aws_credentials_path)?;
-    let mut profiles_in_aws_credentials =
-        collect_profile_names_from_aws_credentials(&aws_credentials_file_contents)?;
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum SectionKey {
+    Profile(String),
+    /// `[default]` in the config file, which `[profile default]` replaces.
+    BareDefault,
+    SsoSession(String),
+    Services(String),
+}
 
-    profiles_in_aws_config.append(&mut profiles_in_aws_credentials);
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FileKind {
+    Config,
+    Credentials,
+}
 
-    Ok(profiles_in_aws_config)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub region: Option<String>,
+    pub aws_access_key_id: Option<String>,
+    pub role_arn: Option<String>,
+    pub source_profile: Option<String>,
+    pub credential_source: Option<String>,
+    pub role_session_name: Option<String>,
+    pub external_id: Option<String>,
+    pub mfa_serial: Option<String>,
+    pub duration_seconds: Option<u32>,
+    pub credential_process: Option<String>,
+    pub sso_session: Option<String>,
+    pub sso_start_url: Option<String>,
+    pub sso_region: Option<String>,
+    pub sso_account_id: Option<String>,
+    pub sso_role_name: Option<String>,
+    pub endpoint_url: Option<String>,
+    /// Name of the `[services ...]` section that this profile uses.
+    pub services: Option<String>,
+    /// Every property of the profile except the secret ones, including the
+    /// ones that have a typed field above.
+    pub properties: BTreeMap<String, Property>,
 }
 
-fn get_aws_config_path() -> Result<PathBuf, Error> {
-    let Some(user_dirs) = directories::UserDirs::new() else {
-        return Err(Error::UnableToLoadAwsConfig);
-    };
+impl Profile {
+    fn from_properties(name: String, mut properties: Properties) -> Self {
+        for secret in SECRET_PROPERTIES {
+            properties.remove(*secret);
+        }
+        let get = |key: &str| value_of(&properties, key);
+
+        Self {
+            region: get("region"),
+            aws_access_key_id: get("aws_access_key_id"),
+            role_arn: get("role_arn"),
+            source_profile: get("source_profile"),
+            credential_source: get("credential_source"),
+            role_session_name: get("role_session_name"),
+            external_id: get("external_id"),
+            mfa_serial: get("mfa_serial"),
+            duration_seconds: get("duration_seconds").and_then(|d| d.parse().ok()),
+            credential_process: get("credential_process"),
+            sso_session: get("sso_session"),
+            sso_start_url: get("sso_start_url"),
+            sso_region: get("sso_region"),
+            sso_account_id: get("sso_account_id"),
+            sso_role_name: get("sso_role_name"),
+            endpoint_url: get("endpoint_url"),
+            services: get("services"),
+            name,
+            properties,
+        }
+    }
+
+    pub fn is_assume_role(&self) -> bool {
+        self.role_arn.is_some()
+    }
+
+    pub fn is_sso(&self) -> bool {
+        self.sso_session.is_some() || self.sso_start_url.is_some()
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SsoSession {
+    pub name: String,
+    pub sso_start_url: Option<String>,
+    pub sso_region: Option<String>,
+    pub sso_registration_scopes: Vec<String>,
+    pub properties: BTreeMap<String, Property>,
+}
+
+impl SsoSession {
+    fn from_properties(name: String, properties: Properties) -> Self {
+        let get = |key: &str| value_of(&properties, key);
+
+        Self {
+            sso_start_url: get("sso_start_url"),
+            sso_region: get("sso_region"),
+            sso_registration_scopes: get("sso_registration_scopes")
+                .map(|scopes| {
+                    scopes
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            name,
+            properties,
+        }
+    }
+}
+
+/// A `[services ...]` section, which maps service IDs such as `s3` or
+/// `dynamodb` to their own settings.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Services {
+    pub name: String,
+    pub services: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Services {
+    fn from_properties(name: String, properties: Properties) -> Self {
+        Self {
+            name,
+            services: properties
+                .into_iter()
+                .map(|(service, property)| (service, property.sub_properties))
+                .collect(),
+        }
+    }
+
+    pub fn endpoint_url(&self, service: &str) -> Option<&str> {
+        self.services
+            .get(service)
+            .and_then(|settings| settings.get("endpoint_url"))
+            .map(|url| url.as_str())
+    }
+}
+
+/// The merged contents of the shared config and credentials files.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SharedConfig {
+    /// Sorted by name. A profile that is defined in both files appears once.
+    pub profiles: Vec<Profile>,
+    pub sso_sessions: Vec<SsoSession>,
+    pub services: Vec<Services>,
+}
+
+impl SharedConfig {
+    /// Profiles that appear in both files are merged. Where both define the
+    /// same property, the credentials file wins, as it does in the SDKs.
+    pub fn parse(config: &str, credentials: &str) -> Self {
+        let mut sections = parse_sections(config, FileKind::Config);
+        // As in the SDKs, `[default]` is ignored if `[profile default]` exists.
+        if let Some(properties) = sections.remove(&SectionKey::BareDefault) {
+            sections
+                .entry(SectionKey::Profile("default".to_string()))
+                .or_insert(properties);
+        }
+        for (key, properties) in parse_sections(credentials, FileKind::Credentials) {
+            sections.entry(key).or_default().extend(properties);
+        }
+
+        let mut shared_config = Self::default();
+        for (key, properties) in sections {
+            match key {
+                SectionKey::Profile(name) => shared_config
+                    .profiles
+                    .push(Profile::from_properties(name, properties)),
+                SectionKey::SsoSession(name) => shared_config
+                    .sso_sessions
+                    .push(SsoSession::from_properties(name, properties)),
+                SectionKey::Services(name) => shared_config
+                    .services
+                    .push(Services::from_properties(name, properties)),
+                // Taken as the default profile above.
+                SectionKey::BareDefault => {}
+            }
+        }
+        shared_config
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn sso_session(&self, name: &str) -> Option<&SsoSession> {
+        self.sso_sessions.iter().find(|s| s.name == name)
+    }
+
+    pub fn services(&self, name: &str) -> Option<&Services> {
+        self.services.iter().find(|s| s.name == name)
+    }
+}
 
-    let mut path = PathBuf::new();
-    path.push(user_dirs.home_dir());
-    path.push(".aws");
-    path.push("config");
-    Ok(path)
+/// Paths given on the command line, which take precedence over the
+/// `AWS_CONFIG_FILE` and `AWS_SHARED_CREDENTIALS_FILE` environment variables.
+static PROFILE_FILE_OVERRIDES: OnceCell<ProfileFileOverrides> = OnceCell::new();
+
+#[derive(Clone, Debug, Default)]
+pub struct ProfileFileOverrides {
+    pub config: Option<PathBuf>,
+    pub credentials: Option<PathBuf>,
+}
+
+/// Must be called before anything reads the shared config files.
+pub fn set_profile_file_overrides(overrides: ProfileFileOverrides) {
+    if PROFILE_FILE_OVERRIDES.set(overrides).is_err() {
+        tracing::warn!("Profile file overrides were already set");
+    }
+}
+
+/// Starts loading an SDK config from the same files that the profile list
+/// comes from, for clients of `service`, e.g. `s3`.
+pub fn sdk_config_loader(profile: &str, region: &str, service: &str) -> aws_config::ConfigLoader {
+    let mut loader = request_policy::apply_to_loader(
+        aws_config::defaults(BehaviorVersion::v2024_03_28())
+            .profile_name(profile)
+            .region(Region::new(region.to_string())),
+    );
+    let config = load_shared_config().unwrap_or_default();
+    if let Some(url) = endpoint_url(&config, profile, service) {
+        loader = loader.endpoint_url(url);
+    }
+    // The default chain cannot ask for an MFA code, so profiles that need one
+    // use the session started from the prompt.
+    if let Some(p) = config
+        .profile(profile)
+        .filter(|_| requires_mfa(&config, profile))
+    {
+        loader = loader.credentials_provider(MfaSessionProvider::new(p));
+    }
+
+    match (get_aws_config_path(), get_aws_credentials_path()) {
+        (Ok(config), Ok(credentials)) => loader.profile_files(
+            EnvConfigFiles::builder()
+                .with_file(EnvConfigFileKind::Config, config)
+                .with_file(EnvConfigFileKind::Credentials, credentials)
+                .build(),
+        ),
+        _ => loader,
+    }
+}
+
+pub fn load_shared_config() -> Result<SharedConfig, Error> {
+    let aws_config_file_contents = read_to_string_or_empty(&get_aws_config_path()?)?;
+    let aws_credentials_file_contents = read_to_string_or_empty(&get_aws_credentials_path()?)?;
+    Ok(SharedConfig::parse(
+        &aws_config_file_contents,
+        &aws_credentials_file_contents,
+    ))
+}
+
+pub fn load_profiles() -> Result<Vec<Profile>, Error> {
+    Ok(load_shared_config()?.profiles)
+}
+
+/// A missing file is not an error: machines that only use SSO often have no
+/// credentials file, and some have no config file either.
+fn read_to_string_or_empty(path: &Path) -> Result<String, Error> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn get_aws_config_path() -> Result<PathBuf, Error> {
+    resolve_path(
+        PROFILE_FILE_OVERRIDES.get().and_then(|o| o.config.clone()),
+        std::env::var_os("AWS_CONFIG_FILE"),
+        "config",
+        home_dir,
+    )
 }
 
 fn get_aws_credentials_path() -> Result<PathBuf, Error> {
-    let Some(user_dirs) = directories::UserDirs::new() else {
-        return Err(Error::UnableToLoadAwsConfig);
-    };
+    resolve_path(
+        PROFILE_FILE_OVERRIDES
+            .get()
+            .and_then(|o| o.credentials.clone()),
+        std::env::var_os("AWS_SHARED_CREDENTIALS_FILE"),
+        "credentials",
+        home_dir,
+    )
+}
+
+fn home_dir() -> Option<PathBuf> {
+    directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+}
+
+/// The path given by the flag, else by the environment variable, else the
+/// default one. The home directory is only looked up when it is needed, so
+/// that overrides work where there is none, e.g. in containers.
+fn resolve_path(
+    flag: Option<PathBuf>,
+    env_value: Option<OsString>,
+    default_file_name: &str,
+    home_dir: impl Fn() -> Option<PathBuf>,
+) -> Result<PathBuf, Error> {
+    if let Some(path) = flag {
+        return Ok(path);
+    }
+    let home = || home_dir().ok_or(Error::UnableToLoadAwsConfig);
+    if let Some(path) = env_value.filter(|p| !p.is_empty()).map(PathBuf::from) {
+        return match path.starts_with("~") {
+            true => Ok(expand_home(path, &home()?)),
+            false => Ok(path),
+        };
+    }
+    Ok(home()?.join(".aws").join(default_file_name))
+}
 
-    let mut path = PathBuf::new();
-    path.push(user_dirs.home_dir());
-    path.push(".aws");
-    path.push("credentials");
-    Ok(path)
+/// The SDKs expand a leading `~` in the environment variables, so we do too.
+fn expand_home(path: PathBuf, home: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home.join(rest),
+        Err(_) => path,
+    }
+}
+
+fn value_of(properties: &Properties, key: &str) -> Option<String> {
+    properties
+        .get(key)
+        .map(|p| p.value.clone())
+        .filter(|v| !v.is_empty())
 }
 
 // The contents of ~/.aws/config look like:
@@ -64,41 +368,482 @@ fn get_aws_credentials_path() -> Result<PathBuf, Error> {
 //
 // [services my-services]
 // dynamodb =
-//   endpoint_url = http://localhost:8000`
+//   endpoint_url = http://localhost:8000
 // ```
 //
-// it may have indented "sub-sections". It is different from .ini or .toml.
-// So we cannot use any existing crates. We should implement our own function to parse it.
-fn collect_profile_names_from_aws_config(content: &str) -> Result<Vec<String>, Error> {
-    let mut result = vec![];
+// It may have indented "sub-sections" and continuation lines, so it is
+// neither .ini nor .toml, and the parsing is done by hand. Lines that cannot
+// be understood are skipped, as are sections of unknown types, so that one
+// odd entry does not hide every profile.
+fn parse_sections(content: &str, kind: FileKind) -> BTreeMap<SectionKey, Properties> {
+    let mut sections: BTreeMap<SectionKey, Properties> = BTreeMap::new();
+    let mut section: Option<SectionKey> = None;
+    let mut property: Option<String> = None;
 
     for line in content.lines() {
-        if let Some(captures) = PROFILE_PATTERN_IN_AWS_CONFIG.lock().unwrap().captures(line) {
-            if captures.len() >= 2 {
-                if let Some(cap) = captures.get(1) {
-                    result.push(cap.as_str().to_string());
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            // An indented line belongs to the property above it: either as a
+            // sub-property, if that property has no value, or as a
+            // continuation of its value.
+            let (Some(section), Some(property)) = (&section, &property) else {
+                continue;
+            };
+            let Some(p) = sections
+                .get_mut(section)
+                .and_then(|properties| properties.get_mut(property))
+            else {
+                continue;
+            };
+            if p.value.is_empty() {
+                if let Some((key, value)) = parse_property(trimmed) {
+                    p.sub_properties.insert(key, value);
                 }
+            } else {
+                p.value.push('\n');
+                p.value.push_str(trimmed);
+            }
+            continue;
+        }
+
+        property = None;
+
+        if trimmed.starts_with('[') {
+            section = parse_section_header(trimmed, kind);
+            if let Some(key) = &section {
+                sections.entry(key.clone()).or_default();
             }
+            continue;
         }
+
+        let Some(key) = &section else {
+            continue;
+        };
+        let Some((name, value)) = parse_property(trimmed) else {
+            continue;
+        };
+        sections.entry(key.clone()).or_default().insert(
+            name.clone(),
+            Property {
+                value,
+                sub_properties: BTreeMap::new(),
+            },
+        );
+        property = Some(name);
     }
-    Ok(result)
+
+    sections
 }
 
-fn collect_profile_names_from_aws_credentials(content: &str) -> Result<Vec<String>, Error> {
-    let mut result = vec![];
+fn parse_section_header(line: &str, kind: FileKind) -> Option<SectionKey> {
+    let (name, rest) = line.strip_prefix('[')?.split_once(']')?;
+    let rest = rest.trim();
+    if !rest.is_empty() && !rest.starts_with('#') && !rest.starts_with(';') {
+        return None;
+    }
 
-    for line in content.lines() {
-        if let Some(captures) = PROFILE_PATTERN_IN_AWS_CREDENTIALS
-            .lock()
-            .unwrap()
-            .captures(line)
-        {
-            if captures.len() >= 2 {
-                if let Some(cap) = captures.get(1) {
-                    result.push(cap.as_str().to_string());
-                }
+    let name = name.trim();
+    if kind == FileKind::Credentials {
+        return (!name.is_empty()).then(|| SectionKey::Profile(name.to_string()));
+    }
+    if name == "default" {
+        return Some(SectionKey::BareDefault);
+    }
+
+    let (section_type, section_name) = name.split_once(char::is_whitespace)?;
+    let section_name = section_name.trim().to_string();
+    if section_name.is_empty() {
+        return None;
+    }
+    match section_type {
+        "profile" => Some(SectionKey::Profile(section_name)),
+        "sso-session" => Some(SectionKey::SsoSession(section_name)),
+        "services" => Some(SectionKey::Services(section_name)),
+        _ => None,
+    }
+}
+
+fn parse_property(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() {
+        return None;
+    }
+    Some((
+        key.to_string(),
+        strip_inline_comment(value).trim().to_string(),
+    ))
+}
+
+/// A `#` or `;` only starts a comment when whitespace precedes it, so that
+/// values such as URLs with fragments survive.
+fn strip_inline_comment(value: &str) -> &str {
+    let mut previous_is_whitespace = false;
+    for (i, c) in value.char_indices() {
+        if (c == '#' || c == ';') && previous_is_whitespace {
+            return &value[..i];
+        }
+        previous_is_whitespace = c.is_whitespace();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/aws")
+            .join(name);
+        std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("unable to read {}: {}", path.display(), e))
+    }
+
+    fn parse_fixtures(config: &str, credentials: &str) -> SharedConfig {
+        SharedConfig::parse(&fixture(config), &fixture(credentials))
+    }
+
+    fn names(config: &SharedConfig) -> Vec<&str> {
+        config.profiles.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_default_and_named_profiles() {
+        let config = parse_fixtures("basic_config", "empty");
+
+        assert_eq!(names(&config), vec!["default", "dev", "prod"]);
+        assert_eq!(
+            config.profile("default").unwrap().region.as_deref(),
+            Some("us-east-1")
+        );
+        assert_eq!(
+            config.profile("dev").unwrap().region.as_deref(),
+            Some("ap-northeast-1")
+        );
+    }
+
+    #[test]
+    fn profile_default_section_is_the_default_profile() {
+        let config = SharedConfig::parse("[profile default]\nregion = eu-west-1\n", "");
+
+        assert_eq!(names(&config), vec!["default"]);
+        assert_eq!(
+            config.profile("default").unwrap().region.as_deref(),
+            Some("eu-west-1")
+        );
+
+        for content in [
+            "[profile default]\nregion = eu-west-1\n[default]\nregion = us-east-1\nrole_arn = arn:aws:iam::1:role/r\n",
+            "[default]\nregion = us-east-1\nrole_arn = arn:aws:iam::1:role/r\n[profile default]\nregion = eu-west-1\n",
+        ] {
+            let config = SharedConfig::parse(content, "");
+            assert_eq!(names(&config), vec!["default"]);
+            let default = config.profile("default").unwrap();
+            assert_eq!(default.region.as_deref(), Some("eu-west-1"));
+            assert_eq!(default.role_arn, None);
+        }
+    }
+
+    #[test]
+    fn deduplicates_profiles_in_both_files() {
+        let config = parse_fixtures("basic_config", "basic_credentials");
+
+        assert_eq!(names(&config), vec!["default", "dev", "prod", "staging"]);
+    }
+
+    #[test]
+    fn credentials_file_overrides_config_file() {
+        let config = parse_fixtures("basic_config", "basic_credentials");
+
+        let dev = config.profile("dev").unwrap();
+        assert_eq!(dev.aws_access_key_id.as_deref(), Some("AKIADEVFROMCREDS"));
+        // Properties only in the config file are kept.
+        assert_eq!(dev.region.as_deref(), Some("ap-northeast-1"));
+        // Where both files set a property, the credentials file wins.
+        assert_eq!(
+            config.profile("prod").unwrap().region.as_deref(),
+            Some("us-west-2")
+        );
+    }
+
+    #[test]
+    fn secrets_are_not_kept() {
+        let config = parse_fixtures("basic_config", "basic_credentials");
+
+        for profile in &config.profiles {
+            for secret in SECRET_PROPERTIES {
+                assert!(!profile.properties.contains_key(*secret));
             }
         }
     }
-    Ok(result)
+
+    #[test]
+    fn profile_prefix_is_literal_in_credentials_file() {
+        let config = SharedConfig::parse("", "[profile odd]\naws_access_key_id = AKIA\n");
+
+        assert_eq!(names(&config), vec!["profile odd"]);
+    }
+
+    #[test]
+    fn sections_without_profile_prefix_are_ignored_in_config_file() {
+        let config = parse_fixtures("unknown_sections_config", "empty");
+
+        assert_eq!(names(&config), vec!["valid"]);
+        assert!(config.sso_sessions.is_empty());
+        assert!(config.services.is_empty());
+    }
+
+    #[test]
+    fn parses_assume_role_profiles() {
+        let config = parse_fixtures("assume_role_config", "empty");
+
+        let admin = config.profile("admin").unwrap();
+        assert!(admin.is_assume_role());
+        assert_eq!(
+            admin.role_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:role/Admin")
+        );
+        assert_eq!(admin.source_profile.as_deref(), Some("base"));
+        assert_eq!(
+            admin.mfa_serial.as_deref(),
+            Some("arn:aws:iam::123456789012:mfa/alice")
+        );
+        assert_eq!(admin.external_id.as_deref(), Some("partner-1"));
+        assert_eq!(admin.role_session_name.as_deref(), Some("awsome"));
+        assert_eq!(admin.duration_seconds, Some(3600));
+
+        let ec2 = config.profile("from-instance").unwrap();
+        assert_eq!(
+            ec2.credential_source.as_deref(),
+            Some("Ec2InstanceMetadata")
+        );
+        assert!(!config.profile("base").unwrap().is_assume_role());
+    }
+
+    #[test]
+    fn parses_credential_process() {
+        let config = parse_fixtures("assume_role_config", "empty");
+
+        assert_eq!(
+            config
+                .profile("process")
+                .unwrap()
+                .credential_process
+                .as_deref(),
+            Some("/usr/local/bin/get-credentials --profile process --format json")
+        );
+    }
+
+    #[test]
+    fn parses_sso_profiles_and_sessions() {
+        let config = parse_fixtures("sso_config", "empty");
+
+        let session = config.sso_session("my-sso").unwrap();
+        assert_eq!(
+            session.sso_start_url.as_deref(),
+            Some("https://my-sso-portal.awsapps.com/start")
+        );
+        assert_eq!(session.sso_region.as_deref(), Some("us-east-1"));
+        assert_eq!(
+            session.sso_registration_scopes,
+            vec!["sso:account:access", "codecatalyst:read_write"]
+        );
+
+        let dev = config.profile("sso-dev").unwrap();
+        assert!(dev.is_sso());
+        assert_eq!(dev.sso_session.as_deref(), Some("my-sso"));
+        assert_eq!(dev.sso_account_id.as_deref(), Some("111122223333"));
+        assert_eq!(dev.sso_role_name.as_deref(), Some("ReadOnly"));
+
+        let legacy = config.profile("sso-legacy").unwrap();
+        assert!(legacy.is_sso());
+        assert_eq!(legacy.sso_session, None);
+        assert_eq!(
+            legacy.sso_start_url.as_deref(),
+            Some("https://legacy.awsapps.com/start")
+        );
+        assert_eq!(legacy.sso_region.as_deref(), Some("eu-west-1"));
+
+        // An sso-session section is not a profile.
+        assert!(config.profile("my-sso").is_none());
+    }
+
+    #[test]
+    fn parses_services_sections_and_sub_sections() {
+        let config = parse_fixtures("services_config", "empty");
+
+        let local = config.profile("local").unwrap();
+        assert_eq!(local.services.as_deref(), Some("local-services"));
+        assert_eq!(local.endpoint_url.as_deref(), Some("http://localhost:4566"));
+
+        let services = config.services("local-services").unwrap();
+        assert_eq!(
+            services.endpoint_url("dynamodb"),
+            Some("http://localhost:8000")
+        );
+        assert_eq!(services.endpoint_url("s3"), Some("http://localhost:9000"));
+        assert_eq!(
+            services.services["s3"]
+                .get("addressing_style")
+                .map(|s| s.as_str()),
+            Some("path")
+        );
+        assert_eq!(services.endpoint_url("lambda"), None);
+    }
+
+    #[test]
+    fn parses_sub_sections_in_profiles() {
+        let config = parse_fixtures("services_config", "empty");
+
+        let s3 = &config.profile("local").unwrap().properties["s3"];
+        assert_eq!(s3.value, "");
+        assert_eq!(
+            s3.sub_properties
+                .get("max_concurrent_requests")
+                .map(|s| s.as_str()),
+            Some("20")
+        );
+        assert_eq!(
+            s3.sub_properties
+                .get("addressing_style")
+                .map(|s| s.as_str()),
+            Some("virtual")
+        );
+    }
+
+    #[test]
+    fn parses_continuation_lines() {
+        let config = parse_fixtures("services_config", "empty");
+
+        assert_eq!(
+            config.profile("local").unwrap().properties["description"].value,
+            "first line\nsecond line"
+        );
+    }
+
+    #[test]
+    fn skips_comments() {
+        let config = parse_fixtures("comments_config", "empty");
+
+        assert_eq!(names(&config), vec!["commented", "default"]);
+        let profile = config.profile("commented").unwrap();
+        assert_eq!(profile.region.as_deref(), Some("us-east-2"));
+        assert_eq!(
+            profile.endpoint_url.as_deref(),
+            Some("https://example.com/path#fragment")
+        );
+        assert_eq!(profile.role_arn.as_deref(), Some("arn:aws:iam::1:role/R;1"));
+        assert!(!profile.properties.contains_key("# region"));
+    }
+
+    #[test]
+    fn tolerates_malformed_lines() {
+        let config = parse_fixtures("malformed_config", "empty");
+
+        assert_eq!(names(&config), vec!["after-broken", "ok"]);
+        let ok = config.profile("ok").unwrap();
+        assert_eq!(ok.region.as_deref(), Some("us-east-1"));
+        assert_eq!(ok.properties.len(), 1);
+    }
+
+    #[test]
+    fn later_duplicate_sections_are_merged() {
+        let config = SharedConfig::parse(
+            "[profile a]\nregion = us-east-1\noutput = json\n[profile a]\nregion = us-west-1\n",
+            "",
+        );
+
+        let a = config.profile("a").unwrap();
+        assert_eq!(a.region.as_deref(), Some("us-west-1"));
+        assert_eq!(a.properties["output"].value, "json");
+    }
+
+    #[test]
+    fn handles_crlf_and_whitespace() {
+        let config = SharedConfig::parse(
+            "[ profile   spaced ]\r\n  \r\nregion=us-east-1\r\n",
+            "[  creds  ]\r\naws_access_key_id   =   AKIA  \r\n",
+        );
+
+        assert_eq!(names(&config), vec!["creds", "spaced"]);
+        assert_eq!(
+            config.profile("spaced").unwrap().region.as_deref(),
+            Some("us-east-1")
+        );
+        assert_eq!(
+            config
+                .profile("creds")
+                .unwrap()
+                .aws_access_key_id
+                .as_deref(),
+            Some("AKIA")
+        );
+    }
+
+    #[test]
+    fn empty_values_are_none() {
+        let config = SharedConfig::parse("[profile blank]\nregion =\n", "");
+
+        let blank = config.profile("blank").unwrap();
+        assert_eq!(blank.region, None);
+        assert!(blank.properties.contains_key("region"));
+    }
+
+    #[test]
+    fn missing_files_are_empty() {
+        let missing =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aws/does-not-exist");
+
+        assert_eq!(read_to_string_or_empty(&missing).unwrap(), "");
+    }
+
+    #[test]
+    fn resolves_overrides_without_a_home_directory() {
+        let no_home = || None;
+        let home = || Some(PathBuf::from("/home/user"));
+        let env = |value: &str| Some(OsString::from(value));
+
+        assert_eq!(
+            resolve_path(Some("/flag".into()), env("/env"), "config", no_home).unwrap(),
+            PathBuf::from("/flag")
+        );
+        assert_eq!(
+            resolve_path(None, env("/env"), "config", no_home).unwrap(),
+            PathBuf::from("/env")
+        );
+        assert!(resolve_path(None, env("~/env"), "config", no_home).is_err());
+        assert!(resolve_path(None, None, "config", no_home).is_err());
+        assert_eq!(
+            resolve_path(None, env(""), "config", home).unwrap(),
+            PathBuf::from("/home/user/.aws/config")
+        );
+    }
+
+    #[test]
+    fn expands_home_in_paths() {
+        let home = Path::new("/home/user");
+
+        assert_eq!(
+            expand_home(PathBuf::from("~/.aws/alt-config"), home),
+            PathBuf::from("/home/user/.aws/alt-config")
+        );
+        assert_eq!(
+            expand_home(PathBuf::from("/etc/aws/config"), home),
+            PathBuf::from("/etc/aws/config")
+        );
+        assert_eq!(
+            expand_home(PathBuf::from("relative/~/config"), home),
+            PathBuf::from("relative/~/config")
+        );
+    }
+
+    #[test]
+    fn empty_files_have_no_profiles() {
+        assert_eq!(SharedConfig::parse("", ""), SharedConfig::default());
+    }
 }
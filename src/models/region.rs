@@ -1,18 +1,127 @@
-use aws_config::{BehaviorVersion, Region};
+use std::time::{Duration, Instant};
 
-use crate::error::Error;
+use iced::futures::{stream::FuturesUnordered, StreamExt};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::{
+    error::{Error, ResultExt},
+    models::{client_pool::ec2_client, profile::Profile},
+};
+
+/// Used when nothing better is known, e.g. to call DescribeRegions before the
+/// nearest region has been measured.
+pub const FALLBACK_REGION: &str = "us-east-1";
+
+/// Regions whose endpoints are probed to find the nearest one. Opt-in regions
+/// are left out, as most accounts cannot use them.
+const PROBED_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "ca-central-1",
+    "sa-east-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "eu-central-1",
+    "eu-north-1",
+    "ap-south-1",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ap-southeast-1",
+    "ap-southeast-2",
+];
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A regional endpoint to measure. `address` is a `host:port` that answers
+/// plain HTTP requests for `/ping`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegionEndpoint {
+    pub region: String,
+    pub address: String,
+}
+
+pub fn default_region_endpoints() -> Vec<RegionEndpoint> {
+    PROBED_REGIONS
+        .iter()
+        .map(|region| RegionEndpoint {
+            region: region.to_string(),
+            address: format!("dynamodb.{}.amazonaws.com:80", region),
+        })
+        .collect()
+}
+
+/// The region to start browsing a profile in: the profile's own `region`
+/// setting, then `AWS_REGION`. `None` means that the nearest region should be
+/// used instead.
+pub fn default_region(profile: Option<&Profile>) -> Option<String> {
+    profile
+        .and_then(|p| p.region.clone())
+        .or_else(|| std::env::var("AWS_REGION").ok().filter(|r| !r.is_empty()))
+}
+
+/// Probes every endpoint at once and returns the region that answered first.
+/// Endpoints that fail or time out are ignored.
+pub async fn measure_nearest_region(endpoints: Vec<RegionEndpoint>) -> Option<String> {
+    let mut probes: FuturesUnordered<_> = endpoints
+        .into_iter()
+        .map(|endpoint| async move {
+            let latency = measure_latency(&endpoint.address).await;
+            (endpoint.region, latency)
+        })
+        .collect();
+
+    while let Some((region, latency)) = probes.next().await {
+        match latency {
+            Ok(_) => return Some(region),
+            Err(e) => tracing::debug!(region, error = ?e, "Unable to measure the latency"),
+        }
+    }
+    None
+}
+
+/// Time from connecting until the first byte of the response to a plain
+/// `GET /ping`. Any response counts, as only the round trip matters.
+async fn measure_latency(address: &str) -> Result<Duration, std::io::Error> {
+    let host = address.rsplit_once(':').map(|(h, _)| h).unwrap_or(address);
+    let request = format!(
+        "GET /ping HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        host
+    );
+
+    let probe = async {
+        let started = Instant::now();
+        let mut stream = TcpStream::connect(address).await?;
+        stream.write_all(request.as_bytes()).await?;
+        let mut buf = [0u8; 1];
+        if stream.read(&mut buf).await? == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+        Ok(started.elapsed())
+    };
+
+    timeout(PROBE_TIMEOUT, probe)
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))?
+}
 
 pub async fn load_region_names(
     profile: String,
     nearest_region: String,
 ) -> Result<Vec<String>, Error> {
-    let config = aws_config::defaults(BehaviorVersion::v2024_03_28())
-        .profile_name(profile)
-        .region(Region::new(nearest_region.to_string()))
-        .load()
-        .await;
-    let client = aws_sdk_ec2::Client::new(&config);
-    let out = client.describe_regions().send().await?;
+    let client = ec2_client(&profile, &nearest_region).await;
+    let out = client.describe_regions().send().await.during_in(
+        "ec2:DescribeRegions",
+        &profile,
+        &nearest_region,
+    )?;
     let mut regions = out
         .regions
         .map(|regions| {
@@ -22,7 +131,7 @@ pub async fn load_region_names(
                 .collect()
         })
         .unwrap_or_else(|| {
-            eprintln!("Regions was None");
+            tracing::warn!("DescribeRegions returned no regions");
             vec![]
         });
 
@@ -30,3 +139,117 @@ pub async fn load_region_names(
 
     Ok(regions)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// A stand-in for a regional endpoint that answers every request after
+    /// `delay`.
+    fn stand_in(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                thread::spawn(move || {
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf);
+                    thread::sleep(delay);
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+                });
+            }
+        });
+        address
+    }
+
+    /// An address that refuses connections.
+    fn closed_port() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    fn endpoint(region: &str, address: String) -> RegionEndpoint {
+        RegionEndpoint {
+            region: region.to_string(),
+            address,
+        }
+    }
+
+    fn measure(endpoints: Vec<RegionEndpoint>) -> Option<String> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(measure_nearest_region(endpoints))
+    }
+
+    #[test]
+    fn picks_the_fastest_endpoint() {
+        let nearest = measure(vec![
+            endpoint("slow-1", stand_in(Duration::from_millis(300))),
+            endpoint("fast-1", stand_in(Duration::ZERO)),
+            endpoint("slow-2", stand_in(Duration::from_millis(500))),
+        ]);
+
+        assert_eq!(nearest.as_deref(), Some("fast-1"));
+    }
+
+    #[test]
+    fn does_not_wait_for_slower_endpoints() {
+        let started = Instant::now();
+        let nearest = measure(vec![
+            endpoint("hung-1", stand_in(PROBE_TIMEOUT * 2)),
+            endpoint("fast-1", stand_in(Duration::ZERO)),
+        ]);
+
+        assert_eq!(nearest.as_deref(), Some("fast-1"));
+        assert!(started.elapsed() < PROBE_TIMEOUT);
+    }
+
+    #[test]
+    fn ignores_unreachable_endpoints() {
+        let nearest = measure(vec![
+            endpoint("closed-1", closed_port()),
+            endpoint("slow-1", stand_in(Duration::from_millis(100))),
+        ]);
+
+        assert_eq!(nearest.as_deref(), Some("slow-1"));
+    }
+
+    #[test]
+    fn returns_none_when_nothing_answers() {
+        assert_eq!(measure(vec![endpoint("closed-1", closed_port())]), None);
+        assert_eq!(measure(vec![]), None);
+    }
+
+    #[test]
+    fn probes_every_default_region() {
+        let endpoints = default_region_endpoints();
+
+        assert_eq!(endpoints.len(), PROBED_REGIONS.len());
+        assert!(endpoints.contains(&endpoint(
+            "ap-northeast-1",
+            "dynamodb.ap-northeast-1.amazonaws.com:80".to_string()
+        )));
+    }
+
+    #[test]
+    fn profile_region_comes_first() {
+        let profile = Profile {
+            name: "dev".to_string(),
+            region: Some("eu-west-1".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(default_region(Some(&profile)).as_deref(), Some("eu-west-1"));
+    }
+}
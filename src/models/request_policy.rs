@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use aws_config::{
+    retry::{ErrorKind, RetryConfig},
+    timeout::TimeoutConfig,
+    ConfigLoader, SdkConfig,
+};
+use aws_sdk_s3::config::{ConfigBag, RuntimeComponents};
+use aws_smithy_runtime_api::{
+    box_error::BoxError,
+    client::{
+        http::{
+            HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings,
+            SharedHttpClient, SharedHttpConnector,
+        },
+        interceptors::{context::FinalizerInterceptorContextRef, Intercept},
+        orchestrator::{HttpRequest, Metadata},
+        retries::{
+            classifiers::{ClassifyRetry, RetryAction, RetryReason},
+            RequestAttempts,
+        },
+    },
+};
+use once_cell::sync::Lazy;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::models::{
+    client_pool,
+    workspace::settings::{RequestSettings, RetryMode},
+};
+
+/// Set from the workspace settings, and applied to every client.
+static REQUEST_SETTINGS: Lazy<RwLock<RequestSettings>> =
+    Lazy::new(|| RwLock::new(RequestSettings::default()));
+
+/// Accounts by profile, once their caller identity is known.
+static ACCOUNTS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Request slots by account, or by profile while its account is unknown.
+static LIMITS: Lazy<Mutex<HashMap<String, Arc<Semaphore>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Must be called whenever a workspace is loaded.
+pub fn set_request_settings(settings: RequestSettings) {
+    let mut current = REQUEST_SETTINGS.write().unwrap();
+    if *current != settings {
+        *current = settings;
+        LIMITS.lock().unwrap().clear();
+        // Pooled configs were built with the previous settings.
+        client_pool::clear();
+    }
+}
+
+fn request_settings() -> RequestSettings {
+    REQUEST_SETTINGS.read().unwrap().clone()
+}
+
+/// Lets the profiles of one account share its request slots.
+pub fn set_profile_account(profile: &str, account: &str) {
+    ACCOUNTS
+        .lock()
+        .unwrap()
+        .insert(profile.to_string(), account.to_string());
+}
+
+/// Applies the retry and timeout settings. Unset ones are left to the SDK,
+/// which takes them from the profile or its own defaults.
+pub fn apply_to_loader(loader: ConfigLoader) -> ConfigLoader {
+    let settings = request_settings();
+    let mut loader = loader;
+    if let Some(retry_config) = retry_config(&settings) {
+        loader = loader.retry_config(retry_config);
+    }
+    if let Some(timeout_config) = timeout_config(&settings) {
+        loader = loader.timeout_config(timeout_config);
+    }
+    loader
+}
+
+fn retry_config(settings: &RequestSettings) -> Option<RetryConfig> {
+    if settings.retry_mode.is_none() && settings.max_attempts.is_none() {
+        return None;
+    }
+    let config = match settings.retry_mode.unwrap_or_default() {
+        RetryMode::Standard => RetryConfig::standard(),
+        RetryMode::Adaptive => RetryConfig::adaptive(),
+    };
+    Some(match settings.max_attempts {
+        Some(max_attempts) => config.with_max_attempts(max_attempts.max(1)),
+        None => config,
+    })
+}
+
+fn timeout_config(settings: &RequestSettings) -> Option<TimeoutConfig> {
+    if settings.connect_timeout_secs.is_none() && settings.operation_timeout_secs.is_none() {
+        return None;
+    }
+    let mut builder = TimeoutConfig::builder();
+    if let Some(secs) = settings.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = settings.operation_timeout_secs {
+        builder = builder.operation_timeout(Duration::from_secs(secs));
+    }
+    Some(builder.build())
+}
+
+/// Makes the requests of `profile` wait for a free slot of its account if
+/// the settings limit the concurrency.
+pub fn limit_concurrency(profile: &str, cfg: SdkConfig) -> SdkConfig {
+    let Some(max_concurrency) = request_settings().max_concurrency_per_account else {
+        return cfg;
+    };
+    let Some(inner) = cfg.http_client() else {
+        return cfg;
+    };
+    cfg.into_builder()
+        .http_client(ConcurrencyLimitedClient {
+            inner,
+            profile: profile.to_string(),
+            max_concurrency,
+        })
+        .build()
+}
+
+fn request_slots(profile: &str, max_concurrency: usize) -> Arc<Semaphore> {
+    let key = ACCOUNTS
+        .lock()
+        .unwrap()
+        .get(profile)
+        .cloned()
+        .unwrap_or_else(|| profile.to_string());
+    let mut limits = LIMITS.lock().unwrap();
+    limits
+        .entry(key)
+        .or_insert_with(|| Arc::new(Semaphore::new(max_concurrency.max(1))))
+        .clone()
+}
+
+async fn acquire_request_slot(
+    profile: &str,
+    max_concurrency: usize,
+) -> Option<OwnedSemaphorePermit> {
+    let slots = request_slots(profile, max_concurrency);
+    if slots.available_permits() == 0 {
+        tracing::debug!(profile, "Waiting for a free request slot of the account");
+    }
+    slots.acquire_owned().await.ok()
+}
+
+#[derive(Debug)]
+struct ConcurrencyLimitedClient {
+    inner: SharedHttpClient,
+    profile: String,
+    max_concurrency: usize,
+}
+
+impl HttpClient for ConcurrencyLimitedClient {
+    fn http_connector(
+        &self,
+        settings: &HttpConnectorSettings,
+        components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(ConcurrencyLimitedConnector {
+            inner: self.inner.http_connector(settings, components),
+            profile: self.profile.clone(),
+            max_concurrency: self.max_concurrency,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ConcurrencyLimitedConnector {
+    inner: SharedHttpConnector,
+    profile: String,
+    max_concurrency: usize,
+}
+
+impl HttpConnector for ConcurrencyLimitedConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let inner = self.inner.clone();
+        let profile = self.profile.clone();
+        let max_concurrency = self.max_concurrency;
+        HttpConnectorFuture::new(async move {
+            let _slot = acquire_request_slot(&profile, max_concurrency).await;
+            inner.call(request).await
+        })
+    }
+}
+
+/// Logs attempts that failed with a retryable error, so that throttling
+/// can be told apart from a slow network.
+#[derive(Debug)]
+pub struct RetryLogger {
+    profile: String,
+}
+
+impl RetryLogger {
+    pub fn new(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+        }
+    }
+}
+
+impl Intercept for RetryLogger {
+    fn name(&self) -> &'static str {
+        "RetryLogger"
+    }
+
+    fn read_after_attempt(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let mut action = RetryAction::NoActionIndicated;
+        for classifier in runtime_components.retry_classifiers() {
+            match classifier.classify_retry(context.inner()) {
+                RetryAction::NoActionIndicated => {}
+                RetryAction::RetryForbidden => return Ok(()),
+                a => action = a,
+            }
+        }
+        let RetryAction::RetryIndicated(RetryReason::RetryableError { kind, retry_after }) = action
+        else {
+            return Ok(());
+        };
+
+        let attempt = cfg.load::<RequestAttempts>().map(|a| a.attempts());
+        let operation = cfg
+            .load::<Metadata>()
+            .map(|m| format!("{}:{}", m.service(), m.name()));
+        let profile = self.profile.as_str();
+        match kind {
+            ErrorKind::ThrottlingError => tracing::warn!(
+                profile,
+                operation,
+                attempt,
+                ?retry_after,
+                "Request was throttled"
+            ),
+            kind => tracing::info!(
+                profile,
+                operation,
+                attempt,
+                reason = %kind,
+                "Request failed with a retryable error"
+            ),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_smithy_runtime_api::{client::orchestrator::HttpResponse, http::StatusCode};
+    use aws_smithy_types::body::SdkBody;
+
+    use super::*;
+
+    #[test]
+    fn unset_settings_keep_the_sdk_defaults() {
+        let settings = RequestSettings::default();
+
+        assert_eq!(retry_config(&settings), None);
+        assert_eq!(timeout_config(&settings), None);
+    }
+
+    #[test]
+    fn builds_the_retry_config() {
+        let settings = RequestSettings {
+            retry_mode: Some(RetryMode::Adaptive),
+            max_attempts: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            retry_config(&settings),
+            Some(RetryConfig::adaptive().with_max_attempts(5))
+        );
+
+        let settings = RequestSettings {
+            max_attempts: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            retry_config(&settings),
+            Some(RetryConfig::standard().with_max_attempts(1))
+        );
+    }
+
+    #[test]
+    fn builds_the_timeout_config() {
+        let settings = RequestSettings {
+            connect_timeout_secs: Some(5),
+            operation_timeout_secs: Some(60),
+            ..Default::default()
+        };
+        let config = timeout_config(&settings).unwrap();
+
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(config.operation_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(config.read_timeout(), None);
+    }
+
+    /// Answers after a moment and records how many requests it was serving
+    /// at once.
+    #[derive(Debug, Default)]
+    struct SlowConnector {
+        in_flight: Arc<Mutex<(usize, usize)>>,
+    }
+
+    impl HttpConnector for SlowConnector {
+        fn call(&self, _request: HttpRequest) -> HttpConnectorFuture {
+            let in_flight = self.in_flight.clone();
+            HttpConnectorFuture::new(async move {
+                {
+                    let mut in_flight = in_flight.lock().unwrap();
+                    in_flight.0 += 1;
+                    in_flight.1 = in_flight.1.max(in_flight.0);
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.lock().unwrap().0 -= 1;
+                Ok(HttpResponse::new(
+                    StatusCode::try_from(200).unwrap(),
+                    SdkBody::empty(),
+                ))
+            })
+        }
+    }
+
+    /// Accounts and limits are global, so every test uses profiles and
+    /// accounts of its own.
+    #[test]
+    fn profiles_of_one_account_share_its_request_slots() {
+        let a = request_slots("slots-a", 2);
+        assert!(!Arc::ptr_eq(&a, &request_slots("slots-b", 2)));
+
+        set_profile_account("slots-a", "slots-111111111111");
+        set_profile_account("slots-b", "slots-111111111111");
+        set_profile_account("slots-c", "slots-222222222222");
+
+        let a = request_slots("slots-a", 2);
+        assert!(Arc::ptr_eq(&a, &request_slots("slots-b", 2)));
+        assert!(!Arc::ptr_eq(&a, &request_slots("slots-c", 2)));
+        assert_eq!(a.available_permits(), 2);
+    }
+
+    #[test]
+    fn limits_the_requests_in_flight_per_account() {
+        set_profile_account("limited-a", "limited-111111111111");
+        set_profile_account("limited-b", "limited-111111111111");
+        let inner = SlowConnector::default();
+        let in_flight = inner.in_flight.clone();
+        let inner = SharedHttpConnector::new(inner);
+        let connector = |profile: &str| ConcurrencyLimitedConnector {
+            inner: inner.clone(),
+            profile: profile.to_string(),
+            max_concurrency: 2,
+        };
+        let (a, b) = (connector("limited-a"), connector("limited-b"));
+
+        let calls: Vec<_> = [&a, &b, &a, &b, &a]
+            .into_iter()
+            .map(|c| c.call(HttpRequest::empty()))
+            .collect();
+        let responses = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(iced::futures::future::join_all(calls));
+
+        assert!(responses.iter().all(|r| r.is_ok()));
+        assert_eq!(*in_flight.lock().unwrap(), (0, 2));
+    }
+
+    #[test]
+    fn reads_the_settings_from_yaml() {
+        let yaml = "retry_mode: adaptive\nmax_attempts: 5\nmax_concurrency_per_account: 8\n";
+        let settings: RequestSettings = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(settings.retry_mode, Some(RetryMode::Adaptive));
+        assert_eq!(settings.max_attempts, Some(5));
+        assert_eq!(settings.max_concurrency_per_account, Some(8));
+        assert_eq!(settings.connect_timeout_secs, None);
+    }
+}
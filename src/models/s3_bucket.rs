@@ -0,0 +1,93 @@
+use iced::futures::{stream, StreamExt};
+
+use crate::{
+    error::{Error, ResultExt},
+    models::{
+        client_pool::s3_client, s3_object::resolve_bucket_region, service::Service,
+        workspace::ResourceDescriptor,
+    },
+};
+
+const BUCKET_REGION_LOOKUP_CONCURRENCY: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BucketInfo {
+    pub bucket: aws_sdk_s3::types::Bucket,
+    pub descriptor: ResourceDescriptor,
+}
+
+/// Looks up the bucket's own region, which the returned descriptor points at.
+pub async fn load_s3_bucket(rd: &ResourceDescriptor) -> Result<BucketInfo, Error> {
+    let client = s3_client(&rd.profile, &rd.region).await;
+    let region = resolve_bucket_region(&client, &rd.id).await?;
+
+    Ok(BucketInfo {
+        bucket: aws_sdk_s3::types::Bucket::builder()
+            .name(rd.id.clone())
+            .build(),
+        descriptor: ResourceDescriptor {
+            region,
+            ..rd.clone()
+        },
+    })
+}
+
+/// Lists the buckets in `region`, or in every region if `all_regions` is
+/// set. ListBuckets returns the buckets of all regions at once, so each
+/// bucket's own region has to be looked up separately.
+pub async fn list_s3_buckets(
+    profile: &str,
+    region: &str,
+    service: &Service,
+    all_regions: bool,
+) -> Result<Vec<BucketInfo>, Error> {
+    let client = s3_client(profile, region).await;
+    let result: Result<Vec<_>, _> = client
+        .list_buckets()
+        .into_paginator()
+        .items()
+        .send()
+        .collect()
+        .await;
+
+    let mut buckets = result.during_in("s3:ListBuckets", profile, region)?;
+    buckets.sort_by_key(|b| b.name.clone());
+
+    let resolved: Vec<_> = stream::iter(buckets)
+        .map(|b| {
+            let client = &client;
+            async move {
+                let name = b.name.clone().unwrap_or_default();
+                let bucket_region = resolve_bucket_region(client, &name).await;
+                (b, bucket_region)
+            }
+        })
+        .buffered(BUCKET_REGION_LOOKUP_CONCURRENCY)
+        .collect()
+        .await;
+
+    Ok(resolved
+        .into_iter()
+        .filter_map(|(b, bucket_region)| {
+            // Without a region we cannot tell that the bucket is elsewhere,
+            // so keep it visible under the selected region.
+            let bucket_region = bucket_region.unwrap_or_else(|e| {
+                tracing::warn!(bucket = ?b.name, error = ?e, "Unable to resolve the region");
+                region.to_string()
+            });
+            if !all_regions && bucket_region != region {
+                return None;
+            }
+            let descriptor = ResourceDescriptor {
+                profile: profile.to_string(),
+                region: bucket_region,
+                service: service.clone(),
+                id: b.name.clone().unwrap_or_default(),
+            };
+            Some(BucketInfo {
+                bucket: b,
+                descriptor,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,412 @@
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_smithy_runtime_api::client::result::SdkError;
+
+use crate::models::{
+    client_pool::s3_client, s3_object::region_from_location_constraint,
+    workspace::ResourceDescriptor,
+};
+
+/// The state of one part of a bucket's configuration. Each part is fetched
+/// with its own API call, and any of them may be missing or forbidden
+/// without the others being affected.
+#[derive(Clone, Debug)]
+pub enum BucketSetting {
+    Configured(String),
+    NotConfigured,
+    AccessDenied,
+    Failed(String),
+}
+
+impl std::fmt::Display for BucketSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Configured(value) => write!(f, "{}", value),
+            Self::NotConfigured => write!(f, "Not configured"),
+            Self::AccessDenied => write!(f, "Access denied"),
+            Self::Failed(message) => write!(f, "Failed to load: {}", message),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct S3BucketConfig {
+    pub region: BucketSetting,
+    pub versioning: BucketSetting,
+    pub encryption: BucketSetting,
+    pub public_access_block: BucketSetting,
+    pub policy: BucketSetting,
+    pub ownership_controls: BucketSetting,
+    pub cors: BucketSetting,
+    pub lifecycle: BucketSetting,
+    pub replication: BucketSetting,
+    pub logging: BucketSetting,
+    pub notifications: BucketSetting,
+    pub tags: BucketSetting,
+}
+
+impl S3BucketConfig {
+    pub fn settings(&self) -> Vec<(&'static str, &BucketSetting)> {
+        vec![
+            ("Region", &self.region),
+            ("Versioning", &self.versioning),
+            ("Default encryption", &self.encryption),
+            ("Block public access", &self.public_access_block),
+            ("Bucket policy", &self.policy),
+            ("Object ownership", &self.ownership_controls),
+            ("CORS", &self.cors),
+            ("Lifecycle rules", &self.lifecycle),
+            ("Replication", &self.replication),
+            ("Server access logging", &self.logging),
+            ("Event notifications", &self.notifications),
+            ("Tags", &self.tags),
+        ]
+    }
+}
+
+// The error codes with which S3 answers for a bucket that lacks the setting.
+// Other codes, such as `NoSuchBucket`, are failures.
+const NO_ENCRYPTION: &[&str] = &["ServerSideEncryptionConfigurationNotFoundError"];
+const NO_PUBLIC_ACCESS_BLOCK: &[&str] = &["NoSuchPublicAccessBlockConfiguration"];
+const NO_POLICY: &[&str] = &["NoSuchBucketPolicy"];
+const NO_OWNERSHIP_CONTROLS: &[&str] = &["OwnershipControlsNotFoundError"];
+const NO_CORS: &[&str] = &["NoSuchCORSConfiguration"];
+const NO_LIFECYCLE: &[&str] = &["NoSuchLifecycleConfiguration"];
+const NO_REPLICATION: &[&str] = &["ReplicationConfigurationNotFoundError"];
+const NO_TAGS: &[&str] = &["NoSuchTagSet"];
+
+fn to_setting<T, E, R>(
+    result: Result<T, SdkError<E, R>>,
+    not_configured: &[&str],
+    f: impl FnOnce(T) -> BucketSetting,
+) -> BucketSetting
+where
+    E: ProvideErrorMetadata + std::fmt::Debug,
+    R: std::fmt::Debug,
+{
+    match result {
+        Ok(out) => f(out),
+        Err(e) => error_setting(e.code(), not_configured, || {
+            e.message()
+                .map(|m| m.to_string())
+                .unwrap_or_else(|| format!("{:?}", e))
+        }),
+    }
+}
+
+fn error_setting(
+    code: Option<&str>,
+    not_configured: &[&str],
+    message: impl FnOnce() -> String,
+) -> BucketSetting {
+    match code {
+        Some("AccessDenied") => BucketSetting::AccessDenied,
+        Some(code) if not_configured.contains(&code) => BucketSetting::NotConfigured,
+        _ => BucketSetting::Failed(message()),
+    }
+}
+
+fn lines_or_not_configured(lines: Vec<String>) -> BucketSetting {
+    if lines.is_empty() {
+        BucketSetting::NotConfigured
+    } else {
+        BucketSetting::Configured(lines.join("\n"))
+    }
+}
+
+fn flag(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "On",
+        Some(false) => "Off",
+        None => "-",
+    }
+}
+
+pub async fn load_bucket_config(bucket: ResourceDescriptor) -> S3BucketConfig {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    let name = bucket.id.as_str();
+
+    let (
+        location,
+        versioning,
+        encryption,
+        public_access_block,
+        policy,
+        ownership_controls,
+        cors,
+        lifecycle,
+        replication,
+        logging,
+        notifications,
+        tags,
+    ) = iced::futures::join!(
+        client.get_bucket_location().bucket(name).send(),
+        client.get_bucket_versioning().bucket(name).send(),
+        client.get_bucket_encryption().bucket(name).send(),
+        client.get_public_access_block().bucket(name).send(),
+        client.get_bucket_policy().bucket(name).send(),
+        client.get_bucket_ownership_controls().bucket(name).send(),
+        client.get_bucket_cors().bucket(name).send(),
+        client
+            .get_bucket_lifecycle_configuration()
+            .bucket(name)
+            .send(),
+        client.get_bucket_replication().bucket(name).send(),
+        client.get_bucket_logging().bucket(name).send(),
+        client
+            .get_bucket_notification_configuration()
+            .bucket(name)
+            .send(),
+        client.get_bucket_tagging().bucket(name).send(),
+    );
+
+    S3BucketConfig {
+        region: to_setting(location, &[], |out| {
+            let region =
+                region_from_location_constraint(out.location_constraint().map(|c| c.as_str()));
+            BucketSetting::Configured(region.to_string())
+        }),
+        versioning: to_setting(versioning, &[], |out| match out.status() {
+            None => BucketSetting::NotConfigured,
+            Some(status) => BucketSetting::Configured(format!(
+                "{} (MFA delete: {})",
+                status,
+                out.mfa_delete()
+                    .map(|m| m.to_string())
+                    .unwrap_or("Disabled".to_string())
+            )),
+        }),
+        encryption: to_setting(encryption, NO_ENCRYPTION, |out| {
+            let rules = out
+                .server_side_encryption_configuration()
+                .map(|c| c.rules())
+                .unwrap_or_default();
+            lines_or_not_configured(
+                rules
+                    .iter()
+                    .map(|rule| {
+                        let default = rule.apply_server_side_encryption_by_default();
+                        format!(
+                            "Algorithm: {}, KMS key: {}, Bucket key: {}",
+                            default
+                                .map(|d| d.sse_algorithm().to_string())
+                                .unwrap_or("-".to_string()),
+                            default.and_then(|d| d.kms_master_key_id()).unwrap_or("-"),
+                            flag(rule.bucket_key_enabled()),
+                        )
+                    })
+                    .collect(),
+            )
+        }),
+        public_access_block: to_setting(public_access_block, NO_PUBLIC_ACCESS_BLOCK, |out| {
+            match out.public_access_block_configuration() {
+                None => BucketSetting::NotConfigured,
+                Some(c) => BucketSetting::Configured(format!(
+                    "Block public ACLs: {}\nIgnore public ACLs: {}\nBlock public policy: {}\nRestrict public buckets: {}",
+                    flag(c.block_public_acls()),
+                    flag(c.ignore_public_acls()),
+                    flag(c.block_public_policy()),
+                    flag(c.restrict_public_buckets()),
+                )),
+            }
+        }),
+        policy: to_setting(policy, NO_POLICY, |out| match out.policy() {
+            None => BucketSetting::NotConfigured,
+            Some(policy) => BucketSetting::Configured(
+                serde_json::from_str::<serde_json::Value>(policy)
+                    .and_then(|v| serde_json::to_string_pretty(&v))
+                    .unwrap_or(policy.to_string()),
+            ),
+        }),
+        ownership_controls: to_setting(ownership_controls, NO_OWNERSHIP_CONTROLS, |out| {
+            lines_or_not_configured(
+                out.ownership_controls()
+                    .map(|c| c.rules())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|rule| rule.object_ownership().to_string())
+                    .collect(),
+            )
+        }),
+        cors: to_setting(cors, NO_CORS, |out| {
+            lines_or_not_configured(
+                out.cors_rules()
+                    .iter()
+                    .map(|rule| {
+                        format!(
+                            "{}Origins: {}; Methods: {}; Headers: {}; Expose: {}; Max age: {}",
+                            rule.id().map(|id| format!("[{}] ", id)).unwrap_or_default(),
+                            rule.allowed_origins().join(", "),
+                            rule.allowed_methods().join(", "),
+                            rule.allowed_headers().join(", "),
+                            rule.expose_headers().join(", "),
+                            rule.max_age_seconds()
+                                .map(|s| format!("{}s", s))
+                                .unwrap_or("-".to_string()),
+                        )
+                    })
+                    .collect(),
+            )
+        }),
+        lifecycle: to_setting(lifecycle, NO_LIFECYCLE, |out| {
+            lines_or_not_configured(
+                out.rules()
+                    .iter()
+                    .map(|rule| {
+                        let mut parts = vec![format!(
+                            "{} ({})",
+                            rule.id().unwrap_or("Unnamed rule"),
+                            rule.status()
+                        )];
+                        if let Some(prefix) = rule
+                            .filter()
+                            .and_then(|f| f.as_prefix().ok())
+                            .map(|p| p.as_str())
+                        {
+                            parts.push(format!("prefix '{}'", prefix));
+                        }
+                        for t in rule.transitions() {
+                            parts.push(format!(
+                                "to {} after {} day(s)",
+                                t.storage_class()
+                                    .map(|s| s.to_string())
+                                    .unwrap_or("-".to_string()),
+                                t.days().unwrap_or(0)
+                            ));
+                        }
+                        if let Some(days) = rule.expiration().and_then(|e| e.days()) {
+                            parts.push(format!("expire after {} day(s)", days));
+                        }
+                        if let Some(days) = rule
+                            .noncurrent_version_expiration()
+                            .and_then(|e| e.noncurrent_days())
+                        {
+                            parts.push(format!("expire noncurrent versions after {} day(s)", days));
+                        }
+                        if let Some(days) = rule
+                            .abort_incomplete_multipart_upload()
+                            .and_then(|a| a.days_after_initiation())
+                        {
+                            parts.push(format!("abort incomplete uploads after {} day(s)", days));
+                        }
+                        parts.join(", ")
+                    })
+                    .collect(),
+            )
+        }),
+        replication: to_setting(replication, NO_REPLICATION, |out| {
+            match out.replication_configuration() {
+                None => BucketSetting::NotConfigured,
+                Some(c) => {
+                    let mut lines = vec![format!("Role: {}", c.role())];
+                    for rule in c.rules() {
+                        lines.push(format!(
+                            "{} ({}) -> {}{}",
+                            rule.id().unwrap_or("Unnamed rule"),
+                            rule.status(),
+                            rule.destination().map(|d| d.bucket()).unwrap_or("-"),
+                            rule.destination()
+                                .and_then(|d| d.storage_class())
+                                .map(|s| format!(" as {}", s))
+                                .unwrap_or_default(),
+                        ));
+                    }
+                    BucketSetting::Configured(lines.join("\n"))
+                }
+            }
+        }),
+        logging: to_setting(logging, &[], |out| match out.logging_enabled() {
+            None => BucketSetting::NotConfigured,
+            Some(l) => BucketSetting::Configured(format!(
+                "s3://{}/{}",
+                l.target_bucket(),
+                l.target_prefix()
+            )),
+        }),
+        notifications: to_setting(notifications, &[], |out| {
+            let events = |events: &[aws_sdk_s3::types::Event]| {
+                events
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let mut lines = vec![];
+            for c in out.topic_configurations() {
+                lines.push(format!("SNS {} on {}", c.topic_arn(), events(c.events())));
+            }
+            for c in out.queue_configurations() {
+                lines.push(format!("SQS {} on {}", c.queue_arn(), events(c.events())));
+            }
+            for c in out.lambda_function_configurations() {
+                lines.push(format!(
+                    "Lambda {} on {}",
+                    c.lambda_function_arn(),
+                    events(c.events())
+                ));
+            }
+            if out.event_bridge_configuration().is_some() {
+                lines.push("Amazon EventBridge: On".to_string());
+            }
+            lines_or_not_configured(lines)
+        }),
+        tags: to_setting(tags, NO_TAGS, |out| {
+            lines_or_not_configured(
+                out.tag_set()
+                    .iter()
+                    .map(|t| format!("{} = {}", t.key(), t.value()))
+                    .collect(),
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(code: Option<&str>, not_configured: &[&str]) -> BucketSetting {
+        error_setting(code, not_configured, || "failed".to_string())
+    }
+
+    #[test]
+    fn only_the_expected_codes_mean_not_configured() {
+        assert!(matches!(
+            setting(Some("NoSuchLifecycleConfiguration"), NO_LIFECYCLE),
+            BucketSetting::NotConfigured
+        ));
+        assert!(matches!(
+            setting(
+                Some("ServerSideEncryptionConfigurationNotFoundError"),
+                NO_ENCRYPTION
+            ),
+            BucketSetting::NotConfigured
+        ));
+        // Another setting's code is not this one's.
+        assert!(matches!(
+            setting(Some("NoSuchTagSet"), NO_LIFECYCLE),
+            BucketSetting::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn missing_buckets_are_failures() {
+        for not_configured in [NO_POLICY, NO_CORS, NO_TAGS, &[]] {
+            assert!(matches!(
+                setting(Some("NoSuchBucket"), not_configured),
+                BucketSetting::Failed(message) if message == "failed"
+            ));
+        }
+    }
+
+    #[test]
+    fn access_denied_and_unknown_errors() {
+        assert!(matches!(
+            setting(Some("AccessDenied"), NO_REPLICATION),
+            BucketSetting::AccessDenied
+        ));
+        assert!(matches!(
+            setting(None, NO_REPLICATION),
+            BucketSetting::Failed(_)
+        ));
+    }
+}
@@ -0,0 +1,990 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use aws_sdk_s3::{
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart, ServerSideEncryption, StorageClass},
+};
+use aws_smithy_types::DateTime;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+
+use crate::{
+    error::{Error, ResultExt},
+    models::{
+        client_pool::s3_client,
+        transfer::{ProgressReporter, TransferOutcome},
+        workspace::ResourceDescriptor,
+    },
+};
+
+/// Objects larger than this are not fetched for an inline preview.
+const PREVIEW_SIZE_LIMIT: i64 = 4 * 1024 * 1024;
+
+/// Upper bound for the decompressed size of a gzipped preview.
+const GZIP_PREVIEW_SIZE_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Files of this size or larger are uploaded with a multipart upload.
+const MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// The smallest part of a multipart upload. Larger files get larger parts,
+/// as S3 accepts at most `MAX_MULTIPART_PARTS` of them.
+const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+const MAX_MULTIPART_PARTS: u64 = 10_000;
+
+/// The largest object that a single CopyObject request can copy.
+const COPY_OBJECT_SIZE_LIMIT: i64 = 5 * 1024 * 1024 * 1024;
+
+/// Characters that must be escaped in the key part of a copy source.
+const COPY_SOURCE_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+pub const STORAGE_CLASSES: &[StorageClass] = &[
+    StorageClass::Standard,
+    StorageClass::StandardIa,
+    StorageClass::OnezoneIa,
+    StorageClass::IntelligentTiering,
+    StorageClass::GlacierIr,
+    StorageClass::Glacier,
+    StorageClass::DeepArchive,
+    StorageClass::ReducedRedundancy,
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum S3ObjectEntry {
+    Prefix(String),
+    Object(S3ObjectInfo),
+    Version(S3ObjectVersion),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct S3ObjectInfo {
+    pub key: String,
+    pub size: Option<i64>,
+    pub last_modified: Option<String>,
+    pub storage_class: Option<String>,
+}
+
+impl S3ObjectInfo {
+    pub fn file_name(&self) -> &str {
+        file_name_of_key(&self.key)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct S3ObjectVersion {
+    pub key: String,
+    pub version_id: String,
+    pub is_latest: bool,
+    pub is_delete_marker: bool,
+    pub size: Option<i64>,
+    pub last_modified: Option<DateTime>,
+}
+
+impl S3ObjectVersion {
+    pub fn file_name(&self) -> &str {
+        file_name_of_key(&self.key)
+    }
+}
+
+/// A change to the version history of an object. Both kinds replace what
+/// `GetObject` returns for the key, so they are only run after confirmation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum S3VersionAction {
+    /// Copies an old version over the current one.
+    Restore { key: String, version_id: String },
+    /// Deletes a delete marker. If it is the latest version, the object
+    /// becomes visible again.
+    RemoveDeleteMarker { key: String, version_id: String },
+}
+
+impl std::fmt::Display for S3VersionAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Restore { key, version_id } => write!(
+                f,
+                "Restore version {} of {} over the current version",
+                version_id, key
+            ),
+            Self::RemoveDeleteMarker { key, version_id } => {
+                write!(f, "Remove delete marker {} of {}", version_id, key)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct S3ObjectPreview {
+    pub key: String,
+    pub size: Option<i64>,
+    pub content_type: Option<String>,
+    pub content: PreviewContent,
+}
+
+#[derive(Clone, Debug)]
+pub enum PreviewContent {
+    Text(String),
+    Json(String),
+    Image(Vec<u8>),
+    Binary,
+    TooLarge,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ServerSideEncryptionOption {
+    #[default]
+    None,
+    Aes256,
+    AwsKms,
+}
+
+impl ServerSideEncryptionOption {
+    pub const ALL: &'static [Self] = &[Self::None, Self::Aes256, Self::AwsKms];
+
+    fn to_sdk(self) -> Option<ServerSideEncryption> {
+        match self {
+            Self::None => None,
+            Self::Aes256 => Some(ServerSideEncryption::Aes256),
+            Self::AwsKms => Some(ServerSideEncryption::AwsKms),
+        }
+    }
+}
+
+impl std::fmt::Display for ServerSideEncryptionOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "Bucket default"),
+            Self::Aes256 => write!(f, "SSE-S3 (AES256)"),
+            Self::AwsKms => write!(f, "SSE-KMS"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UploadOptions {
+    pub content_type: Option<String>,
+    pub storage_class: Option<StorageClass>,
+    pub encryption: ServerSideEncryptionOption,
+    pub kms_key_id: Option<String>,
+}
+
+pub fn region_from_location_constraint(constraint: Option<&str>) -> &str {
+    // Buckets in us-east-1 report no location constraint, and very old
+    // buckets in eu-west-1 still report the legacy "EU" constraint.
+    match constraint {
+        None | Some("") => "us-east-1",
+        Some("EU") => "eu-west-1",
+        Some(region) => region,
+    }
+}
+
+/// Finds the region a bucket lives in. `client` may be for any region.
+pub async fn resolve_bucket_region(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+) -> Result<String, Error> {
+    // HeadBucket reports the region in a header, even when it is answered
+    // with a redirect or AccessDenied.
+    let region = match client.head_bucket().bucket(bucket).send().await {
+        Ok(out) => out.bucket_region().map(|r| r.to_string()),
+        Err(e) => {
+            if e.as_service_error().is_some_and(|e| e.is_not_found()) {
+                return Err(Error::ResourceNotFound);
+            }
+            e.raw_response()
+                .and_then(|r| r.headers().get("x-amz-bucket-region"))
+                .map(|r| r.to_string())
+        }
+    };
+    if let Some(region) = region {
+        return Ok(region);
+    }
+
+    let out = client.get_bucket_location().bucket(bucket).send().await?;
+    Ok(region_from_location_constraint(out.location_constraint().map(|c| c.as_str())).to_string())
+}
+
+pub fn file_name_of_key(key: &str) -> &str {
+    key.trim_end_matches('/').rsplit('/').next().unwrap_or(key)
+}
+
+pub fn parent_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(pos) => trimmed[..=pos].to_string(),
+        None => String::new(),
+    }
+}
+
+pub async fn list_objects(
+    bucket: ResourceDescriptor,
+    prefix: String,
+) -> Result<Vec<S3ObjectEntry>, Error> {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    let pages: Result<Vec<_>, _> = client
+        .list_objects_v2()
+        .bucket(bucket.id.clone())
+        .prefix(prefix)
+        .delimiter("/")
+        .into_paginator()
+        .send()
+        .collect()
+        .await;
+
+    let mut entries = vec![];
+    for page in pages.during("s3:ListObjectsV2", Some(&bucket))? {
+        for p in page.common_prefixes() {
+            if let Some(prefix) = p.prefix() {
+                entries.push(S3ObjectEntry::Prefix(prefix.to_string()));
+            }
+        }
+        for o in page.contents() {
+            let Some(key) = o.key() else {
+                continue;
+            };
+            entries.push(S3ObjectEntry::Object(S3ObjectInfo {
+                key: key.to_string(),
+                size: o.size(),
+                last_modified: o.last_modified().map(|d| d.to_string()),
+                storage_class: o.storage_class().map(|s| s.to_string()),
+            }));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Lists every version and delete marker directly under `prefix`, newest
+/// first for each key.
+pub async fn list_object_versions(
+    bucket: ResourceDescriptor,
+    prefix: String,
+) -> Result<Vec<S3ObjectEntry>, Error> {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+
+    let mut prefixes = vec![];
+    let mut versions = vec![];
+    let mut key_marker = None;
+    let mut version_id_marker = None;
+    loop {
+        let page = client
+            .list_object_versions()
+            .bucket(bucket.id.clone())
+            .prefix(prefix.clone())
+            .delimiter("/")
+            .set_key_marker(key_marker)
+            .set_version_id_marker(version_id_marker)
+            .send()
+            .await
+            .during("s3:ListObjectVersions", Some(&bucket))?;
+
+        for p in page.common_prefixes() {
+            if let Some(prefix) = p.prefix() {
+                prefixes.push(S3ObjectEntry::Prefix(prefix.to_string()));
+            }
+        }
+        for v in page.versions() {
+            versions.push(S3ObjectVersion {
+                key: v.key().unwrap_or_default().to_string(),
+                version_id: v.version_id().unwrap_or("null").to_string(),
+                is_latest: v.is_latest().unwrap_or(false),
+                is_delete_marker: false,
+                size: v.size(),
+                last_modified: v.last_modified().copied(),
+            });
+        }
+        for m in page.delete_markers() {
+            versions.push(S3ObjectVersion {
+                key: m.key().unwrap_or_default().to_string(),
+                version_id: m.version_id().unwrap_or("null").to_string(),
+                is_latest: m.is_latest().unwrap_or(false),
+                is_delete_marker: true,
+                size: None,
+                last_modified: m.last_modified().copied(),
+            });
+        }
+
+        if !page.is_truncated().unwrap_or(false) {
+            break;
+        }
+        key_marker = page.next_key_marker().map(|m| m.to_string());
+        version_id_marker = page.next_version_id_marker().map(|m| m.to_string());
+    }
+
+    sort_versions(&mut versions);
+    prefixes.extend(versions.into_iter().map(S3ObjectEntry::Version));
+    Ok(prefixes)
+}
+
+/// Groups versions by key, newest first. Versions without a timestamp come
+/// last.
+fn sort_versions(versions: &mut [S3ObjectVersion]) {
+    versions.sort_by(|a, b| {
+        a.key
+            .cmp(&b.key)
+            .then_with(|| b.last_modified.cmp(&a.last_modified))
+    });
+}
+
+pub async fn apply_version_action(
+    bucket: ResourceDescriptor,
+    action: S3VersionAction,
+) -> Result<(), Error> {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    match action {
+        S3VersionAction::Restore { key, version_id } => {
+            let head = client
+                .head_object()
+                .bucket(bucket.id.clone())
+                .key(key.clone())
+                .version_id(version_id.clone())
+                .send()
+                .await
+                .during("s3:HeadObject", Some(&bucket))?;
+            if head.content_length().unwrap_or(0) > COPY_OBJECT_SIZE_LIMIT {
+                return Err(Error::Unsupported(
+                    "Versions larger than 5 GiB cannot be restored by copying".to_string(),
+                ));
+            }
+
+            client
+                .copy_object()
+                .bucket(bucket.id.clone())
+                .key(key.clone())
+                .copy_source(format!(
+                    "{}/{}?versionId={}",
+                    bucket.id,
+                    utf8_percent_encode(&key, COPY_SOURCE_ENCODE_SET),
+                    utf8_percent_encode(&version_id, NON_ALPHANUMERIC)
+                ))
+                .send()
+                .await
+                .during("s3:CopyObject", Some(&bucket))?;
+        }
+        S3VersionAction::RemoveDeleteMarker { key, version_id } => {
+            client
+                .delete_object()
+                .bucket(bucket.id.clone())
+                .key(key)
+                .version_id(version_id)
+                .send()
+                .await
+                .during("s3:DeleteObject", Some(&bucket))?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn load_object_preview(
+    bucket: ResourceDescriptor,
+    key: String,
+) -> Result<S3ObjectPreview, Error> {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    let head = client
+        .head_object()
+        .bucket(bucket.id.clone())
+        .key(key.clone())
+        .send()
+        .await
+        .during("s3:HeadObject", Some(&bucket))?;
+
+    let size = head.content_length();
+    let content_type = head.content_type().map(|s| s.to_string());
+    let mut preview = S3ObjectPreview {
+        key: key.clone(),
+        size,
+        content_type: content_type.clone(),
+        content: PreviewContent::TooLarge,
+    };
+    if size.unwrap_or(0) > PREVIEW_SIZE_LIMIT {
+        return Ok(preview);
+    }
+
+    let out = client
+        .get_object()
+        .bucket(bucket.id.clone())
+        .key(key.clone())
+        .send()
+        .await
+        .during("s3:GetObject", Some(&bucket))?;
+    let gzipped = out.content_encoding() == Some("gzip");
+    let bytes = out.body.collect().await?.into_bytes().to_vec();
+
+    preview.content = classify_preview(&key, content_type.as_deref(), gzipped, bytes)?;
+    Ok(preview)
+}
+
+fn classify_preview(
+    key: &str,
+    content_type: Option<&str>,
+    gzipped: bool,
+    bytes: Vec<u8>,
+) -> Result<PreviewContent, Error> {
+    let content_type = content_type.unwrap_or("");
+    let key = key.to_lowercase();
+
+    if gzipped || key.ends_with(".gz") || bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(bytes.as_slice())
+            .take(GZIP_PREVIEW_SIZE_LIMIT)
+            .read_to_end(&mut decompressed)?;
+        let inner_key = key.trim_end_matches(".gz");
+        return classify_preview(inner_key, None, false, decompressed);
+    }
+
+    let is_image = content_type.starts_with("image/")
+        || [".png", ".jpg", ".jpeg", ".gif", ".bmp", ".webp", ".ico"]
+            .iter()
+            .any(|ext| key.ends_with(ext));
+    if is_image {
+        return Ok(PreviewContent::Image(bytes));
+    }
+
+    let Ok(text) = String::from_utf8(bytes) else {
+        return Ok(PreviewContent::Binary);
+    };
+
+    let is_json = content_type.contains("json") || key.ends_with(".json");
+    if is_json {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) {
+            if let Ok(pretty) = serde_json::to_string_pretty(&value) {
+                return Ok(PreviewContent::Json(pretty));
+            }
+        }
+    }
+
+    Ok(PreviewContent::Text(text))
+}
+
+pub async fn download_object(
+    bucket: ResourceDescriptor,
+    key: String,
+    version_id: Option<String>,
+    destination: PathBuf,
+    mut progress: ProgressReporter,
+) -> Result<TransferOutcome, Error> {
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    let out = client
+        .get_object()
+        .bucket(bucket.id.clone())
+        .key(key)
+        .set_version_id(version_id)
+        .send()
+        .await
+        .during("s3:GetObject", Some(&bucket))?;
+    let total = out.content_length().map(|l| l as u64);
+
+    let partial = partial_path(&destination);
+    let mut file = File::create(&partial)
+        .await
+        .map_err(|e| Error::io(&partial, e))?;
+    let mut body = out.body;
+    let mut transferred = 0;
+    progress.report(transferred, total);
+
+    loop {
+        if progress.is_cancelled() {
+            drop(file);
+            let _ = fs::remove_file(&partial).await;
+            return Ok(TransferOutcome::Cancelled);
+        }
+        let chunk = match body.try_next().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                drop(file);
+                let _ = fs::remove_file(&partial).await;
+                return Err(Error::from(e).during("s3:GetObject", Some(&bucket)));
+            }
+        };
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| Error::io(&partial, e))?;
+        transferred += chunk.len() as u64;
+        progress.report(transferred, total);
+    }
+
+    file.flush().await.map_err(|e| Error::io(&partial, e))?;
+    drop(file);
+    fs::rename(&partial, &destination)
+        .await
+        .map_err(|e| Error::io(&destination, e))?;
+    Ok(TransferOutcome::Completed)
+}
+
+/// Downloads are written into a sibling file first, so that a cancelled or
+/// failed download never leaves a truncated file under the final name.
+fn partial_path(destination: &Path) -> PathBuf {
+    let mut partial = destination.as_os_str().to_os_string();
+    partial.push(".part");
+    PathBuf::from(partial)
+}
+
+struct UploadItem {
+    path: PathBuf,
+    key: String,
+    size: u64,
+}
+
+/// Uploads a single file, or every file below a directory, under `prefix`.
+/// A directory keeps its own name as the first path segment of the keys.
+pub async fn upload_path(
+    bucket: ResourceDescriptor,
+    local_path: PathBuf,
+    prefix: String,
+    options: UploadOptions,
+    mut progress: ProgressReporter,
+) -> Result<TransferOutcome, Error> {
+    let base_key = format!(
+        "{}{}",
+        prefix,
+        local_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+    let items = collect_upload_items(&local_path, base_key).await?;
+
+    let total: u64 = items.iter().map(|item| item.size).sum();
+    let mut transferred = 0;
+    progress.report(transferred, Some(total));
+
+    let client = s3_client(&bucket.profile, &bucket.region).await;
+    for item in items {
+        if progress.is_cancelled() {
+            return Ok(TransferOutcome::Cancelled);
+        }
+
+        let outcome = if item.size >= MULTIPART_THRESHOLD {
+            upload_multipart(
+                &client,
+                &bucket,
+                &item,
+                &options,
+                &mut progress,
+                &mut transferred,
+                total,
+            )
+            .await?
+        } else {
+            let body = fs::read(&item.path)
+                .await
+                .map_err(|e| Error::io(&item.path, e))?;
+            client
+                .put_object()
+                .bucket(bucket.id.clone())
+                .key(item.key.clone())
+                .set_content_type(content_type_for(&item.path, &options))
+                .set_storage_class(options.storage_class.clone())
+                .set_server_side_encryption(options.encryption.to_sdk())
+                .set_ssekms_key_id(kms_key_id_for(&options))
+                .body(ByteStream::from(body))
+                .send()
+                .await
+                .during("s3:PutObject", Some(&bucket))?;
+            transferred += item.size;
+            progress.report(transferred, Some(total));
+            TransferOutcome::Completed
+        };
+
+        if let TransferOutcome::Cancelled = outcome {
+            return Ok(outcome);
+        }
+    }
+
+    Ok(TransferOutcome::Completed)
+}
+
+/// The files below `path` in the order of their keys, each directory's
+/// entries sorted by name. Symlinks to files are followed, symlinks to
+/// directories below `path` are skipped, as they may lead back up the tree.
+async fn collect_upload_items(root: &Path, key: String) -> Result<Vec<UploadItem>, Error> {
+    let mut items = vec![];
+    // Walked with a stack, as async functions cannot recurse without boxing.
+    let mut pending = vec![(root.to_path_buf(), key)];
+    while let Some((path, key)) = pending.pop() {
+        let is_link = fs::symlink_metadata(&path)
+            .await
+            .map_err(|e| Error::io(&path, e))?
+            .is_symlink();
+        let metadata = fs::metadata(&path).await.map_err(|e| Error::io(&path, e))?;
+        if metadata.is_dir() && is_link && path != root {
+            tracing::info!(path = %path.display(), "Skipping a symlinked directory");
+            continue;
+        }
+        if !metadata.is_dir() {
+            items.push(UploadItem {
+                path,
+                key,
+                size: metadata.len(),
+            });
+            continue;
+        }
+
+        let mut entries = vec![];
+        let mut read_dir = fs::read_dir(&path).await.map_err(|e| Error::io(&path, e))?;
+        while let Some(entry) = read_dir
+            .next_entry()
+            .await
+            .map_err(|e| Error::io(&path, e))?
+        {
+            entries.push(entry);
+        }
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries.into_iter().rev() {
+            let child_key = format!("{}/{}", key, entry.file_name().to_string_lossy());
+            pending.push((entry.path(), child_key));
+        }
+    }
+    Ok(items)
+}
+
+async fn upload_multipart(
+    client: &aws_sdk_s3::Client,
+    bucket: &ResourceDescriptor,
+    item: &UploadItem,
+    options: &UploadOptions,
+    progress: &mut ProgressReporter,
+    transferred: &mut u64,
+    total: u64,
+) -> Result<TransferOutcome, Error> {
+    let created = client
+        .create_multipart_upload()
+        .bucket(bucket.id.clone())
+        .key(item.key.clone())
+        .set_content_type(content_type_for(&item.path, options))
+        .set_storage_class(options.storage_class.clone())
+        .set_server_side_encryption(options.encryption.to_sdk())
+        .set_ssekms_key_id(kms_key_id_for(options))
+        .send()
+        .await
+        .during("s3:CreateMultipartUpload", Some(bucket))?;
+    let Some(upload_id) = created.upload_id().map(|id| id.to_string()) else {
+        return Err(Error::InvalidResponse {
+            operation: "s3:CreateMultipartUpload".to_string(),
+            message: "no upload ID".to_string(),
+        }
+        .during("s3:CreateMultipartUpload", Some(bucket)));
+    };
+
+    let result = upload_parts(
+        client,
+        bucket,
+        item,
+        &upload_id,
+        progress,
+        transferred,
+        total,
+    )
+    .await;
+    let parts = match result {
+        Ok(Some(parts)) => parts,
+        other => {
+            // Incomplete multipart uploads keep being billed, so clean up.
+            let _ = client
+                .abort_multipart_upload()
+                .bucket(bucket.id.clone())
+                .key(item.key.clone())
+                .upload_id(upload_id)
+                .send()
+                .await;
+            return other.map(|_| TransferOutcome::Cancelled);
+        }
+    };
+
+    client
+        .complete_multipart_upload()
+        .bucket(bucket.id.clone())
+        .key(item.key.clone())
+        .upload_id(upload_id)
+        .multipart_upload(
+            CompletedMultipartUpload::builder()
+                .set_parts(Some(parts))
+                .build(),
+        )
+        .send()
+        .await
+        .during("s3:CompleteMultipartUpload", Some(bucket))?;
+
+    Ok(TransferOutcome::Completed)
+}
+
+/// Returns `None` when the upload was cancelled between two parts.
+async fn upload_parts(
+    client: &aws_sdk_s3::Client,
+    bucket: &ResourceDescriptor,
+    item: &UploadItem,
+    upload_id: &str,
+    progress: &mut ProgressReporter,
+    transferred: &mut u64,
+    total: u64,
+) -> Result<Option<Vec<CompletedPart>>, Error> {
+    let mut file = File::open(&item.path)
+        .await
+        .map_err(|e| Error::io(&item.path, e))?;
+    let part_size = part_size(item.size);
+    let mut parts = vec![];
+    let mut part_number = 1;
+
+    loop {
+        if progress.is_cancelled() {
+            return Ok(None);
+        }
+
+        let mut buf = vec![];
+        let read = (&mut file)
+            .take(part_size)
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|e| Error::io(&item.path, e))?;
+        if read == 0 {
+            break;
+        }
+
+        let out = client
+            .upload_part()
+            .bucket(bucket.id.clone())
+            .key(item.key.clone())
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(buf))
+            .send()
+            .await
+            .during("s3:UploadPart", Some(bucket))?;
+        parts.push(
+            CompletedPart::builder()
+                .part_number(part_number)
+                .set_e_tag(out.e_tag().map(|t| t.to_string()))
+                .build(),
+        );
+
+        *transferred += read as u64;
+        progress.report(*transferred, Some(total));
+        part_number += 1;
+    }
+
+    Ok(Some(parts))
+}
+
+fn part_size(file_size: u64) -> u64 {
+    MULTIPART_PART_SIZE.max(file_size.div_ceil(MAX_MULTIPART_PARTS))
+}
+
+fn content_type_for(path: &Path, options: &UploadOptions) -> Option<String> {
+    if options.content_type.is_some() {
+        return options.content_type.clone();
+    }
+
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let content_type = match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+    Some(content_type.to_string())
+}
+
+fn kms_key_id_for(options: &UploadOptions) -> Option<String> {
+    match options.encryption {
+        ServerSideEncryptionOption::AwsKms => options.kms_key_id.clone(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn classifies_previews() {
+        let preview = |key, content_type, bytes: &[u8]| {
+            classify_preview(key, content_type, false, bytes.to_vec()).unwrap()
+        };
+
+        assert!(matches!(
+            preview("a.json", None, b"{\"a\":1}"),
+            PreviewContent::Json(json) if json == "{\n  \"a\": 1\n}"
+        ));
+        // Invalid JSON is still worth showing as it is.
+        assert!(matches!(
+            preview("a.json", None, b"{\"a\":"),
+            PreviewContent::Text(text) if text == "{\"a\":"
+        ));
+        assert!(matches!(
+            preview("data", Some("application/json"), b"[]"),
+            PreviewContent::Json(_)
+        ));
+        assert!(matches!(
+            preview("a.txt", None, b"hello"),
+            PreviewContent::Text(text) if text == "hello"
+        ));
+        assert!(matches!(
+            preview("photo.PNG", None, &[0x89, 0x50]),
+            PreviewContent::Image(_)
+        ));
+        assert!(matches!(
+            preview("blob", Some("image/webp"), b"RIFF"),
+            PreviewContent::Image(_)
+        ));
+        assert!(matches!(
+            preview("a.bin", None, &[0xff, 0xfe, 0x00]),
+            PreviewContent::Binary
+        ));
+    }
+
+    #[test]
+    fn decompresses_gzipped_previews() {
+        let compressed = gzip(b"{\"a\":1}");
+
+        // By extension, by encoding and by magic number.
+        for (key, gzipped) in [("a.json.gz", false), ("a.json", true), ("a.json", false)] {
+            let content = classify_preview(key, None, gzipped, compressed.clone()).unwrap();
+            assert!(matches!(content, PreviewContent::Json(_)), "{}", key);
+        }
+        assert!(classify_preview("a.gz", None, false, b"not gzip".to_vec()).is_err());
+    }
+
+    fn version(key: &str, id: &str, secs: i64, nanos: u32, marker: bool) -> S3ObjectVersion {
+        S3ObjectVersion {
+            key: key.to_string(),
+            version_id: id.to_string(),
+            is_latest: false,
+            is_delete_marker: marker,
+            size: (!marker).then_some(1),
+            last_modified: Some(DateTime::from_secs_and_nanos(secs, nanos)),
+        }
+    }
+
+    #[test]
+    fn sorts_versions_newest_first_per_key() {
+        // Formatted, these would compare as "...:40.5Z" < "...:40Z".
+        let mut versions = vec![
+            version("b", "b1", 100, 0, false),
+            version("a", "a1", 100, 0, false),
+            version("a", "marker", 100, 500_000_000, true),
+            version("a", "a2", 99, 999_000_000, false),
+            S3ObjectVersion {
+                last_modified: None,
+                ..version("a", "unknown", 0, 0, false)
+            },
+        ];
+        sort_versions(&mut versions);
+
+        let ids: Vec<_> = versions.iter().map(|v| v.version_id.as_str()).collect();
+        assert_eq!(ids, vec!["marker", "a1", "a2", "unknown", "b1"]);
+        assert!(versions[0].is_delete_marker);
+        assert_eq!(versions[0].size, None);
+    }
+
+    #[test]
+    fn downloads_go_to_a_sibling_part_file() {
+        assert_eq!(
+            partial_path(Path::new("/tmp/report.csv")),
+            PathBuf::from("/tmp/report.csv.part")
+        );
+        assert_eq!(
+            partial_path(Path::new("noext")),
+            PathBuf::from("noext.part")
+        );
+    }
+
+    #[test]
+    fn collects_upload_items_in_key_order() {
+        let dir = std::env::temp_dir().join(format!("awsome-upload-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(dir.join("b")).unwrap();
+        std::fs::write(dir.join("c.txt"), "ccc").unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("b").join("d.txt"), "dd").unwrap();
+
+        let items = block_on(collect_upload_items(&dir, "up".to_string())).unwrap();
+        let keys: Vec<_> = items.iter().map(|i| (i.key.as_str(), i.size)).collect();
+        assert_eq!(
+            keys,
+            vec![("up/a.txt", 1), ("up/b/d.txt", 2), ("up/c.txt", 3)]
+        );
+
+        let single = block_on(collect_upload_items(&dir.join("a.txt"), "a".to_string())).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].key, "a");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn upload_errors_name_the_path() {
+        let missing = std::env::temp_dir().join(format!("awsome-upload-{}", ulid::Ulid::new()));
+
+        let Err(Error::Io(e)) = block_on(collect_upload_items(&missing, "up".to_string())) else {
+            panic!("the path does not exist");
+        };
+        assert_eq!(e.path, Some(missing));
+        assert_eq!(e.kind, std::io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_symlinked_directories() {
+        let dir = std::env::temp_dir().join(format!("awsome-upload-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(dir.join("b")).unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("b").join("loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("a.txt"), dir.join("link.txt")).unwrap();
+
+        let items = block_on(collect_upload_items(&dir, "up".to_string())).unwrap();
+        let keys: Vec<_> = items.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, vec!["up/a.txt", "up/link.txt"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn grows_parts_to_stay_within_the_part_limit() {
+        let gib = 1024 * 1024 * 1024;
+        assert_eq!(part_size(20 * 1024 * 1024), MULTIPART_PART_SIZE);
+        assert_eq!(part_size(78 * gib), MULTIPART_PART_SIZE);
+        let size = 100 * gib + 1;
+        assert!(part_size(size) > MULTIPART_PART_SIZE);
+        assert!(size.div_ceil(part_size(size)) <= MAX_MULTIPART_PARTS);
+    }
+}
@@ -0,0 +1,161 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_s3::{config::ProvideCredentials, presigning::PresigningConfig, primitives::DateTime};
+
+use crate::{
+    error::Error,
+    models::{client_pool::s3_config, workspace::ResourceDescriptor},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PresignMethod {
+    #[default]
+    Get,
+    Put,
+}
+
+impl PresignMethod {
+    pub const ALL: &'static [Self] = &[Self::Get, Self::Put];
+}
+
+impl std::fmt::Display for PresignMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Get => write!(f, "GET (download)"),
+            Self::Put => write!(f, "PUT (upload)"),
+        }
+    }
+}
+
+/// SigV4 presigned URLs are valid for at most seven days.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PresignExpiry {
+    Minutes15,
+    #[default]
+    Hour1,
+    Hours12,
+    Day1,
+    Days7,
+}
+
+impl PresignExpiry {
+    pub const ALL: &'static [Self] = &[
+        Self::Minutes15,
+        Self::Hour1,
+        Self::Hours12,
+        Self::Day1,
+        Self::Days7,
+    ];
+
+    pub fn duration(self) -> Duration {
+        const HOUR: u64 = 60 * 60;
+        match self {
+            Self::Minutes15 => Duration::from_secs(15 * 60),
+            Self::Hour1 => Duration::from_secs(HOUR),
+            Self::Hours12 => Duration::from_secs(12 * HOUR),
+            Self::Day1 => Duration::from_secs(24 * HOUR),
+            Self::Days7 => Duration::from_secs(7 * 24 * HOUR),
+        }
+    }
+}
+
+impl std::fmt::Display for PresignExpiry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Minutes15 => write!(f, "15 minutes"),
+            Self::Hour1 => write!(f, "1 hour"),
+            Self::Hours12 => write!(f, "12 hours"),
+            Self::Day1 => write!(f, "1 day"),
+            Self::Days7 => write!(f, "7 days"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresignRequest {
+    pub key: String,
+    pub method: PresignMethod,
+    pub expiry: PresignExpiry,
+    /// Only used for PUT. The uploader has to send the same header.
+    pub content_type: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PresignedUrl {
+    pub key: String,
+    pub method: PresignMethod,
+    pub url: String,
+    /// Headers the caller must send along with the request.
+    pub headers: Vec<(String, String)>,
+    pub expires_at: String,
+    /// Profile and access key ID of the credentials used for signing.
+    pub signed_by: String,
+    /// Set when the signing credentials are temporary and expire before the
+    /// URL does. The URL stops working at that point.
+    pub credentials_expire_at: Option<String>,
+}
+
+fn format_time(time: SystemTime) -> String {
+    DateTime::from(time).to_string()
+}
+
+pub async fn presign_object_url(
+    bucket: ResourceDescriptor,
+    request: PresignRequest,
+) -> Result<PresignedUrl, Error> {
+    let (cfg, builder) = s3_config(&bucket.profile, &bucket.region).await;
+    let Some(credentials_provider) = cfg.credentials_provider() else {
+        return Err(Error::UnableToLoadAwsConfig);
+    };
+    let credentials = credentials_provider
+        .provide_credentials()
+        .await
+        .map_err(|e| Error::AwsSdk(format!("{:?}", e)))?;
+
+    // Sign with exactly these credentials. Otherwise the client would resolve
+    // its own, and for assumed roles the reported access key could differ
+    // from the one in the URL.
+    let client =
+        aws_sdk_s3::Client::from_conf(builder.credentials_provider(credentials.clone()).build());
+
+    let expires_in = request.expiry.duration();
+    let presigning_config =
+        PresigningConfig::expires_in(expires_in).map_err(|e| Error::Unsupported(e.to_string()))?;
+    let expires_at = SystemTime::now() + expires_in;
+
+    let presigned = match request.method {
+        PresignMethod::Get => {
+            client
+                .get_object()
+                .bucket(bucket.id.clone())
+                .key(request.key.clone())
+                .presigned(presigning_config)
+                .await?
+        }
+        PresignMethod::Put => {
+            client
+                .put_object()
+                .bucket(bucket.id.clone())
+                .key(request.key.clone())
+                .set_content_type(request.content_type.clone())
+                .presigned(presigning_config)
+                .await?
+        }
+    };
+
+    Ok(PresignedUrl {
+        key: request.key,
+        method: request.method,
+        url: presigned.uri().to_string(),
+        headers: presigned
+            .headers()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        expires_at: format_time(expires_at),
+        signed_by: format!("{} ({})", bucket.profile, credentials.access_key_id()),
+        credentials_expire_at: credentials
+            .expiry()
+            .filter(|expiry| *expiry < expires_at)
+            .map(format_time),
+    })
+}
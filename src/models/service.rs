@@ -1,17 +1,21 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
+/// The name of an AWS service, as stored in workspace files. Each service is
+/// backed by a resource provider, which defines a constant for its name.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
-pub enum Service {
-    Lambda,
-    S3,
+#[serde(transparent)]
+pub struct Service(Cow<'static, str>);
+
+impl Service {
+    pub const fn new_static(name: &'static str) -> Self {
+        Self(Cow::Borrowed(name))
+    }
 }
 
 impl std::fmt::Display for Service {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let name = match self {
-            Self::Lambda => "Lambda",
-            Self::S3 => "S3",
-        };
-        write!(f, "{}", name)
+        write!(f, "{}", self.0)
     }
 }
@@ -0,0 +1,138 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::{
+    error::{Error, IoError},
+    models::{service::Service, workspace::store::write_atomically},
+    view::pane_type::PaneType,
+};
+
+/// Where the app was left in a workspace: its layout and what the Explore
+/// tab showed. Kept beside the workspace rather than in it, as it changes
+/// all the time and is personal while workspaces may be shared. The
+/// selection of the Projects tab is part of the workspace's `appearance`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Session {
+    #[serde(default)]
+    pub active_pane: PaneType,
+
+    #[serde(default)]
+    pub side_drawer_open: bool,
+
+    /// Ratios of the Explore tab's splits, outermost first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explore_split_ratios: Vec<f32>,
+
+    #[serde(default)]
+    pub explore: ExploreSession,
+
+    /// Column widths of the Projects tab's resources table, by service.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_widths: BTreeMap<Service, Vec<f32>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExploreSession {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<Service>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_regions: bool,
+}
+
+/// The platform's data directory of the app, e.g.
+/// `~/.local/share/awsome/sessions` on Linux.
+fn sessions_dir() -> Result<PathBuf, Error> {
+    let Some(project_dirs) = directories::ProjectDirs::from("", "", "awsome") else {
+        return Err(Error::Io(IoError {
+            path: None,
+            kind: io::ErrorKind::NotFound,
+            message: "Unable to determine the data directory".to_string(),
+        }));
+    };
+    Ok(project_dirs.data_dir().join("sessions"))
+}
+
+/// Sessions are named after the SHA-1 of their workspace's path.
+fn session_path(dir: &Path, workspace: &Path) -> PathBuf {
+    let digest = Sha1::digest(workspace.to_string_lossy().as_bytes());
+    let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join(format!("{}.yaml", name))
+}
+
+impl Session {
+    /// The session of the workspace at `workspace`. A missing or unreadable
+    /// session is treated as a fresh one.
+    pub fn load(workspace: &Path) -> Self {
+        let loaded = sessions_dir().and_then(|dir| Self::load_from(&session_path(&dir, workspace)));
+        loaded.unwrap_or_else(|e| {
+            tracing::warn!(error = ?e, "Failed to load the session");
+            Self::default()
+        })
+    }
+
+    fn load_from(path: &Path) -> Result<Self, Error> {
+        let f = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+        serde_yaml::from_reader(f).map_err(|e| Error::yaml(path, e))
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<(), Error> {
+        self.save_to(&session_path(&sessions_dir()?, workspace))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), Error> {
+        let yaml = serde_yaml::to_string(self).map_err(|e| Error::yaml(path, e))?;
+        write_atomically(path, yaml.as_bytes(), 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::s3;
+
+    #[test]
+    fn sessions_are_kept_per_workspace() {
+        let dir = std::env::temp_dir().join(format!("awsome-sessions-{}", ulid::Ulid::new()));
+        let a = session_path(&dir, Path::new("/workspaces/a.yaml"));
+        let b = session_path(&dir, Path::new("/workspaces/b.yaml"));
+        assert_ne!(a, b);
+        assert_eq!(a, session_path(&dir, Path::new("/workspaces/a.yaml")));
+
+        let session = Session {
+            active_pane: PaneType::Projects,
+            side_drawer_open: true,
+            explore_split_ratios: vec![0.25, 0.5],
+            explore: ExploreSession {
+                profile: Some("dev".to_string()),
+                region: Some("eu-west-1".to_string()),
+                service: Some(s3::SERVICE.clone()),
+                all_regions: true,
+            },
+            column_widths: BTreeMap::from([(s3::SERVICE.clone(), vec![100.0, 200.0])]),
+        };
+        session.save_to(&a).unwrap();
+
+        assert_eq!(Session::load_from(&a).unwrap(), session);
+        assert_eq!(Session::load_from(&b).unwrap(), Session::default());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
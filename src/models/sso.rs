@@ -0,0 +1,684 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use aws_sdk_ssooidc::{
+    config::{BehaviorVersion, Region},
+    operation::create_token::CreateTokenError,
+};
+use aws_smithy_types::{date_time::Format as DateTimeFormat, DateTime};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::{
+    error::Error,
+    models::{endpoint::endpoint_url, profile::SharedConfig},
+};
+
+/// The key of SSO OIDC in `[services ...]` sections and
+/// `AWS_ENDPOINT_URL_SSO_OIDC`.
+const OIDC_SERVICE: &str = "sso_oidc";
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const CLIENT_NAME: &str = "awsome";
+
+/// Tokens that expire within this margin are renewed right away rather than
+/// failing halfway through the next request.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Used when the OIDC service does not say how often to poll.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// Where and how to log in for an SSO profile.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SsoLogin {
+    pub profile: String,
+    /// `None` for legacy profiles that set `sso_start_url` themselves instead
+    /// of referring to an `sso-session` section.
+    pub session_name: Option<String>,
+    pub start_url: String,
+    pub region: String,
+    pub scopes: Vec<String>,
+    /// Overrides the OIDC endpoint, e.g. to talk to a local stand-in.
+    pub endpoint_url: Option<String>,
+}
+
+impl SsoLogin {
+    /// `None` if the profile does not exist, is not an SSO profile, or lacks
+    /// the start URL or region.
+    pub fn for_profile(config: &SharedConfig, profile: &str) -> Option<Self> {
+        let p = config.profile(profile).filter(|p| p.is_sso())?;
+        let endpoint_url = endpoint_url(config, profile, OIDC_SERVICE);
+        if let Some(session_name) = &p.sso_session {
+            let session = config.sso_session(session_name)?;
+            return Some(Self {
+                profile: profile.to_string(),
+                session_name: Some(session_name.clone()),
+                start_url: session.sso_start_url.clone()?,
+                region: session.sso_region.clone()?,
+                scopes: session.sso_registration_scopes.clone(),
+                endpoint_url,
+            });
+        }
+        Some(Self {
+            profile: profile.to_string(),
+            session_name: None,
+            start_url: p.sso_start_url.clone()?,
+            region: p.sso_region.clone()?,
+            scopes: vec![],
+            endpoint_url,
+        })
+    }
+
+    /// The SDKs name cache files after the SHA-1 of the session name, or of
+    /// the start URL for legacy profiles.
+    pub fn cache_file(&self, cache_dir: &Path) -> PathBuf {
+        let key = self.session_name.as_ref().unwrap_or(&self.start_url);
+        let digest = Sha1::digest(key.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        cache_dir.join(format!("{}.json", name))
+    }
+
+    fn oidc_client(&self) -> aws_sdk_ssooidc::Client {
+        let mut config = aws_sdk_ssooidc::Config::builder()
+            .behavior_version(BehaviorVersion::v2024_03_28())
+            .region(Region::new(self.region.clone()));
+        config.set_endpoint_url(self.endpoint_url.clone());
+        aws_sdk_ssooidc::Client::from_conf(config.build())
+    }
+}
+
+/// The token cache shared with the AWS CLI and SDKs.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedToken {
+    pub start_url: String,
+    pub region: String,
+    pub access_token: String,
+    pub expires_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_expires_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    fn read(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|e| Error::SsoLogin(e.to_string()))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents)?;
+        // The cache holds bearer tokens, so only the owner may read it.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// The registration can be reused for another device authorization as
+    /// long as it has not expired.
+    fn registration(&self, now: SystemTime) -> Option<ClientRegistration> {
+        let expires_at = parse_time(self.registration_expires_at.as_ref()?)?;
+        (expires_at > now + EXPIRY_MARGIN).then(|| ClientRegistration {
+            client_id: self.client_id.clone().unwrap_or_default(),
+            client_secret: self.client_secret.clone().unwrap_or_default(),
+            expires_at,
+        })
+    }
+}
+
+/// `~/.aws/sso/cache`, which is not affected by `AWS_CONFIG_FILE`.
+pub fn sso_cache_dir() -> Result<PathBuf, Error> {
+    let Some(user_dirs) = directories::UserDirs::new() else {
+        return Err(Error::UnableToLoadAwsConfig);
+    };
+    Ok(user_dirs.home_dir().join(".aws").join("sso").join("cache"))
+}
+
+/// True if there is no usable cached token for the login.
+pub fn needs_login(login: &SsoLogin, cache_dir: &Path, now: SystemTime) -> bool {
+    let Some(token) = CachedToken::read(&login.cache_file(cache_dir)) else {
+        return true;
+    };
+    let expires_at = parse_time(&token.expires_at);
+    token.start_url != login.start_url || expires_at.is_none_or(|t| t <= now + EXPIRY_MARGIN)
+}
+
+#[derive(Clone, Debug)]
+struct ClientRegistration {
+    client_id: String,
+    client_secret: String,
+    expires_at: SystemTime,
+}
+
+/// The requests waiting for the SSO login in progress. Only one login runs at
+/// a time, and everything that needed it is sent again once it has finished.
+#[derive(Debug)]
+pub struct SsoLoginQueue<T> {
+    /// `None` while no login is in progress.
+    waiting: Option<Vec<T>>,
+}
+
+impl<T> Default for SsoLoginQueue<T> {
+    fn default() -> Self {
+        Self { waiting: None }
+    }
+}
+
+impl<T> SsoLoginQueue<T> {
+    /// True if a login has to be started for `retry`, false if it waits for
+    /// the one in progress.
+    pub fn request(&mut self, retry: T) -> bool {
+        match &mut self.waiting {
+            Some(waiting) => {
+                waiting.push(retry);
+                false
+            }
+            None => {
+                self.waiting = Some(vec![retry]);
+                true
+            }
+        }
+    }
+
+    /// Ends the login in progress and returns what was waiting for it, in
+    /// the order it was requested.
+    pub fn finish(&mut self) -> Vec<T> {
+        self.waiting.take().unwrap_or_default()
+    }
+}
+
+/// A started device authorization. The user has to open the verification URI
+/// and enter the user code while the app polls for the token.
+#[derive(Clone, Debug)]
+pub struct SsoDeviceAuthorization {
+    pub login: SsoLogin,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// The verification URI with the user code filled in.
+    pub verification_uri_complete: Option<String>,
+    registration: ClientRegistration,
+    device_code: String,
+    interval: Duration,
+    expires_at: SystemTime,
+}
+
+pub async fn start_device_authorization(
+    login: SsoLogin,
+    cache_dir: PathBuf,
+) -> Result<SsoDeviceAuthorization, Error> {
+    let client = login.oidc_client();
+    let now = SystemTime::now();
+
+    let cached_registration =
+        CachedToken::read(&login.cache_file(&cache_dir)).and_then(|t| t.registration(now));
+    let registration = match cached_registration {
+        Some(registration) => registration,
+        None => {
+            let out = client
+                .register_client()
+                .client_name(CLIENT_NAME)
+                .client_type("public")
+                .set_scopes(Some(login.scopes.clone()).filter(|s| !s.is_empty()))
+                .send()
+                .await
+                .map_err(|e| Error::SsoLogin(format!("RegisterClient failed: {:?}", e)))?;
+            ClientRegistration {
+                client_id: out.client_id().unwrap_or_default().to_string(),
+                client_secret: out.client_secret().unwrap_or_default().to_string(),
+                expires_at: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(out.client_secret_expires_at().max(0) as u64),
+            }
+        }
+    };
+
+    let out = client
+        .start_device_authorization()
+        .client_id(&registration.client_id)
+        .client_secret(&registration.client_secret)
+        .start_url(&login.start_url)
+        .send()
+        .await
+        .map_err(|e| Error::SsoLogin(format!("StartDeviceAuthorization failed: {:?}", e)))?;
+
+    Ok(SsoDeviceAuthorization {
+        user_code: out.user_code().unwrap_or_default().to_string(),
+        verification_uri: out.verification_uri().unwrap_or_default().to_string(),
+        verification_uri_complete: out.verification_uri_complete().map(|u| u.to_string()),
+        device_code: out.device_code().unwrap_or_default().to_string(),
+        interval: match out.interval() {
+            i if i > 0 => Duration::from_secs(i as u64),
+            _ => DEFAULT_POLL_INTERVAL,
+        },
+        expires_at: now + Duration::from_secs(out.expires_in().max(0) as u64),
+        registration,
+        login,
+    })
+}
+
+/// Polls until the user has approved the authorization, then writes the token
+/// to the cache, where the SDK's SSO credentials provider picks it up.
+pub async fn complete_device_authorization(
+    authorization: SsoDeviceAuthorization,
+    cache_dir: PathBuf,
+) -> Result<(), Error> {
+    let login = &authorization.login;
+    let client = login.oidc_client();
+    let mut interval = authorization.interval;
+
+    let out = loop {
+        tokio::time::sleep(interval).await;
+        if SystemTime::now() > authorization.expires_at {
+            return Err(Error::SsoLogin(format!(
+                "The login for {} was not approved in time",
+                login.profile
+            )));
+        }
+
+        let res = client
+            .create_token()
+            .grant_type(DEVICE_CODE_GRANT_TYPE)
+            .device_code(&authorization.device_code)
+            .client_id(&authorization.registration.client_id)
+            .client_secret(&authorization.registration.client_secret)
+            .send()
+            .await;
+        match res {
+            Ok(out) => break out,
+            Err(e) => match e.as_service_error() {
+                Some(CreateTokenError::AuthorizationPendingException(_)) => {}
+                Some(CreateTokenError::SlowDownException(_)) => interval += SLOW_DOWN_INCREMENT,
+                _ => return Err(Error::SsoLogin(format!("CreateToken failed: {:?}", e))),
+            },
+        }
+    };
+
+    let now = SystemTime::now();
+    let token = CachedToken {
+        start_url: login.start_url.clone(),
+        region: login.region.clone(),
+        access_token: out.access_token().unwrap_or_default().to_string(),
+        expires_at: format_time(now + Duration::from_secs(out.expires_in().max(0) as u64)),
+        client_id: Some(authorization.registration.client_id.clone()),
+        client_secret: Some(authorization.registration.client_secret.clone()),
+        registration_expires_at: Some(format_time(authorization.registration.expires_at)),
+        refresh_token: out.refresh_token().map(|t| t.to_string()),
+    };
+    token.write(&login.cache_file(&cache_dir))
+}
+
+fn format_time(time: SystemTime) -> String {
+    DateTime::from(time)
+        .fmt(DateTimeFormat::DateTime)
+        .unwrap_or_default()
+}
+
+/// Older versions of the AWS CLI wrote `UTC` instead of `Z`.
+fn parse_time(time: &str) -> Option<SystemTime> {
+    let time = match time.strip_suffix("UTC") {
+        Some(time) => format!("{}Z", time),
+        None => time.to_string(),
+    };
+    let time = DateTime::from_str(&time, DateTimeFormat::DateTime).ok()?;
+    SystemTime::try_from(time).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A stand-in for the OIDC endpoints. `/token` answers with the queued
+    /// error codes first, then issues a token.
+    struct OidcStandIn {
+        endpoint_url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl OidcStandIn {
+        fn start(token_errors: Vec<&'static str>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let endpoint_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(vec![]));
+            let token_errors = Arc::new(Mutex::new(token_errors));
+
+            let log = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else {
+                        continue;
+                    };
+                    let path = read_request(&mut stream);
+                    log.lock().unwrap().push(path.clone());
+
+                    let (status, error_type, body) = match path.as_str() {
+                        "/client/register" => (
+                            200,
+                            None,
+                            r#"{"clientId":"client-1","clientSecret":"secret-1","clientIdIssuedAt":1700000000,"clientSecretExpiresAt":4102444800}"#,
+                        ),
+                        "/device_authorization" => (
+                            200,
+                            None,
+                            r#"{"deviceCode":"device-1","userCode":"ABCD-EFGH","verificationUri":"https://device.sso.example.com/","verificationUriComplete":"https://device.sso.example.com/?user_code=ABCD-EFGH","expiresIn":600,"interval":1}"#,
+                        ),
+                        "/token" => match token_errors.lock().unwrap().pop() {
+                            Some(error_type) => (400, Some(error_type), r#"{"error":"error"}"#),
+                            None => (
+                                200,
+                                None,
+                                r#"{"accessToken":"token-1","tokenType":"Bearer","expiresIn":28800,"refreshToken":"refresh-1"}"#,
+                            ),
+                        },
+                        _ => (404, None, "{}"),
+                    };
+                    let error_header = error_type
+                        .map(|t| format!("x-amzn-errortype: {}\r\n", t))
+                        .unwrap_or_default();
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {} X\r\ncontent-type: application/json\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+                        status,
+                        error_header,
+                        body.len(),
+                        body
+                    );
+                }
+            });
+
+            Self {
+                endpoint_url,
+                requests,
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// Reads a whole request and returns its path.
+    fn read_request(stream: &mut std::net::TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn temp_dir() -> (TempDir, PathBuf) {
+        let dir = TempDir::with_prefix("awsome-sso-").unwrap();
+        let path = dir.path().to_path_buf();
+        (dir, path)
+    }
+
+    fn login(endpoint_url: Option<String>) -> SsoLogin {
+        SsoLogin {
+            profile: "dev".to_string(),
+            session_name: Some("my-sso".to_string()),
+            start_url: "https://my-sso-portal.awsapps.com/start".to_string(),
+            region: "us-east-1".to_string(),
+            scopes: vec!["sso:account:access".to_string()],
+            endpoint_url,
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn write_token(login: &SsoLogin, dir: &Path, expires_at: SystemTime) {
+        CachedToken {
+            start_url: login.start_url.clone(),
+            region: login.region.clone(),
+            access_token: "token-0".to_string(),
+            expires_at: format_time(expires_at),
+            ..Default::default()
+        }
+        .write(&login.cache_file(dir))
+        .unwrap();
+    }
+
+    #[test]
+    fn resolves_sso_session_and_legacy_profiles() {
+        let config = SharedConfig::parse(
+            &std::fs::read_to_string(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/aws/sso_config"
+            ))
+            .unwrap(),
+            "",
+        );
+
+        let dev = SsoLogin::for_profile(&config, "sso-dev").unwrap();
+        assert_eq!(dev.session_name.as_deref(), Some("my-sso"));
+        assert_eq!(dev.start_url, "https://my-sso-portal.awsapps.com/start");
+        assert_eq!(dev.region, "us-east-1");
+
+        let legacy = SsoLogin::for_profile(&config, "sso-legacy").unwrap();
+        assert_eq!(legacy.session_name, None);
+        assert_eq!(legacy.start_url, "https://legacy.awsapps.com/start");
+        assert_eq!(legacy.region, "eu-west-1");
+
+        assert_eq!(SsoLogin::for_profile(&config, "missing"), None);
+    }
+
+    #[test]
+    fn uses_the_configured_oidc_endpoint() {
+        let config = SharedConfig::parse(
+            "[profile local]
+sso_start_url = https://local.awsapps.com/start
+sso_region = us-east-1
+services = local-services
+
+[services local-services]
+sso_oidc =
+  endpoint_url = http://localhost:4566
+
+[profile plain]
+region = us-east-1
+",
+            "",
+        );
+
+        let local = SsoLogin::for_profile(&config, "local").unwrap();
+        assert_eq!(local.endpoint_url.as_deref(), Some("http://localhost:4566"));
+        // Not an SSO profile.
+        assert_eq!(SsoLogin::for_profile(&config, "plain"), None);
+    }
+
+    #[test]
+    fn cache_file_is_named_after_the_sha1_of_the_key() {
+        let dir = Path::new("/cache");
+        let mut legacy = login(None);
+        legacy.session_name = None;
+
+        assert_eq!(
+            login(None).cache_file(dir),
+            dir.join("0ad374308c5a4e22f723adf10145eafad7c4031c.json")
+        );
+        assert_eq!(
+            legacy.cache_file(dir),
+            dir.join("c7aaaf71fcc8777ae2475525ed049d39fe16c484.json")
+        );
+    }
+
+    #[test]
+    fn detects_missing_and_expired_tokens() {
+        let (_dir, dir) = temp_dir();
+        let login = login(None);
+        let now = SystemTime::now();
+
+        assert!(needs_login(&login, &dir, now));
+
+        write_token(&login, &dir, now - Duration::from_secs(1));
+        assert!(needs_login(&login, &dir, now));
+
+        write_token(&login, &dir, now + Duration::from_secs(30));
+        assert!(needs_login(&login, &dir, now));
+
+        write_token(&login, &dir, now + Duration::from_secs(3600));
+        assert!(!needs_login(&login, &dir, now));
+    }
+
+    #[test]
+    fn reads_tokens_written_by_older_clis() {
+        assert_eq!(
+            parse_time("2024-01-02T03:04:05UTC"),
+            parse_time("2024-01-02T03:04:05Z")
+        );
+        assert!(parse_time("2024-01-02T03:04:05Z").is_some());
+    }
+
+    #[test]
+    fn device_flow_writes_the_token_to_the_cache() {
+        let stand_in = OidcStandIn::start(vec![
+            "AuthorizationPendingException",
+            "AuthorizationPendingException",
+        ]);
+        let (_dir, dir) = temp_dir();
+        let login = login(Some(stand_in.endpoint_url.clone()));
+
+        let authorization =
+            block_on(start_device_authorization(login.clone(), dir.clone())).unwrap();
+        assert_eq!(authorization.user_code, "ABCD-EFGH");
+        assert_eq!(
+            authorization.verification_uri,
+            "https://device.sso.example.com/"
+        );
+
+        block_on(complete_device_authorization(authorization, dir.clone())).unwrap();
+
+        let token = CachedToken::read(&login.cache_file(&dir)).unwrap();
+        assert_eq!(token.access_token, "token-1");
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh-1"));
+        assert_eq!(token.client_id.as_deref(), Some("client-1"));
+        assert_eq!(token.start_url, login.start_url);
+        assert!(!needs_login(&login, &dir, SystemTime::now()));
+        assert_eq!(
+            stand_in.requests(),
+            [
+                "/client/register",
+                "/device_authorization",
+                "/token",
+                "/token",
+                "/token"
+            ]
+        );
+    }
+
+    #[test]
+    fn concurrent_requests_share_one_login() {
+        let stand_in = OidcStandIn::start(vec![]);
+        let (_dir, dir) = temp_dir();
+        let login = login(Some(stand_in.endpoint_url.clone()));
+        let mut queue = SsoLoginQueue::default();
+
+        assert!(queue.request("list buckets"));
+        assert!(!queue.request("list functions"));
+        let authorization =
+            block_on(start_device_authorization(login.clone(), dir.clone())).unwrap();
+        assert!(!queue.request("list regions"));
+        block_on(complete_device_authorization(authorization, dir.clone())).unwrap();
+
+        assert_eq!(
+            queue.finish(),
+            ["list buckets", "list functions", "list regions"]
+        );
+        assert!(!needs_login(&login, &dir, SystemTime::now()));
+        assert_eq!(
+            stand_in.requests(),
+            ["/client/register", "/device_authorization", "/token"]
+        );
+        // The next login starts afresh.
+        assert!(queue.request("list buckets"));
+        assert_eq!(queue.finish(), ["list buckets"]);
+        assert!(queue.finish().is_empty());
+    }
+
+    #[test]
+    fn reuses_a_cached_registration() {
+        let stand_in = OidcStandIn::start(vec![]);
+        let (_dir, dir) = temp_dir();
+        let login = login(Some(stand_in.endpoint_url.clone()));
+        CachedToken {
+            start_url: login.start_url.clone(),
+            region: login.region.clone(),
+            expires_at: format_time(SystemTime::now()),
+            client_id: Some("client-0".to_string()),
+            client_secret: Some("secret-0".to_string()),
+            registration_expires_at: Some(format_time(
+                SystemTime::now() + Duration::from_secs(86400),
+            )),
+            ..Default::default()
+        }
+        .write(&login.cache_file(&dir))
+        .unwrap();
+
+        let authorization = block_on(start_device_authorization(login, dir)).unwrap();
+
+        assert_eq!(authorization.registration.client_id, "client-0");
+        assert_eq!(stand_in.requests(), ["/device_authorization"]);
+    }
+
+    #[test]
+    fn denied_authorization_fails() {
+        let stand_in = OidcStandIn::start(vec!["AccessDeniedException"]);
+        let (_dir, dir) = temp_dir();
+        let login = login(Some(stand_in.endpoint_url.clone()));
+
+        let authorization =
+            block_on(start_device_authorization(login.clone(), dir.clone())).unwrap();
+        let res = block_on(complete_device_authorization(authorization, dir.clone()));
+
+        assert!(matches!(res, Err(Error::SsoLogin(_))));
+        assert!(needs_login(&login, &dir, SystemTime::now()));
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use iced::futures::{channel::mpsc, SinkExt, Stream};
+
+use crate::error::Error;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TransferId(String);
+
+impl TransferId {
+    pub fn new() -> Self {
+        Self(ulid::Ulid::new().to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransferKind {
+    Download,
+    Upload,
+}
+
+impl std::fmt::Display for TransferKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Download => write!(f, "Download"),
+            Self::Upload => write!(f, "Upload"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TransferStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed(Error),
+}
+
+#[derive(Clone, Debug)]
+pub enum TransferEvent {
+    Progressed {
+        transferred: u64,
+        total: Option<u64>,
+    },
+    Completed,
+    Cancelled,
+    Failed(Error),
+}
+
+/// What a transfer function returns when it stopped without an error.
+pub enum TransferOutcome {
+    Completed,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Handed to a running transfer so that it can report its progress and
+/// notice that the user asked it to stop.
+pub struct ProgressReporter {
+    sender: mpsc::Sender<TransferEvent>,
+    cancel: CancelToken,
+}
+
+impl ProgressReporter {
+    pub fn report(&mut self, transferred: u64, total: Option<u64>) {
+        // Progress events are only informational, so dropping one when the
+        // channel is full is fine.
+        let _ = self
+            .sender
+            .try_send(TransferEvent::Progressed { transferred, total });
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    pub id: TransferId,
+    pub kind: TransferKind,
+    pub label: String,
+    pub transferred: u64,
+    pub total: Option<u64>,
+    pub status: TransferStatus,
+    cancel: CancelToken,
+}
+
+impl Transfer {
+    pub fn new(kind: TransferKind, label: String) -> Self {
+        Self {
+            id: TransferId::new(),
+            kind,
+            label,
+            transferred: 0,
+            total: None,
+            status: TransferStatus::Running,
+            cancel: CancelToken::default(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.status, TransferStatus::Running)
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn apply(&mut self, event: TransferEvent) {
+        match event {
+            TransferEvent::Progressed { transferred, total } => {
+                self.transferred = transferred;
+                self.total = total;
+            }
+            TransferEvent::Completed => self.status = TransferStatus::Completed,
+            TransferEvent::Cancelled => self.status = TransferStatus::Cancelled,
+            TransferEvent::Failed(e) => self.status = TransferStatus::Failed(e),
+        }
+    }
+
+    /// Runs `f` in the background and turns its progress reports and its
+    /// final result into a stream of events for this transfer.
+    pub fn run<F, Fut>(&self, f: F) -> impl Stream<Item = TransferEvent>
+    where
+        F: FnOnce(ProgressReporter) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<TransferOutcome, Error>> + Send + 'static,
+    {
+        let cancel = self.cancel.clone();
+        iced::stream::channel(100, move |mut output| async move {
+            let reporter = ProgressReporter {
+                sender: output.clone(),
+                cancel,
+            };
+            let event = match f(reporter).await {
+                Ok(TransferOutcome::Completed) => TransferEvent::Completed,
+                Ok(TransferOutcome::Cancelled) => TransferEvent::Cancelled,
+                Err(e) => TransferEvent::Failed(e),
+            };
+            let _ = output.send(event).await;
+        })
+    }
+}
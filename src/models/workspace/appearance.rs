@@ -1,15 +1,29 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
 
-use super::{resource_descriptor::ResourceDescriptor, Project};
+use super::resource_descriptor::ResourceDescriptor;
+use crate::models::service::Service;
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Appearance {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub selected_project: Option<Project>,
+    /// The ID of the project selected in the Projects tab.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_project_id: Option<String>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub selected_service: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_service: Option<Service>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub selected_resource: Option<ResourceDescriptor>,
+
+    /// The environment shown of each project that has some, by project ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub selected_environments: BTreeMap<String, String>,
+
+    /// Fields of newer versions of the app, kept so that saving does not
+    /// drop them.
+    #[serde(flatten)]
+    extra: Mapping,
 }
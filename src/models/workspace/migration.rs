@@ -0,0 +1,159 @@
+use serde::de::Error as _;
+use serde_yaml::{Mapping, Value};
+
+/// Upgrades a document from the version of its index to the next one.
+/// Migrations work on the raw YAML, so that they keep working however the
+/// model changes later on.
+type Migration = fn(&mut Mapping) -> Result<(), serde_yaml::Error>;
+
+const MIGRATIONS: [Migration; 1] = [v0_to_v1];
+
+/// The version workspaces are saved with.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Upgrades a workspace document step by step to `CURRENT_VERSION`.
+/// Documents saved by a newer version of the app are left as they are.
+/// The file itself is only replaced on the next save, which backs up the
+/// old version.
+pub fn migrate(document: Value) -> Result<Value, serde_yaml::Error> {
+    let mut document = match document {
+        Value::Mapping(m) => m,
+        // An empty file.
+        Value::Null => Mapping::new(),
+        _ => return Err(serde_yaml::Error::custom("a workspace must be a mapping")),
+    };
+    let version = match document.get("version") {
+        // Files from before versioning.
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| serde_yaml::Error::custom("version must be a number"))?,
+    };
+    if version > CURRENT_VERSION as u64 {
+        tracing::warn!(version, "Workspace was saved by a newer version of the app");
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut document)?;
+        let to = from as u64 + 1;
+        document.insert("version".into(), to.into());
+        tracing::info!(from, to, "Migrated workspace");
+    }
+    Ok(Value::Mapping(document))
+}
+
+/// Gives every project an ID and refers to the selected project by it
+/// rather than by a copy. Drops the absolute `path`, which is no longer
+/// saved.
+fn v0_to_v1(document: &mut Mapping) -> Result<(), serde_yaml::Error> {
+    document.remove("path");
+
+    if let Some(Value::Sequence(projects)) = document.get_mut("projects") {
+        for project in projects.iter_mut() {
+            let Value::Mapping(project) = project else {
+                return Err(serde_yaml::Error::custom("a project must be a mapping"));
+            };
+            if !project.contains_key("id") {
+                project.insert("id".into(), ulid::Ulid::new().to_string().into());
+            }
+        }
+    }
+
+    let Some(Value::Mapping(appearance)) = document.get("appearance") else {
+        return Ok(());
+    };
+    let selected_project_id = match appearance.get("selected_project") {
+        Some(Value::Mapping(selected)) => match selected.get("id") {
+            Some(id) => Some(id.clone()),
+            // A copy taken before projects had IDs; only its name is left
+            // to find it by.
+            None => find_project_id_by_name(document, selected.get("name")),
+        },
+        _ => None,
+    };
+    if let Some(Value::Mapping(appearance)) = document.get_mut("appearance") {
+        appearance.remove("selected_project");
+        if let Some(id) = selected_project_id {
+            appearance.insert("selected_project_id".into(), id);
+        }
+    }
+    Ok(())
+}
+
+fn find_project_id_by_name(document: &Mapping, name: Option<&Value>) -> Option<Value> {
+    let name = name?;
+    let Some(Value::Sequence(projects)) = document.get("projects") else {
+        return None;
+    };
+    projects
+        .iter()
+        .find(|p| p.get("name") == Some(name))
+        .and_then(|p| p.get("id"))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrate_str(yaml: &str) -> Value {
+        migrate(serde_yaml::from_str(yaml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn migrates_unversioned_workspaces() {
+        let migrated = migrate_str(
+            "path: /home/someone/.config/awsome/workspaces/default.yaml
+projects:
+- name: a
+  resources: []
+- name: b
+  resources: []
+appearance:
+  selected_project:
+    name: b
+    resources: []
+  selected_service: Lambda
+",
+        );
+
+        assert_eq!(migrated["version"], Value::from(CURRENT_VERSION));
+        assert_eq!(migrated.get("path"), None);
+        let ids: Vec<_> = migrated["projects"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|p| p["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+        let appearance = &migrated["appearance"];
+        assert_eq!(appearance.get("selected_project"), None);
+        assert_eq!(
+            appearance["selected_project_id"],
+            Value::from(ids[1].clone())
+        );
+        assert_eq!(appearance["selected_service"], Value::from("Lambda"));
+    }
+
+    #[test]
+    fn keeps_current_and_newer_workspaces() {
+        let yaml = "version: 1\nprojects:\n- id: a\n  name: a\n";
+        assert_eq!(
+            migrate_str(yaml),
+            serde_yaml::from_str::<Value>(yaml).unwrap()
+        );
+
+        let yaml = "version: 99\nlayout: grid\n";
+        assert_eq!(
+            migrate_str(yaml),
+            serde_yaml::from_str::<Value>(yaml).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_workspaces() {
+        assert!(migrate(serde_yaml::from_str("- a").unwrap()).is_err());
+        assert!(migrate(serde_yaml::from_str("version: one").unwrap()).is_err());
+    }
+}
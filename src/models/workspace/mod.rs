@@ -1,50 +1,223 @@
 mod appearance;
+mod migration;
 pub mod project;
 pub mod resource_descriptor;
+pub mod settings;
+pub mod store;
 
 pub use project::Project;
 pub use resource_descriptor::ResourceDescriptor;
 
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
 
-use crate::{error::Error, models::workspace::appearance::Appearance};
+use crate::{
+    error::{Error, IoError},
+    models::{
+        service::Service,
+        workspace::{
+            appearance::Appearance,
+            migration::{migrate, CURRENT_VERSION},
+            settings::Settings,
+            store::{
+                default_workspace_path, modified_time, workspace_path, workspaces_dir,
+                write_atomically, RecentWorkspaces, WORKSPACE_BACKUPS,
+            },
+        },
+    },
+};
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Workspace {
+    /// The schema version, see `migration`.
+    #[serde(default)]
+    version: u32,
+    /// Where the workspace was loaded from. Not saved, as the file may be
+    /// moved or shared.
+    #[serde(skip)]
     path: Option<PathBuf>,
+    /// When the app last read or wrote the file, to tell edits by others
+    /// apart from its own.
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+    /// The modification time of the last external change that was reported.
+    #[serde(skip)]
+    reported_change: Option<SystemTime>,
     pub projects: Vec<Project>,
     pub appearance: Appearance,
+    #[serde(default)]
+    pub settings: Settings,
+    /// Fields of newer versions of the app, kept so that saving does not
+    /// drop them.
+    #[serde(flatten)]
+    extra: Mapping,
 }
 
 impl Workspace {
+    /// Loads `workspace_file`, or else the last opened workspace. Without
+    /// either, the default workspace is created in the config directory.
     pub async fn load(workspace_file: Option<PathBuf>) -> Result<Self, Error> {
-        match workspace_file {
-            Some(workspace_file) => Ok(Self::load_workspace_file(workspace_file).await?),
-            None => Ok(Self::load_empty_workspace().await?),
+        if let Some(workspace_file) = workspace_file {
+            return Self::load_workspace_file(workspace_file).await;
+        }
+        if let Some(last) = RecentWorkspaces::load().last() {
+            return Self::load_workspace_file(last.to_path_buf()).await;
+        }
+        let default_path = default_workspace_path()?;
+        match default_path.exists() {
+            true => Self::load_workspace_file(default_path).await,
+            false => Self::create_at(default_path, Workspace::default()),
         }
     }
 
     async fn load_workspace_file(workspace_file: PathBuf) -> Result<Self, Error> {
-        let f = File::open(workspace_file.clone())?;
-        let mut workspace: Workspace = serde_yaml::from_reader(f)?;
-        workspace.path = Some(workspace_file);
+        let f = File::open(&workspace_file).map_err(|e| Error::io(&workspace_file, e))?;
+        let modified = modified_time(&workspace_file);
+        let document = serde_yaml::from_reader(f)
+            .and_then(migrate)
+            .map_err(|e| Error::yaml(&workspace_file, e))?;
+        let mut workspace: Workspace =
+            serde_yaml::from_value(document).map_err(|e| Error::yaml(&workspace_file, e))?;
+        // So that the workspace is found in the recent ones whatever
+        // directory the app is started from.
+        workspace.path = Some(fs::canonicalize(&workspace_file).unwrap_or(workspace_file));
+        workspace.modified = modified;
         Ok(workspace)
     }
 
-    async fn load_empty_workspace() -> Result<Self, Error> {
-        let workspace = Workspace::default();
+    /// Creates an empty workspace named `name` in the config directory.
+    pub fn create(name: &str) -> Result<Self, Error> {
+        Self::create_at(
+            workspace_path(&workspaces_dir()?, name),
+            Workspace::default(),
+        )
+    }
+
+    /// Copies the workspace to a new one named `name` in the config
+    /// directory.
+    pub fn duplicate(&self, name: &str) -> Result<Self, Error> {
+        Self::create_at(workspace_path(&workspaces_dir()?, name), self.clone())
+    }
+
+    fn create_at(path: PathBuf, workspace: Workspace) -> Result<Self, Error> {
+        if path.exists() {
+            return Err(Error::Io(IoError {
+                path: Some(path),
+                kind: io::ErrorKind::AlreadyExists,
+                message: "A workspace with this name already exists".to_string(),
+            }));
+        }
+        let mut workspace = Workspace {
+            path: Some(path),
+            ..workspace
+        };
+        workspace.save()?;
         Ok(workspace)
     }
 
+    /// `None` if the workspace could not be loaded and is not saved.
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// True once the file has been modified by someone else since the app
+    /// last read or wrote it. Each modification is reported once.
+    pub fn take_external_change(&mut self) -> bool {
+        let Some(modified) = self.external_modification() else {
+            return false;
+        };
+        if self.reported_change == Some(modified) {
+            return false;
+        }
+        self.reported_change = Some(modified);
+        true
+    }
+
+    /// When the file was modified by someone else, if it has been since the
+    /// app last read or wrote it.
+    fn external_modification(&self) -> Option<SystemTime> {
+        let modified = modified_time(self.path.as_ref()?)?;
+        (Some(modified) != self.modified).then_some(modified)
+    }
+
+    /// Takes the file as it is on disk as read, e.g. when an external change
+    /// only touched it.
+    pub fn accept_external_change(&mut self, theirs: &Workspace) {
+        if theirs.path == self.path {
+            self.modified = theirs.modified;
+        }
+    }
+
+    /// True if both would be saved the same, e.g. when an external change
+    /// only touched the file.
+    pub fn has_same_content(&self, other: &Workspace) -> bool {
+        match (serde_yaml::to_string(self), serde_yaml::to_string(other)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Takes the projects and settings from `theirs`, keeping the projects
+    /// and resources only this workspace has. Nothing is removed, so
+    /// projects deleted by them are kept too.
+    pub fn merge(&mut self, theirs: Workspace) -> Result<(), Error> {
+        let mut projects = theirs.projects;
+        for mine in &self.projects {
+            match projects.iter_mut().find(|p| p.get_id() == mine.get_id()) {
+                Some(project) => {
+                    for resource in &mine.resources {
+                        project.add_resource(resource.clone());
+                    }
+                }
+                None => projects.push(mine.clone()),
+            }
+        }
+        self.projects = projects;
+        self.settings = theirs.settings;
+        self.overwrite()
+    }
+
     pub fn set_selected_resource(
         &mut self,
         resource: Option<ResourceDescriptor>,
     ) -> Result<(), Error> {
         self.appearance.selected_resource = resource;
-        self.save()?;
-        Ok(())
+        self.save_selection()
+    }
+
+    pub fn set_selected_project(
+        &mut self,
+        project_id: Option<String>,
+        service: Option<Service>,
+    ) -> Result<(), Error> {
+        self.appearance.selected_project_id = project_id;
+        self.appearance.selected_service = service;
+        self.save_selection()
+    }
+
+    pub fn get_selected_environment(&self, project_id: &str) -> Option<&str> {
+        self.appearance
+            .selected_environments
+            .get(project_id)
+            .map(|e| e.as_str())
+    }
+
+    pub fn set_selected_environment(
+        &mut self,
+        project_id: &str,
+        environment: &str,
+    ) -> Result<(), Error> {
+        self.appearance
+            .selected_environments
+            .insert(project_id.to_string(), environment.to_string());
+        self.save_selection()
     }
 
     pub fn add_project(&mut self, project: Project) -> Result<(), Error> {
@@ -53,10 +226,332 @@ impl Workspace {
         Ok(())
     }
 
-    pub fn save(&self) -> Result<(), Error> {
-        let path = self.path.clone().unwrap_or(PathBuf::from("workspace.yaml"));
-        let f = File::create(path)?;
-        serde_yaml::to_writer(f, self)?;
+    fn get_project_mut(&mut self, id: &str) -> Option<&mut Project> {
+        self.projects.iter_mut().find(|p| p.get_id() == id)
+    }
+
+    pub fn rename_project(&mut self, id: &str, name: &str) -> Result<(), Error> {
+        let Some(project) = self.get_project_mut(id) else {
+            return Ok(());
+        };
+        project.name = name.to_string();
+        self.save()
+    }
+
+    pub fn delete_project(&mut self, id: &str) -> Result<(), Error> {
+        self.projects.retain(|p| p.get_id() != id);
+        self.appearance.selected_environments.remove(id);
+        self.save()
+    }
+
+    /// Moves the project to `index`, or to the end if `index` is past it.
+    pub fn move_project(&mut self, id: &str, index: usize) -> Result<(), Error> {
+        let Some(from) = self.projects.iter().position(|p| p.get_id() == id) else {
+            return Ok(());
+        };
+        let project = self.projects.remove(from);
+        let index = index.min(self.projects.len());
+        self.projects.insert(index, project);
+        self.save()
+    }
+
+    pub fn add_resource_to_project(
+        &mut self,
+        id: &str,
+        resource: ResourceDescriptor,
+    ) -> Result<(), Error> {
+        let added = self
+            .get_project_mut(id)
+            .is_some_and(|p| p.add_resource(resource));
+        match added {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    pub fn remove_resource_from_project(
+        &mut self,
+        id: &str,
+        resource: &ResourceDescriptor,
+    ) -> Result<(), Error> {
+        let removed = self
+            .get_project_mut(id)
+            .is_some_and(|p| p.remove_resource(resource));
+        match removed {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    /// Refuses to overwrite changes made by someone else since the app last
+    /// read or wrote the file. They have to be reloaded, merged or dismissed
+    /// first.
+    pub fn save(&mut self) -> Result<(), Error> {
+        if let (Some(path), Some(_)) = (&self.path, self.external_modification()) {
+            return Err(Error::Io(IoError {
+                path: Some(path.clone()),
+                kind: io::ErrorKind::Other,
+                message: "The file has been changed outside the app. Reload or merge it first"
+                    .to_string(),
+            }));
+        }
+        self.overwrite()
+    }
+
+    /// Saves even over changes made by someone else.
+    pub fn overwrite(&mut self) -> Result<(), Error> {
+        let Some(path) = self.path.clone() else {
+            // Rather than overwriting a file that failed to load.
+            return Err(Error::Io(IoError {
+                path: None,
+                kind: io::ErrorKind::NotFound,
+                message: "The workspace has no file to save to".to_string(),
+            }));
+        };
+        // A newer version is kept, so that the file is not migrated again.
+        self.version = self.version.max(CURRENT_VERSION);
+        let yaml = serde_yaml::to_string(self).map_err(|e| Error::yaml(&path, e))?;
+        write_atomically(&path, yaml.as_bytes(), WORKSPACE_BACKUPS)?;
+        self.modified = modified_time(&path);
         Ok(())
     }
+
+    /// Like `save`, but the selection is only kept in memory while there is
+    /// no file or the file has been changed by someone else, so that
+    /// browsing neither reports an error each time nor overwrites them.
+    fn save_selection(&mut self) -> Result<(), Error> {
+        match self.path.is_some() && self.external_modification().is_none() {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::s3;
+
+    fn workspace(names: &[&str]) -> (Workspace, PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("awsome-workspace-{}.yaml", ulid::Ulid::new()));
+        let workspace = Workspace {
+            path: Some(path.clone()),
+            projects: names.iter().map(|n| Project::new(n)).collect(),
+            ..Default::default()
+        };
+        (workspace, path)
+    }
+
+    fn names(workspace: &Workspace) -> Vec<&str> {
+        workspace.projects.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    fn saved(path: &PathBuf) -> Workspace {
+        serde_yaml::from_reader(File::open(path).unwrap()).unwrap()
+    }
+
+    fn bucket(id: &str) -> ResourceDescriptor {
+        ResourceDescriptor {
+            profile: "dev".to_string(),
+            region: "eu-west-1".to_string(),
+            service: s3::SERVICE.clone(),
+            id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn renames_deletes_and_reorders_projects() {
+        let (mut ws, path) = workspace(&["a", "b", "c"]);
+        let id = |ws: &Workspace, i: usize| ws.projects[i].get_id().to_string();
+
+        ws.rename_project(&id(&ws, 0), "renamed").unwrap();
+        assert_eq!(names(&ws), vec!["renamed", "b", "c"]);
+
+        ws.move_project(&id(&ws, 0), 2).unwrap();
+        assert_eq!(names(&ws), vec!["b", "c", "renamed"]);
+        ws.move_project(&id(&ws, 1), 0).unwrap();
+        assert_eq!(names(&ws), vec!["c", "b", "renamed"]);
+
+        ws.delete_project(&id(&ws, 1)).unwrap();
+        assert_eq!(names(&ws), vec!["c", "renamed"]);
+        assert_eq!(names(&saved(&path)), vec!["c", "renamed"]);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn adds_and_removes_resources_once() {
+        let (mut ws, path) = workspace(&["a"]);
+        let id = ws.projects[0].get_id().to_string();
+
+        ws.add_resource_to_project(&id, bucket("logs")).unwrap();
+        ws.add_resource_to_project(&id, bucket("logs")).unwrap();
+        ws.add_resource_to_project(&id, bucket("assets")).unwrap();
+        assert_eq!(
+            saved(&path).projects[0].resources,
+            vec![bucket("logs"), bucket("assets")]
+        );
+
+        ws.remove_resource_from_project(&id, &bucket("logs"))
+            .unwrap();
+        assert_eq!(saved(&path).projects[0].resources, vec![bucket("assets")]);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn saves_without_the_path_and_notices_external_changes() {
+        let (mut ws, path) = workspace(&["a"]);
+        ws.save().unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("path:"));
+        assert!(!ws.take_external_change());
+
+        let mut theirs = saved(&path);
+        theirs.projects[0].name = "renamed".to_string();
+        // Older file systems only keep the modification time in seconds.
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        std::fs::write(&path, serde_yaml::to_string(&theirs).unwrap()).unwrap();
+
+        assert!(ws.take_external_change());
+        assert!(!ws.take_external_change());
+        assert!(!ws.has_same_content(&saved(&path)));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn refuses_edits_without_a_file() {
+        let mut ws = Workspace {
+            projects: vec![Project::new("a")],
+            ..Default::default()
+        };
+        let id = ws.projects[0].get_id().to_string();
+
+        let renamed = ws.rename_project(&id, "renamed");
+        assert!(matches!(
+            renamed,
+            Err(Error::Io(IoError {
+                path: None,
+                kind: io::ErrorKind::NotFound,
+                ..
+            }))
+        ));
+        assert!(ws.set_selected_project(Some(id), None).is_ok());
+        assert!(ws.set_selected_resource(Some(bucket("a"))).is_ok());
+    }
+
+    #[test]
+    fn keeps_external_changes_until_they_are_dealt_with() {
+        let (mut ws, path) = workspace(&["a"]);
+        ws.save().unwrap();
+        let mut theirs = saved(&path);
+        theirs.projects[0].name = "theirs".to_string();
+        // Older file systems only keep the modification time in seconds.
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        std::fs::write(&path, serde_yaml::to_string(&theirs).unwrap()).unwrap();
+
+        // Selecting only changes the selection in memory.
+        ws.set_selected_resource(Some(bucket("a"))).unwrap();
+        assert_eq!(names(&saved(&path)), vec!["theirs"]);
+        assert_eq!(saved(&path).appearance.selected_resource, None);
+        assert!(ws.take_external_change());
+
+        // Edits are refused, also once the change has been reported.
+        let id = ws.projects[0].get_id().to_string();
+        assert!(matches!(
+            ws.rename_project(&id, "mine"),
+            Err(Error::Io(IoError { path: Some(_), .. }))
+        ));
+        assert_eq!(names(&saved(&path)), vec!["theirs"]);
+        assert!(!ws.take_external_change());
+
+        ws.overwrite().unwrap();
+        assert_eq!(names(&saved(&path)), vec!["mine"]);
+        ws.set_selected_resource(None).unwrap();
+        ws.rename_project(&id, "again").unwrap();
+        assert_eq!(names(&saved(&path)), vec!["again"]);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn merges_projects_and_resources_of_both() {
+        let (mut ws, path) = workspace(&["a", "b"]);
+        let mut theirs = ws.clone();
+        theirs.projects[0].name = "renamed".to_string();
+        theirs.projects[0].add_resource(bucket("theirs"));
+        theirs.projects.remove(1);
+        theirs.projects.push(Project::new("c"));
+        ws.projects[0].add_resource(bucket("mine"));
+
+        ws.merge(theirs).unwrap();
+
+        assert_eq!(names(&ws), vec!["renamed", "c", "b"]);
+        assert_eq!(
+            ws.projects[0].resources,
+            vec![bucket("theirs"), bucket("mine")]
+        );
+        assert_eq!(names(&saved(&path)), vec!["renamed", "c", "b"]);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn migrates_on_load_and_keeps_unknown_fields() {
+        let path =
+            std::env::temp_dir().join(format!("awsome-workspace-{}.yaml", ulid::Ulid::new()));
+        std::fs::write(
+            &path,
+            "version: 99
+layout: grid
+projects:
+- id: p1
+  name: a
+  color: blue
+appearance:
+  selected_project_id: p1
+  zoom: 2
+settings:
+  theme: dark
+",
+        )
+        .unwrap();
+
+        let mut ws =
+            iced::futures::executor::block_on(Workspace::load_workspace_file(path.clone()))
+                .unwrap();
+        ws.rename_project("p1", "renamed").unwrap();
+
+        let saved: serde_yaml::Value = serde_yaml::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], serde_yaml::Value::from(99));
+        assert_eq!(saved["layout"], serde_yaml::Value::from("grid"));
+        assert_eq!(
+            saved["projects"][0]["name"],
+            serde_yaml::Value::from("renamed")
+        );
+        assert_eq!(
+            saved["projects"][0]["color"],
+            serde_yaml::Value::from("blue")
+        );
+        assert_eq!(saved["appearance"]["zoom"], serde_yaml::Value::from(2));
+        assert_eq!(saved["settings"]["theme"], serde_yaml::Value::from("dark"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn creates_workspaces_without_overwriting_them() {
+        let (ws, path) = workspace(&["a"]);
+        let dir = std::env::temp_dir().join(format!("awsome-workspaces-{}", ulid::Ulid::new()));
+        let copy_path = workspace_path(&dir, "copy");
+
+        let copy = Workspace::create_at(copy_path.clone(), ws.clone()).unwrap();
+        assert_eq!(copy.get_path(), Some(copy_path.as_path()));
+        assert_eq!(names(&saved(&copy_path)), vec!["a"]);
+        assert!(Workspace::create_at(copy_path, Workspace::default()).is_err());
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
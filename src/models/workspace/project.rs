@@ -1,14 +1,100 @@
 use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
 
 use crate::models::{service::Service, workspace::resource_descriptor::ResourceDescriptor};
 
 #[derive(Debug, Clone, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Project {
+    /// Missing in projects added to the file by hand.
+    #[serde(default = "new_id")]
     id: String,
     pub name: String,
+    /// In a project with environments, resources without a profile and
+    /// region are bound to those of the selected environment. Resources with
+    /// their own, e.g. of a shared account, are the same in every environment.
+    #[serde(default)]
     pub resources: Vec<ResourceDescriptor>,
+    /// Where the same resources live in each stage, e.g.
+    ///
+    /// ```yaml
+    /// environments:
+    ///   - name: dev
+    ///     profile: dev
+    ///     region: eu-west-1
+    ///   - name: prod
+    ///     profile: prod
+    ///     region: eu-central-1
+    ///     name_pattern: "{env}-{name}"
+    /// ```
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environments: Vec<Environment>,
+    /// Fields of newer versions of the app, kept so that saving does not
+    /// drop them.
+    #[serde(flatten)]
+    extra: Mapping,
+}
+
+/// A binding of a project's resources to a profile and region.
+#[derive(Debug, Clone, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Environment {
+    pub name: String,
+    pub profile: String,
+    pub region: String,
+    /// Forms the IDs of the environment's resources from the project's,
+    /// which replace `{name}`. Without it the IDs are the same in every
+    /// environment. `{env}` is replaced by the environment's name, in the
+    /// pattern as well as in the project's IDs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_pattern: Option<String>,
+}
+
+impl Environment {
+    /// Resources with a profile or region of their own are kept as they are.
+    pub fn bind(&self, resource: &ResourceDescriptor) -> ResourceDescriptor {
+        if !resource.profile.is_empty() || !resource.region.is_empty() {
+            return resource.clone();
+        }
+        let id = match &self.name_pattern {
+            Some(pattern) => pattern.replace("{name}", &resource.id),
+            None => resource.id.clone(),
+        };
+        ResourceDescriptor {
+            profile: self.profile.clone(),
+            region: self.region.clone(),
+            service: resource.service.clone(),
+            id: id.replace("{env}", &self.name),
+        }
+    }
+
+    /// The project's resource that is bound to `resource`, if `resource`
+    /// belongs to the environment. The environment's name is not turned back
+    /// into `{env}`, as it cannot be told apart from the rest of the ID, so
+    /// e.g. `dev-shared-dev-authorizer` becomes `shared-dev-authorizer`.
+    fn unbind(&self, resource: &ResourceDescriptor) -> Option<ResourceDescriptor> {
+        if resource.profile != self.profile || resource.region != self.region {
+            return None;
+        }
+        let id = match &self.name_pattern {
+            Some(pattern) => {
+                let pattern = pattern.replace("{env}", &self.name);
+                let (prefix, suffix) = pattern.split_once("{name}")?;
+                resource.id.strip_prefix(prefix)?.strip_suffix(suffix)?
+            }
+            None => &resource.id,
+        };
+        Some(ResourceDescriptor {
+            profile: String::new(),
+            region: String::new(),
+            service: resource.service.clone(),
+            id: id.to_string(),
+        })
+    }
+}
+
+fn new_id() -> String {
+    ulid::Ulid::new().to_string()
 }
 
 impl std::fmt::Display for Project {
@@ -20,12 +106,74 @@ impl std::fmt::Display for Project {
 impl Project {
     pub fn new(name: &str) -> Self {
         Self {
-            id: ulid::Ulid::new().to_string(),
+            id: new_id(),
             name: name.to_string(),
             resources: vec![],
+            environments: vec![],
+            extra: Mapping::new(),
         }
     }
 
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// `name`, or the first environment if there is no such one.
+    pub fn get_environment(&self, name: Option<&str>) -> Option<&Environment> {
+        name.and_then(|n| self.environments.iter().find(|e| e.name == n))
+            .or(self.environments.first())
+    }
+
+    /// The project with its resources bound to the environment. Projects
+    /// without environments are returned as they are.
+    pub fn bound_to(&self, environment: Option<&str>) -> Project {
+        let Some(environment) = self.get_environment(environment) else {
+            return self.clone();
+        };
+        Project {
+            resources: self.resources.iter().map(|r| environment.bind(r)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether `resource` is `own`, as it is or bound to an environment.
+    fn is_bound_to(&self, own: &ResourceDescriptor, resource: &ResourceDescriptor) -> bool {
+        own == resource || self.environments.iter().any(|e| e.bind(own) == *resource)
+    }
+
+    /// False if the project already has the resource. A resource of one of
+    /// the environments is added for all of them.
+    pub fn add_resource(&mut self, resource: ResourceDescriptor) -> bool {
+        if self
+            .resources
+            .iter()
+            .any(|r| self.is_bound_to(r, &resource))
+        {
+            return false;
+        }
+        let resource = self
+            .environments
+            .iter()
+            .find_map(|e| e.unbind(&resource))
+            .unwrap_or(resource);
+        self.resources.push(resource);
+        true
+    }
+
+    /// False if the project does not have the resource, in any of its
+    /// environments.
+    pub fn remove_resource(&mut self, resource: &ResourceDescriptor) -> bool {
+        let len = self.resources.len();
+        let kept: Vec<_> = self
+            .resources
+            .iter()
+            .filter(|r| !self.is_bound_to(r, resource))
+            .cloned()
+            .collect();
+        self.resources = kept;
+        self.resources.len() != len
+    }
+
     pub fn get_services(&self) -> Vec<Service> {
         self.resources
             .iter()
@@ -35,3 +183,108 @@ impl Project {
             .collect::<Vec<_>>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::lambda;
+
+    fn function(profile: &str, region: &str, id: &str) -> ResourceDescriptor {
+        ResourceDescriptor {
+            profile: profile.to_string(),
+            region: region.to_string(),
+            service: lambda::SERVICE.clone(),
+            id: id.to_string(),
+        }
+    }
+
+    fn project() -> Project {
+        let project: Project = serde_yaml::from_str(
+            "name: orders
+resources:
+- service: Lambda
+  id: orders-api
+- service: Lambda
+  id: shared-{env}-authorizer
+environments:
+- name: dev
+  profile: dev
+  region: eu-west-1
+  name_pattern: \"{env}-{name}\"
+- name: prod
+  profile: prod
+  region: eu-central-1
+",
+        )
+        .unwrap();
+        project
+    }
+
+    #[test]
+    fn binds_resources_to_the_selected_environment() {
+        let project = project();
+
+        assert_eq!(
+            project.bound_to(None).resources,
+            vec![
+                function("dev", "eu-west-1", "dev-orders-api"),
+                function("dev", "eu-west-1", "dev-shared-dev-authorizer"),
+            ]
+        );
+        assert_eq!(
+            project.bound_to(Some("prod")).resources,
+            vec![
+                function("prod", "eu-central-1", "orders-api"),
+                function("prod", "eu-central-1", "shared-prod-authorizer"),
+            ]
+        );
+        assert_eq!(project.bound_to(Some("gone")), project.bound_to(None));
+
+        let plain = Project::new("plain");
+        assert_eq!(plain.bound_to(Some("dev")), plain);
+    }
+
+    #[test]
+    fn adds_and_removes_resources_of_any_environment() {
+        let mut project = project();
+
+        assert!(!project.add_resource(function("prod", "eu-central-1", "orders-api")));
+        assert!(project.add_resource(function("dev", "eu-west-1", "dev-payments")));
+        assert_eq!(project.resources[2], function("", "", "payments"));
+        assert_eq!(
+            project.bound_to(Some("prod")).resources[2],
+            function("prod", "eu-central-1", "payments")
+        );
+
+        assert!(project.remove_resource(&function("prod", "eu-central-1", "payments")));
+        assert!(!project.remove_resource(&function("prod", "eu-central-1", "payments")));
+        assert_eq!(project.resources.len(), 2);
+    }
+
+    #[test]
+    fn keeps_resources_of_no_environment_where_they_are() {
+        let mut project = project();
+        let shared = function("shared", "us-east-1", "edge-auth");
+
+        assert!(project.add_resource(shared.clone()));
+        assert_eq!(project.resources[2], shared);
+        assert_eq!(project.bound_to(Some("dev")).resources[2], shared);
+        assert_eq!(project.bound_to(Some("prod")).resources[2], shared);
+        assert!(!project.add_resource(shared.clone()));
+
+        assert!(project.remove_resource(&shared));
+        assert_eq!(project.resources.len(), 2);
+    }
+
+    #[test]
+    fn unbinding_keeps_the_environment_name_in_ids() {
+        let mut project = project();
+
+        assert!(project.add_resource(function("dev", "eu-west-1", "dev-dev-tools")));
+        assert_eq!(project.resources[2], function("", "", "dev-tools"));
+        assert_eq!(
+            project.bound_to(Some("prod")).resources[2],
+            function("prod", "eu-central-1", "dev-tools")
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use crate::models::service::Service;
+use crate::{models::service::Service, providers::registry};
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ResourceDescriptor {
+    /// Left out for the resources of projects with environments.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub profile: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub region: String,
     pub service: Service,
     pub id: String,
@@ -18,9 +21,9 @@ impl std::fmt::Display for ResourceDescriptor {
 
 impl ResourceDescriptor {
     pub fn get_display_name(&self) -> String {
-        match self.service {
-            Service::Lambda => self.id.to_string(),
-            Service::S3 => self.id.to_string(),
+        match registry().get(&self.service) {
+            Some(provider) => provider.display_name(self),
+            None => self.id.clone(),
         }
     }
 }
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Settings {
+    /// Profiles and accounts that must not be changed by accident, e.g.
+    ///
+    /// ```yaml
+    /// protected:
+    ///   - profile: prod
+    ///   - account: "123456789012"
+    ///     mode: confirm
+    /// ```
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected: Vec<ProtectedTarget>,
+
+    /// Sends the requests of every profile to this endpoint instead of AWS,
+    /// e.g. `http://localhost:4566` to work against LocalStack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_url: Option<String>,
+
+    /// Addresses buckets as `endpoint/bucket` instead of `bucket.endpoint`,
+    /// which most emulators require.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub s3_force_path_style: bool,
+
+    /// How requests are retried, timed out and throttled, e.g.
+    ///
+    /// ```yaml
+    /// requests:
+    ///   retry_mode: adaptive
+    ///   max_attempts: 5
+    ///   connect_timeout_secs: 5
+    ///   operation_timeout_secs: 60
+    ///   max_concurrency_per_account: 8
+    /// ```
+    #[serde(default, skip_serializing_if = "RequestSettings::is_default")]
+    pub requests: RequestSettings,
+
+    /// Settings of newer versions of the app, kept so that saving does not
+    /// drop them.
+    #[serde(flatten)]
+    extra: Mapping,
+}
+
+/// Unset fields keep the SDK's defaults, or what the profile configures.
+#[derive(Debug, Clone, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RequestSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_mode: Option<RetryMode>,
+
+    /// Including the first attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Covers every attempt of an operation, including the waits between
+    /// them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_timeout_secs: Option<u64>,
+
+    /// Requests in flight to one account at a time, across all of its
+    /// profiles and regions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency_per_account: Option<usize>,
+}
+
+impl RequestSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryMode {
+    #[default]
+    Standard,
+    /// Also slows down the client once requests are being throttled.
+    Adaptive,
+}
+
+/// Matches a profile by name, an account by ID, or both if both are set.
+#[derive(Debug, Clone, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProtectedTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+
+    #[serde(default)]
+    pub mode: ProtectionMode,
+}
+
+/// Ordered from the least to the most strict.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionMode {
+    /// Mutating operations need the resource name to be typed in.
+    Confirm,
+    /// The profile's account is not known yet, and an account rule might
+    /// apply to it. Mutating operations are disabled until it is known.
+    #[serde(skip)]
+    Unverified,
+    /// Mutating operations are disabled.
+    #[default]
+    ReadOnly,
+}
+
+impl std::fmt::Display for ProtectionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Confirm => write!(f, "changes must be confirmed by typing the resource name"),
+            Self::Unverified => write!(
+                f,
+                "changes are disabled until the account is known, as it may be protected"
+            ),
+            Self::ReadOnly => write!(f, "read-only"),
+        }
+    }
+}
+
+impl ProtectionMode {
+    /// Whether mutating operations are disabled altogether.
+    pub fn blocks_changes(self) -> bool {
+        self != Self::Confirm
+    }
+}
+
+impl ProtectedTarget {
+    fn matches(&self, profile: &str, account: Option<&str>) -> bool {
+        let profile_matches = self.profile.as_ref().map(|p| p == profile);
+        let account_matches = self.account.as_ref().map(|a| Some(a.as_str()) == account);
+        match (profile_matches, account_matches) {
+            (None, None) => false,
+            (p, a) => p.unwrap_or(true) && a.unwrap_or(true),
+        }
+    }
+}
+
+impl Settings {
+    /// The strictest protection that applies to `profile`. `account` is
+    /// `None` while the profile's account is unknown, or could not be
+    /// looked up. If an account rule could then still apply to the profile,
+    /// it is `Unverified` rather than taken to be protected.
+    pub fn protection_for(&self, profile: &str, account: Option<&str>) -> Option<ProtectionMode> {
+        let mut mode = self
+            .protected
+            .iter()
+            .filter(|t| t.matches(profile, account))
+            .map(|t| t.mode)
+            .max();
+
+        let may_match_an_account = self
+            .protected
+            .iter()
+            .any(|t| t.account.is_some() && t.profile.as_ref().is_none_or(|p| p == profile));
+        if account.is_none() && may_match_an_account {
+            mode = mode.max(Some(ProtectionMode::Unverified));
+        }
+        mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ProtectionMode::*;
+
+    fn parse(yaml: &str) -> Settings {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn matches_profiles_and_accounts() {
+        let settings = parse(
+            "protected:
+- profile: prod
+- account: \"111111111111\"
+  mode: confirm
+- profile: admin
+  account: \"222222222222\"
+",
+        );
+
+        assert_eq!(
+            settings.protection_for("prod", Some("999999999999")),
+            Some(ReadOnly)
+        );
+        assert_eq!(
+            settings.protection_for("dev", Some("111111111111")),
+            Some(Confirm)
+        );
+        assert_eq!(settings.protection_for("dev", Some("999999999999")), None);
+        // A rule with both only matches where both do.
+        assert_eq!(
+            settings.protection_for("admin", Some("222222222222")),
+            Some(ReadOnly)
+        );
+        assert_eq!(settings.protection_for("other", Some("222222222222")), None);
+        assert_eq!(settings.protection_for("admin", Some("999999999999")), None);
+    }
+
+    #[test]
+    fn the_strictest_rule_wins() {
+        let settings = parse(
+            "protected:
+- profile: prod
+  mode: confirm
+- account: \"111111111111\"
+",
+        );
+
+        assert_eq!(
+            settings.protection_for("prod", Some("999999999999")),
+            Some(Confirm)
+        );
+        assert_eq!(
+            settings.protection_for("prod", Some("111111111111")),
+            Some(ReadOnly)
+        );
+    }
+
+    #[test]
+    fn unknown_accounts_are_unverified_if_an_account_rule_could_apply() {
+        let settings = parse(
+            "protected:
+- profile: prod
+  mode: confirm
+- profile: admin
+  account: \"222222222222\"
+",
+        );
+
+        assert_eq!(settings.protection_for("admin", None), Some(Unverified));
+        assert_eq!(settings.protection_for("prod", None), Some(Confirm));
+        assert_eq!(settings.protection_for("dev", None), None);
+
+        let any_profile = parse("protected:\n- account: \"111111111111\"\n");
+        assert_eq!(any_profile.protection_for("dev", None), Some(Unverified));
+        assert!(Unverified.blocks_changes());
+        assert!(!Confirm.blocks_changes());
+    }
+
+    #[test]
+    fn unverified_is_not_a_setting() {
+        assert!(serde_yaml::from_str::<ProtectionMode>("unverified").is_err());
+        assert_eq!(
+            serde_yaml::from_str::<ProtectionMode>("read_only").unwrap(),
+            ReadOnly
+        );
+    }
+}
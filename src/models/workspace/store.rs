@@ -0,0 +1,302 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, IoError};
+
+const DEFAULT_WORKSPACE_NAME: &str = "default";
+const RECENT_FILE_NAME: &str = "recent.yaml";
+const MAX_RECENT_WORKSPACES: usize = 10;
+/// Replaced versions kept besides a workspace, as `name.yaml.bak.1`
+/// (newest) to `name.yaml.bak.N`.
+pub const WORKSPACE_BACKUPS: usize = 3;
+
+/// The platform's config directory of the app, e.g. `~/.config/awsome` on
+/// Linux.
+fn config_dir() -> Result<PathBuf, Error> {
+    let Some(project_dirs) = directories::ProjectDirs::from("", "", "awsome") else {
+        return Err(Error::Io(IoError {
+            path: None,
+            kind: io::ErrorKind::NotFound,
+            message: "Unable to determine the config directory".to_string(),
+        }));
+    };
+    Ok(project_dirs.config_dir().to_path_buf())
+}
+
+/// Where workspaces are created, e.g. `~/.config/awsome/workspaces` on Linux.
+pub fn workspaces_dir() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join("workspaces"))
+}
+
+/// The workspace opened when there is no recent one.
+pub fn default_workspace_path() -> Result<PathBuf, Error> {
+    Ok(workspace_path(&workspaces_dir()?, DEFAULT_WORKSPACE_NAME))
+}
+
+/// The file of the workspace named `name` in `dir`. Characters that are not
+/// allowed in file names on some platform are replaced.
+pub fn workspace_path(dir: &Path, name: &str) -> PathBuf {
+    let file_name: String = name
+        .trim()
+        .trim_start_matches('.')
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    dir.join(format!("{}.yaml", file_name))
+}
+
+/// Replaces the file at `path` with `contents`, so that a crash leaves
+/// either the old or the new file but never a truncated one. The replaced
+/// version becomes the newest of `backups` backups.
+pub fn write_atomically(path: &Path, contents: &[u8], backups: usize) -> Result<(), Error> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    }
+    let tmp = with_suffix(path, ".tmp");
+    let written = File::create(&tmp).and_then(|mut f| {
+        f.write_all(contents)?;
+        f.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(Error::io(&tmp, e));
+    }
+
+    // Unchanged saves, e.g. of the same selection, would only push the
+    // useful backups out.
+    let changed = fs::read(path).is_ok_and(|old| old != contents);
+    if changed && backups > 0 {
+        rotate_backups(path, backups)?;
+    }
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        Error::io(path, e)
+    })
+}
+
+fn rotate_backups(path: &Path, backups: usize) -> Result<(), Error> {
+    for n in (1..backups).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            let to = backup_path(path, n + 1);
+            fs::rename(&from, &to).map_err(|e| Error::io(&to, e))?;
+        }
+    }
+    let backup = backup_path(path, 1);
+    fs::copy(path, &backup).map_err(|e| Error::io(&backup, e))?;
+    Ok(())
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &format!(".bak.{}", n))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = OsString::from(path.as_os_str());
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+/// When the file was last modified, or `None` if it is gone.
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A workspace file, named after the file without its extension.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkspaceEntry {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl WorkspaceEntry {
+    pub fn new(path: PathBuf) -> Self {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        Self { name, path }
+    }
+}
+
+impl std::fmt::Display for WorkspaceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The workspaces in `dir`, by name. A missing directory has none.
+pub fn list_workspaces(dir: &Path) -> Vec<WorkspaceEntry> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut entries: Vec<_> = read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "yaml" || e == "yml"))
+        .map(WorkspaceEntry::new)
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+/// The workspaces opened last, kept in the config directory so that the
+/// last one is opened again on the next launch.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct RecentWorkspaces {
+    /// Most recent first.
+    paths: Vec<PathBuf>,
+}
+
+impl RecentWorkspaces {
+    /// An unreadable list is treated as empty, since it can be rebuilt.
+    pub fn load() -> Self {
+        let loaded = config_dir().and_then(|dir| Self::load_from(&dir.join(RECENT_FILE_NAME)));
+        loaded.unwrap_or_else(|e| {
+            tracing::warn!(error = ?e, "Failed to load the recent workspaces");
+            Self::default()
+        })
+    }
+
+    fn load_from(path: &Path) -> Result<Self, Error> {
+        let f = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+        serde_yaml::from_reader(f).map_err(|e| Error::yaml(path, e))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), Error> {
+        let yaml = serde_yaml::to_string(self).map_err(|e| Error::yaml(path, e))?;
+        write_atomically(path, yaml.as_bytes(), 0)
+    }
+
+    /// The most recent workspace that still exists.
+    pub fn last(&self) -> Option<&Path> {
+        self.paths.iter().map(|p| p.as_path()).find(|p| p.exists())
+    }
+
+    /// The recent workspaces that still exist, most recent first.
+    pub fn entries(&self) -> Vec<WorkspaceEntry> {
+        self.paths
+            .iter()
+            .filter(|p| p.exists())
+            .cloned()
+            .map(WorkspaceEntry::new)
+            .collect()
+    }
+
+    fn add(&mut self, path: &Path) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.paths.retain(|p| *p != path);
+        self.paths.insert(0, path);
+        self.paths.truncate(MAX_RECENT_WORKSPACES);
+    }
+}
+
+/// Moves the workspace to the top of the recent workspaces.
+pub fn remember_workspace(path: &Path) -> Result<(), Error> {
+    let recent_file = config_dir()?.join(RECENT_FILE_NAME);
+    let mut recent = RecentWorkspaces::load_from(&recent_file)?;
+    recent.add(path);
+    recent.save_to(&recent_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("awsome-store-{}", ulid::Ulid::new()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn replaces_characters_not_allowed_in_file_names() {
+        let dir = Path::new("/workspaces");
+
+        assert_eq!(
+            workspace_path(dir, " prod/eu: main "),
+            dir.join("prod_eu_ main.yaml")
+        );
+        assert_eq!(workspace_path(dir, "../up"), dir.join("_up.yaml"));
+    }
+
+    #[test]
+    fn lists_workspace_files_by_name() {
+        let dir = temp_dir();
+        for name in ["b.yaml", "a.yml", "notes.txt"] {
+            File::create(dir.join(name)).unwrap();
+        }
+
+        let names: Vec<_> = list_workspaces(&dir).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["a", "b"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn writes_atomically_and_keeps_backups() {
+        let dir = temp_dir();
+        let path = dir.join("w.yaml");
+
+        for contents in ["1", "2", "2", "3", "4", "5"] {
+            write_atomically(&path, contents.as_bytes(), WORKSPACE_BACKUPS).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "5");
+        let backups: Vec<_> = (1..=WORKSPACE_BACKUPS)
+            .map(|n| fs::read_to_string(backup_path(&path, n)).unwrap())
+            .collect();
+        assert_eq!(backups, vec!["4", "3", "2"]);
+        assert!(!backup_path(&path, WORKSPACE_BACKUPS + 1).exists());
+        assert!(!with_suffix(&path, ".tmp").exists());
+        assert_eq!(list_workspaces(&dir).len(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn keeps_recent_workspaces_most_recent_first() {
+        let dir = temp_dir();
+        let paths: Vec<_> = (0..MAX_RECENT_WORKSPACES + 2)
+            .map(|i| dir.join(format!("{}.yaml", i)))
+            .collect();
+        for path in &paths {
+            File::create(path).unwrap();
+        }
+        let paths: Vec<_> = paths.iter().map(|p| fs::canonicalize(p).unwrap()).collect();
+
+        let mut recent = RecentWorkspaces::default();
+        for path in &paths {
+            recent.add(path);
+        }
+        recent.add(&paths[5]);
+        fs::remove_file(&paths[5]).unwrap();
+
+        let recent_file = dir.join(RECENT_FILE_NAME);
+        recent.save_to(&recent_file).unwrap();
+        let recent = RecentWorkspaces::load_from(&recent_file).unwrap();
+
+        assert_eq!(recent.last(), Some(paths[11].as_path()));
+        let entries = recent.entries();
+        assert_eq!(entries.len(), MAX_RECENT_WORKSPACES - 1);
+        assert_eq!(entries[0].name, "11");
+        assert!(!entries.iter().any(|e| e.name == "1"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
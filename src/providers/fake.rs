@@ -0,0 +1,85 @@
+use iced::{
+    futures::{future::BoxFuture, FutureExt},
+    widget::text,
+    Element,
+};
+
+use crate::{
+    error::Error,
+    message::Message,
+    models::{resource::Resource, service::Service, workspace::ResourceDescriptor},
+    providers::ResourceProvider,
+    view::resource_details::DetailsView,
+};
+
+/// A provider that serves a fixed set of resources without calling AWS. Each
+/// resource holds its own descriptor as data.
+pub struct FakeProvider {
+    service: Service,
+    resources: Vec<ResourceDescriptor>,
+}
+
+impl FakeProvider {
+    pub fn new(service: Service, resources: Vec<ResourceDescriptor>) -> Self {
+        Self { service, resources }
+    }
+}
+
+impl ResourceProvider for FakeProvider {
+    fn service(&self) -> &Service {
+        &self.service
+    }
+
+    fn icon(&self, size: f32) -> Element<'static, Message> {
+        text("?").size(size).into()
+    }
+
+    fn list(
+        &self,
+        profile: String,
+        region: String,
+        all_regions: bool,
+    ) -> BoxFuture<'static, Result<Vec<Resource>, Error>> {
+        let resources = self
+            .resources
+            .iter()
+            .filter(|rd| rd.profile == profile && (all_regions || rd.region == region))
+            .map(|rd| Resource::new(rd.clone(), rd.clone()))
+            .collect();
+        async { Ok(resources) }.boxed()
+    }
+
+    fn load(&self, descriptor: ResourceDescriptor) -> BoxFuture<'static, Result<Resource, Error>> {
+        let found = self.resources.contains(&descriptor);
+        async move {
+            if found {
+                Ok(Resource::new(descriptor.clone(), descriptor))
+            } else {
+                Err(Error::ResourceNotFound)
+            }
+        }
+        .boxed()
+    }
+
+    fn details_view(&self) -> Box<dyn DetailsView> {
+        Box::new(FakeDetails)
+    }
+}
+
+struct FakeDetails;
+
+impl DetailsView for FakeDetails {
+    fn set_resource(&mut self, _resource: Option<&Resource>) {}
+
+    fn view<'a>(&'a self, resource: &'a Resource) -> Element<'a, Message> {
+        text(resource.descriptor.id.clone()).into()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,62 @@
+use iced::{
+    futures::{future::BoxFuture, FutureExt},
+    Element, Length,
+};
+
+use crate::{
+    error::Error,
+    message::Message,
+    models::{
+        lambda_function::{list_lambda_functions, load_lambda_function},
+        resource::Resource,
+        service::Service,
+        workspace::ResourceDescriptor,
+    },
+    providers::ResourceProvider,
+    view::{
+        icons::ICONS,
+        resource_details::{lambda_function_details::LambdaFunctionDetails, DetailsView},
+    },
+};
+
+pub static SERVICE: Service = Service::new_static("Lambda");
+
+pub struct LambdaProvider;
+
+impl ResourceProvider for LambdaProvider {
+    fn service(&self) -> &Service {
+        &SERVICE
+    }
+
+    fn icon(&self, size: f32) -> Element<'static, Message> {
+        ICONS.lightning(size, Length::Fixed(16.0), Length::Fixed(16.0))
+    }
+
+    fn list(
+        &self,
+        profile: String,
+        region: String,
+        _all_regions: bool,
+    ) -> BoxFuture<'static, Result<Vec<Resource>, Error>> {
+        async move {
+            let functions = list_lambda_functions(&profile, &region, &SERVICE).await?;
+            Ok(functions
+                .into_iter()
+                .map(|(rd, f)| Resource::new(rd, f))
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn load(&self, descriptor: ResourceDescriptor) -> BoxFuture<'static, Result<Resource, Error>> {
+        async move {
+            let function = load_lambda_function(&descriptor).await?;
+            Ok(Resource::new(descriptor, function))
+        }
+        .boxed()
+    }
+
+    fn details_view(&self) -> Box<dyn DetailsView> {
+        Box::new(LambdaFunctionDetails::new())
+    }
+}
@@ -0,0 +1,222 @@
+#[cfg(test)]
+pub mod fake;
+pub mod lambda;
+pub mod s3;
+
+use iced::{
+    futures::{future::BoxFuture, FutureExt},
+    Element, Task,
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+    error::Error,
+    message::Message,
+    models::{resource::Resource, service::Service, workspace::ResourceDescriptor},
+    view::{resource_details::DetailsView, resources_table::ColumnKind},
+};
+
+static REGISTRY: Lazy<ProviderRegistry> = Lazy::new(|| {
+    ProviderRegistry::new(vec![
+        Box::new(lambda::LambdaProvider),
+        Box::new(s3::S3Provider),
+    ])
+});
+
+/// Everything the app needs to know about one service. Adding a service means
+/// implementing this trait and registering the provider in `REGISTRY`.
+pub trait ResourceProvider: Send + Sync {
+    fn service(&self) -> &Service;
+
+    fn icon(&self, size: f32) -> Element<'static, Message>;
+
+    /// Lists the resources of `profile` in `region`. `all_regions` only
+    /// matters if the provider `lists_globally`.
+    fn list(
+        &self,
+        profile: String,
+        region: String,
+        all_regions: bool,
+    ) -> BoxFuture<'static, Result<Vec<Resource>, Error>>;
+
+    fn load(&self, descriptor: ResourceDescriptor) -> BoxFuture<'static, Result<Resource, Error>>;
+
+    fn display_name(&self, descriptor: &ResourceDescriptor) -> String {
+        descriptor.id.clone()
+    }
+
+    /// Columns of the resources table in the Projects tab.
+    fn table_columns(&self) -> Vec<ColumnKind> {
+        vec![ColumnKind::Name, ColumnKind::Profile, ColumnKind::Region]
+    }
+
+    /// Whether a single listing returns the resources of every region, so
+    /// that the Explore tab can offer to show them all at once.
+    fn lists_globally(&self) -> bool {
+        false
+    }
+
+    fn details_view(&self) -> Box<dyn DetailsView>;
+
+    /// Follow-up work once a resource has been selected, e.g. listing the
+    /// objects of a bucket.
+    fn open(&self, _resource: &Resource) -> Task<Message> {
+        Task::none()
+    }
+}
+
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn ResourceProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new(providers: Vec<Box<dyn ResourceProvider>>) -> Self {
+        Self { providers }
+    }
+
+    pub fn get(&self, service: &Service) -> Option<&dyn ResourceProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.service() == service)
+            .map(|p| p.as_ref())
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &dyn ResourceProvider> {
+        self.providers.iter().map(|p| p.as_ref())
+    }
+
+    /// In the order the providers were registered.
+    pub fn services(&self) -> Vec<&Service> {
+        self.providers().map(|p| p.service()).collect()
+    }
+
+    pub fn list(
+        &self,
+        profile: String,
+        region: String,
+        service: &Service,
+        all_regions: bool,
+    ) -> BoxFuture<'static, Result<Vec<Resource>, Error>> {
+        match self.get(service) {
+            Some(provider) => provider.list(profile, region, all_regions),
+            None => async { Err(Error::InvalidResourceDescriptor) }.boxed(),
+        }
+    }
+
+    pub fn load(
+        &self,
+        descriptor: ResourceDescriptor,
+    ) -> BoxFuture<'static, Result<Resource, Error>> {
+        match self.get(&descriptor.service) {
+            Some(provider) => provider.load(descriptor),
+            None => async { Err(Error::InvalidResourceDescriptor) }.boxed(),
+        }
+    }
+
+    pub fn open(&self, resource: &Resource) -> Task<Message> {
+        match self.get(&resource.descriptor.service) {
+            Some(provider) => provider.open(resource),
+            None => Task::none(),
+        }
+    }
+}
+
+/// The providers of every supported service.
+pub fn registry() -> &'static ProviderRegistry {
+    &REGISTRY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fake::FakeProvider, *};
+
+    fn block_on<T>(future: BoxFuture<'static, T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn descriptor(service: &Service, region: &str, id: &str) -> ResourceDescriptor {
+        ResourceDescriptor {
+            profile: "dev".to_string(),
+            region: region.to_string(),
+            service: service.clone(),
+            id: id.to_string(),
+        }
+    }
+
+    fn fake_registry() -> (Service, ProviderRegistry) {
+        let service = Service::new_static("Fake");
+        let provider = FakeProvider::new(
+            service.clone(),
+            vec![
+                descriptor(&service, "eu-west-1", "a"),
+                descriptor(&service, "us-east-1", "b"),
+            ],
+        );
+        (service, ProviderRegistry::new(vec![Box::new(provider)]))
+    }
+
+    #[test]
+    fn lists_through_the_provider_of_the_service() {
+        let (service, registry) = fake_registry();
+
+        let resources =
+            block_on(registry.list("dev".to_string(), "eu-west-1".to_string(), &service, false))
+                .unwrap();
+
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].descriptor.id, "a");
+        assert_eq!(
+            resources[0].data::<ResourceDescriptor>(),
+            Some(&resources[0].descriptor)
+        );
+        assert_eq!(resources[0].data::<String>(), None);
+    }
+
+    #[test]
+    fn loads_through_the_provider_of_the_service() {
+        let (service, registry) = fake_registry();
+
+        let resource = block_on(registry.load(descriptor(&service, "us-east-1", "b"))).unwrap();
+        assert_eq!(resource.descriptor.id, "b");
+
+        let missing = block_on(registry.load(descriptor(&service, "us-east-1", "c")));
+        assert!(matches!(missing, Err(Error::ResourceNotFound)));
+    }
+
+    #[test]
+    fn unknown_services_are_rejected() {
+        let (_, registry) = fake_registry();
+        let unknown = Service::new_static("Unknown");
+
+        assert!(registry.get(&unknown).is_none());
+        let listed =
+            block_on(registry.list("dev".to_string(), "eu-west-1".to_string(), &unknown, false));
+        assert!(matches!(listed, Err(Error::InvalidResourceDescriptor)));
+        let loaded = block_on(registry.load(descriptor(&unknown, "eu-west-1", "a")));
+        assert!(matches!(loaded, Err(Error::InvalidResourceDescriptor)));
+    }
+
+    #[test]
+    fn default_registry_covers_the_workspace_services() {
+        let services = registry().services();
+
+        assert_eq!(services, vec![&lambda::SERVICE, &s3::SERVICE]);
+        // Workspace files store services by these names.
+        assert_eq!(lambda::SERVICE.to_string(), "Lambda");
+        assert_eq!(s3::SERVICE.to_string(), "S3");
+        assert!(registry().get(&s3::SERVICE).unwrap().lists_globally());
+        assert!(!registry().get(&lambda::SERVICE).unwrap().lists_globally());
+    }
+
+    #[test]
+    fn services_round_trip_through_yaml() {
+        let yaml = serde_yaml::to_string(&descriptor(&s3::SERVICE, "eu-west-1", "a")).unwrap();
+        assert!(yaml.contains("service: S3"));
+
+        let rd: ResourceDescriptor = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(rd.service, s3::SERVICE);
+    }
+}
@@ -0,0 +1,95 @@
+use iced::{
+    futures::{future::BoxFuture, FutureExt},
+    Element, Length, Task,
+};
+
+use crate::{
+    error::Error,
+    message::Message,
+    models::{
+        resource::Resource,
+        s3_bucket::{list_s3_buckets, load_s3_bucket, BucketInfo},
+        s3_bucket_config::load_bucket_config,
+        service::Service,
+        workspace::ResourceDescriptor,
+    },
+    providers::ResourceProvider,
+    view::{
+        icons::ICONS,
+        resource_details::{s3_bucket_details::S3BucketDetails, DetailsView},
+        resources_table::ColumnKind,
+    },
+};
+
+pub static SERVICE: Service = Service::new_static("S3");
+
+pub struct S3Provider;
+
+impl ResourceProvider for S3Provider {
+    fn service(&self) -> &Service {
+        &SERVICE
+    }
+
+    fn icon(&self, size: f32) -> Element<'static, Message> {
+        ICONS.bucket(size, Length::Fixed(16.0), Length::Fixed(16.0))
+    }
+
+    fn list(
+        &self,
+        profile: String,
+        region: String,
+        all_regions: bool,
+    ) -> BoxFuture<'static, Result<Vec<Resource>, Error>> {
+        async move {
+            let buckets = list_s3_buckets(&profile, &region, &SERVICE, all_regions).await?;
+            Ok(buckets
+                .into_iter()
+                .map(|b| Resource::new(b.descriptor.clone(), b))
+                .collect())
+        }
+        .boxed()
+    }
+
+    /// The loaded resource points at the bucket's own region.
+    fn load(&self, descriptor: ResourceDescriptor) -> BoxFuture<'static, Result<Resource, Error>> {
+        async move {
+            let bucket = load_s3_bucket(&descriptor).await?;
+            Ok(Resource::new(bucket.descriptor.clone(), bucket))
+        }
+        .boxed()
+    }
+
+    fn table_columns(&self) -> Vec<ColumnKind> {
+        vec![
+            ColumnKind::Name,
+            ColumnKind::Profile,
+            ColumnKind::Region,
+            ColumnKind::Custom {
+                header: "URI",
+                width: 320.0,
+                value: |rd| format!("s3://{}", rd.id),
+            },
+        ]
+    }
+
+    fn lists_globally(&self) -> bool {
+        true
+    }
+
+    fn details_view(&self) -> Box<dyn DetailsView> {
+        Box::new(S3BucketDetails::new())
+    }
+
+    fn open(&self, resource: &Resource) -> Task<Message> {
+        let Some(b) = resource.data::<BucketInfo>() else {
+            return Task::none();
+        };
+        let bucket = b.descriptor.clone();
+        Task::batch([
+            Task::done(Message::S3PrefixSelected(bucket.clone(), String::new())),
+            Task::perform(load_bucket_config(bucket.clone()), move |config| {
+                Message::S3BucketConfigLoaded(bucket.clone(), Box::new(config))
+            }),
+        ])
+    }
+}
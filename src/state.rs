@@ -1,43 +1,93 @@
-use crate::{error::Error, models::workspace::Workspace, view::pane_type::PaneType};
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::{
+    error::Error,
+    models::{
+        caller_identity::CallerIdentity,
+        error_report::{ErrorReport, ErrorReportId},
+        transfer::{Transfer, TransferEvent, TransferId},
+        workspace::{settings::ProtectionMode, Workspace},
+    },
+    view::pane_type::PaneType,
+};
 
 #[derive(Clone, Debug)]
 pub struct State {
     pub workspace: Workspace,
-    nearest_region: String,
+    nearest_region: Option<String>,
     side_drawer_open: bool,
     side_drawer_width: f32,
     active_pane: PaneType,
-    //log_sender: Option<iced::futures::channel::mpsc::Sender<String>>,
-    logs: Vec<String>,
+    transfers: Vec<Transfer>,
+    /// Newest last.
+    error_reports: Vec<ErrorReport>,
+    errors_panel_open: bool,
+    /// The workspace as changed on disk by someone else, until the user
+    /// decides whether to reload, merge or keep their own.
+    external_change: Option<Workspace>,
+    /// Whether the layout has changed since the session was last saved.
+    session_changed: bool,
+    caller_identities: HashMap<String, CallerIdentity>,
 }
 
+/// Older reports are dropped beyond this.
+const MAX_ERROR_REPORTS: usize = 100;
+
 impl State {
     pub fn new() -> Self {
         Self {
             workspace: Workspace::default(),
-            nearest_region: String::from("ap-northeast-1"), // TODO: get from config
+            nearest_region: None,
             side_drawer_open: false,
             side_drawer_width: 150.0,
             active_pane: PaneType::Explore,
-            //log_sender: None,
-            logs: Vec::new(),
+            transfers: Vec::new(),
+            error_reports: Vec::new(),
+            errors_panel_open: false,
+            external_change: None,
+            session_changed: false,
+            caller_identities: HashMap::new(),
         }
     }
 
     pub fn from_workspace(ws: Workspace) -> Result<Self, Error> {
         Ok(Self {
             workspace: ws,
-            nearest_region: String::from("ap-northeast-1"), // TODO: get from config
+            nearest_region: None,
             side_drawer_open: false,
             side_drawer_width: 150.0,
             active_pane: PaneType::Explore,
-            //log_sender: None,
-            logs: Vec::new(),
+            transfers: Vec::new(),
+            error_reports: Vec::new(),
+            errors_panel_open: false,
+            external_change: None,
+            session_changed: false,
+            caller_identities: HashMap::new(),
         })
     }
 
-    pub fn get_nearest_region(&self) -> &str {
-        &self.nearest_region
+    /// `None` until the latency measurement has finished.
+    pub fn get_nearest_region(&self) -> Option<&str> {
+        self.nearest_region.as_deref()
+    }
+
+    pub fn set_nearest_region(&mut self, region: Option<String>) {
+        self.nearest_region = region;
+    }
+
+    pub fn get_caller_identity(&self, profile: &str) -> Option<&CallerIdentity> {
+        self.caller_identities.get(profile)
+    }
+
+    pub fn set_caller_identity(&mut self, profile: String, identity: CallerIdentity) {
+        self.caller_identities.insert(profile, identity);
+    }
+
+    pub fn protection_for(&self, profile: &str) -> Option<ProtectionMode> {
+        let account = self
+            .get_caller_identity(profile)
+            .map(|i| i.account.as_str());
+        self.workspace.settings.protection_for(profile, account)
     }
 
     pub fn is_side_drawer_open(&self) -> bool {
@@ -48,6 +98,10 @@ impl State {
         self.side_drawer_open = false;
     }
 
+    pub fn set_side_drawer_open(&mut self, open: bool) {
+        self.side_drawer_open = open;
+    }
+
     pub fn toggle_side_drawer(&mut self) {
         self.side_drawer_open = !self.side_drawer_open;
     }
@@ -67,15 +121,87 @@ impl State {
         self.active_pane = pane_type;
     }
 
-    //pub fn set_log_sender(&mut self, log_sender: Sender<String>) {
-    //    self.log_sender = Some(log_sender);
-    //}
+    pub fn add_transfer(&mut self, transfer: Transfer) {
+        self.transfers.push(transfer);
+    }
+
+    pub fn update_transfer(&mut self, id: &TransferId, event: TransferEvent) {
+        if let Some(t) = self.transfers.iter_mut().find(|t| &t.id == id) {
+            t.apply(event);
+        }
+    }
+
+    pub fn cancel_transfer(&mut self, id: &TransferId) {
+        if let Some(t) = self.transfers.iter().find(|t| &t.id == id) {
+            t.cancel();
+        }
+    }
+
+    pub fn dismiss_transfer(&mut self, id: &TransferId) {
+        self.transfers.retain(|t| &t.id != id || t.is_running());
+    }
+
+    pub fn get_transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+
+    pub fn add_error_report(&mut self, report: ErrorReport) {
+        self.error_reports.push(report);
+        if self.error_reports.len() > MAX_ERROR_REPORTS {
+            self.error_reports.remove(0);
+        }
+    }
+
+    pub fn get_error_reports(&self) -> &[ErrorReport] {
+        &self.error_reports
+    }
+
+    pub fn get_error_report_mut(&mut self, id: &ErrorReportId) -> Option<&mut ErrorReport> {
+        self.error_reports.iter_mut().find(|r| &r.id == id)
+    }
+
+    pub fn take_error_report(&mut self, id: &ErrorReportId) -> Option<ErrorReport> {
+        let index = self.error_reports.iter().position(|r| &r.id == id)?;
+        Some(self.error_reports.remove(index))
+    }
+
+    pub fn clear_error_reports(&mut self) {
+        self.error_reports.clear();
+    }
+
+    pub fn get_visible_toasts(&self, now: SystemTime) -> Vec<&ErrorReport> {
+        self.error_reports
+            .iter()
+            .filter(|r| r.is_toast_visible(now))
+            .collect()
+    }
+
+    pub fn is_errors_panel_open(&self) -> bool {
+        self.errors_panel_open
+    }
+
+    pub fn set_errors_panel_open(&mut self, open: bool) {
+        self.errors_panel_open = open;
+    }
+
+    pub fn mark_session_changed(&mut self) {
+        self.session_changed = true;
+    }
+
+    /// True once after the layout has changed.
+    pub fn take_session_changed(&mut self) -> bool {
+        std::mem::take(&mut self.session_changed)
+    }
+
+    pub fn has_external_change(&self) -> bool {
+        self.external_change.is_some()
+    }
 
-    pub fn append_log(&mut self, log: String) {
-        self.logs.push(log);
+    pub fn set_external_change(&mut self, workspace: Option<Workspace>) {
+        self.external_change = workspace;
     }
 
-    pub fn get_logs(&self) -> Vec<String> {
-        self.logs.clone()
+    pub fn take_external_change(&mut self) -> Option<Workspace> {
+        self.external_change.take()
     }
 }
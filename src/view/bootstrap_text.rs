@@ -5,7 +5,7 @@ use iced::{
 
 // Gallery: https://icons.getbootstrap.com/
 
-pub fn bootstrap_text(t: &str, size: f32, width: iced::Length, height: iced::Length) -> Text {
+pub fn bootstrap_text(t: &str, size: f32, width: iced::Length, height: iced::Length) -> Text<'_> {
     text(t)
         .font(Font::with_name("bootstrap-icons"))
         .size(size)
@@ -0,0 +1,197 @@
+use std::time::SystemTime;
+
+use iced::{
+    alignment::{Horizontal, Vertical},
+    widget::{button, column, container, opaque, row, scrollable, text, Column},
+    Element, Length,
+};
+
+use crate::{
+    error::Remedy,
+    message::Message,
+    models::error_report::ErrorReport,
+    state::State,
+    view::{fonts, icons::ICONS, styles, units::format_time_of_day},
+};
+
+pub struct ErrorsPanel {}
+
+impl ErrorsPanel {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// A toggle with the number of errors and, while open, the errors
+    /// themselves, newest first.
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, Message> {
+        let reports = state.get_error_reports();
+        let open = state.is_errors_panel_open();
+
+        let toggle_icon = match open {
+            true => ICONS.chevron_down(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+            false => ICONS.chevron_right(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+        };
+        let mut header = row![button(
+            row![toggle_icon, text(format!("Errors ({})", reports.len()))]
+                .spacing(4)
+                .align_y(Vertical::Center)
+        )
+        .on_press(Message::ErrorsPanelToggled)
+        .style(styles::tool_button)]
+        .align_y(Vertical::Center);
+        if open && !reports.is_empty() {
+            header = header.push(
+                button(text("Clear"))
+                    .on_press(Message::ErrorsCleared)
+                    .style(styles::tool_button),
+            );
+        }
+
+        let mut c = column![header];
+        if open {
+            let mut list: Column<'_, Message> = column![].spacing(4);
+            for report in reports.iter().rev() {
+                list = list.push(self.render_report(report));
+            }
+            c = c.push(
+                scrollable(list)
+                    .width(Length::Fill)
+                    .height(Length::Fixed(200.0)),
+            );
+        }
+
+        container(c).width(Length::Fill).into()
+    }
+
+    /// Toasts of the errors that have just occurred.
+    pub fn view_toasts<'a>(&'a self, state: &'a State) -> Option<Element<'a, Message>> {
+        let toasts = state.get_visible_toasts(SystemTime::now());
+        if toasts.is_empty() {
+            return None;
+        }
+
+        let mut c = column![].spacing(8).width(Length::Fixed(360.0));
+        for report in toasts.into_iter().rev() {
+            c = c.push(opaque(self.render_toast(report)));
+        }
+        Some(
+            container(c)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(16)
+                .align_x(Horizontal::Right)
+                .align_y(Vertical::Bottom)
+                .into(),
+        )
+    }
+
+    fn render_toast<'a>(&'a self, report: &'a ErrorReport) -> Element<'a, Message> {
+        let close = button(ICONS.x_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+            .on_press(Message::ErrorToastClosed(report.id.clone()))
+            .style(styles::tool_button);
+        let title = report
+            .error
+            .operation()
+            .map(|o| o.to_string())
+            .unwrap_or("Error".to_string());
+
+        container(
+            column![
+                row![
+                    text(title).font(fonts::get_bold_font()).width(Length::Fill),
+                    close
+                ]
+                .align_y(Vertical::Center),
+                text(report.error.friendly_message()),
+                self.render_actions(report),
+            ]
+            .spacing(4),
+        )
+        .padding(8)
+        .width(Length::Fill)
+        .style(styles::toast)
+        .into()
+    }
+
+    fn render_report<'a>(&'a self, report: &'a ErrorReport) -> Element<'a, Message> {
+        let summary = row![
+            text(format_time_of_day(report.time)).width(Length::Fixed(96.0)),
+            text(report.error.operation().unwrap_or("-").to_string()).width(Length::Fixed(160.0)),
+            text(report.location_label().unwrap_or("-".to_string())).width(Length::Fixed(200.0)),
+            text(report.error.friendly_message()).width(Length::Fill),
+            self.render_actions(report),
+            button(ICONS.x_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::ErrorDismissed(report.id.clone()))
+                .style(styles::tool_button),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let mut c = column![summary];
+        if report.expanded {
+            c = c.push(self.render_details(report));
+        }
+        c.into()
+    }
+
+    fn render_actions<'a>(&'a self, report: &'a ErrorReport) -> Element<'a, Message> {
+        let mut actions = row![].spacing(4);
+        if report.retry.is_some() {
+            actions = actions.push(
+                button(text("Retry"))
+                    .on_press(Message::ErrorRetried(report.id.clone()))
+                    .style(styles::tool_button),
+            );
+        }
+        let remedy = match report.remedy() {
+            Some(Remedy::LogIn) if report.profile().is_some() => Some("Log in"),
+            Some(Remedy::ShowMissingAction(_)) => Some("Show missing action"),
+            _ => None,
+        };
+        if let Some(label) = remedy {
+            actions = actions.push(
+                button(text(label))
+                    .on_press(Message::ErrorRemedyRequested(report.id.clone()))
+                    .style(styles::tool_button),
+            );
+        }
+        let details = match report.expanded {
+            true => "Hide details",
+            false => "Details",
+        };
+        actions
+            .push(
+                button(text(details))
+                    .on_press(Message::ErrorDetailsToggled(report.id.clone()))
+                    .style(styles::tool_button),
+            )
+            .into()
+    }
+
+    fn render_details<'a>(&'a self, report: &'a ErrorReport) -> Element<'a, Message> {
+        let raw = format!("{}\n\n{:#?}", report.error, report.error);
+        let mut c = column![].spacing(4).padding([4, 96]);
+        if let Some(action) = report.error.missing_action() {
+            c = c.push(
+                row![
+                    text(format!("Missing permission: {}", action)).font(fonts::get_bold_font()),
+                    button(text("Copy"))
+                        .on_press(Message::CopyToClipboard(action))
+                        .style(styles::tool_button),
+                ]
+                .spacing(8)
+                .align_y(Vertical::Center),
+            );
+        }
+        c.push(
+            row![
+                text(raw.clone()).width(Length::Fill),
+                button(text("Copy"))
+                    .on_press(Message::CopyToClipboard(raw))
+                    .style(styles::tool_button),
+            ]
+            .spacing(8),
+        )
+        .into()
+    }
+}
@@ -1,10 +1,10 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use iced::{
     widget::{
-        column, container,
+        checkbox, column, container,
         pane_grid::{self, PaneGrid},
-        pick_list,
+        pick_list, row, text,
     },
     Element, Length,
 };
@@ -12,12 +12,27 @@ use iced_aw::SelectionList;
 
 use crate::{
     message::Message,
-    models::{profile::load_profile_names, resource::Resource, service::Service},
+    models::{
+        caller_identity::CallerIdentity,
+        profile::{load_profiles, Profile},
+        resource::Resource,
+        service::Service,
+        workspace::Project,
+    },
+    providers::registry,
     view::{
-        easing, fonts::get_default_font, linear::Linear, resource_details::ResourceDetails, styles,
+        easing,
+        fonts::{self, get_default_font},
+        linear::Linear,
+        resource_details::ResourceDetails,
+        styles,
+        units::format_remaining,
     },
 };
 
+/// Keeps restored panes from collapsing.
+const MIN_SPLIT_RATIO: f32 = 0.05;
+
 #[derive(Clone, Debug)]
 struct Pane {
     id: PaneId,
@@ -46,44 +61,31 @@ impl Pane {
     }
 }
 
-const SERVICES: &[&Service] = &[&Service::Lambda, &Service::S3];
-
 pub struct ExploreTab {
     panes: pane_grid::State<Pane>,
-    profiles: Vec<String>,
+    profiles: Vec<Profile>,
     selected_profile: Option<String>,
+    caller_identity: Option<CallerIdentity>,
+    /// When the MFA session of the selected profile expires.
+    session_expiry: Option<SystemTime>,
     regions: Vec<String>,
     selected_region: Option<String>,
     services: Vec<&'static Service>,
     selected_service: Option<&'static Service>,
+    all_regions: bool,
     loading_resources: bool,
     resources: Vec<Resource>,
     //selected_resource: Option<Resource>,
     resource_details: ResourceDetails,
+    /// The projects the selected resource can be added to.
+    projects: Vec<Project>,
 }
 
 impl ExploreTab {
     pub fn new() -> Self {
-        let config = pane_grid::Configuration::Split {
-            axis: pane_grid::Axis::Vertical,
-            ratio: 0.15,
-            a: Box::new(pane_grid::Configuration::Pane(Pane::new(
-                PaneId::ServiceSelector,
-            ))),
-            b: Box::new(pane_grid::Configuration::Split {
-                axis: pane_grid::Axis::Vertical,
-                ratio: 0.2,
-                a: Box::new(pane_grid::Configuration::Pane(Pane::new(
-                    PaneId::ResourceSelector,
-                ))),
-                b: Box::new(pane_grid::Configuration::Pane(Pane::new(
-                    PaneId::ResourceDetails,
-                ))),
-            }),
-        };
-        let panes = pane_grid::State::with_configuration(config);
-        let profiles = load_profile_names().unwrap_or_else(|err| {
-            eprintln!("Failed to load profiles: {:?}", err);
+        let panes = pane_grid::State::with_configuration(Self::configuration(&[]));
+        let profiles = load_profiles().unwrap_or_else(|err| {
+            tracing::error!(error = ?err, "Failed to load profiles");
             vec![]
         });
 
@@ -91,21 +93,89 @@ impl ExploreTab {
             panes,
             profiles,
             selected_profile: None,
+            caller_identity: None,
+            session_expiry: None,
             regions: vec![],
             selected_region: None,
-            services: SERVICES.to_vec(),
+            services: registry().services(),
             selected_service: None,
+            all_regions: false,
             loading_resources: false,
             resources: vec![],
             //selected_resource: None,
             resource_details: ResourceDetails::new(),
+            projects: vec![],
+        }
+    }
+
+    /// The default ratios are used for those missing in `ratios`.
+    fn configuration(ratios: &[f32]) -> pane_grid::Configuration<Pane> {
+        let ratio = |i: usize, default: f32| {
+            ratios
+                .get(i)
+                .map_or(default, |r| r.clamp(MIN_SPLIT_RATIO, 1.0 - MIN_SPLIT_RATIO))
+        };
+        pane_grid::Configuration::Split {
+            axis: pane_grid::Axis::Vertical,
+            ratio: ratio(0, 0.15),
+            a: Box::new(pane_grid::Configuration::Pane(Pane::new(
+                PaneId::ServiceSelector,
+            ))),
+            b: Box::new(pane_grid::Configuration::Split {
+                axis: pane_grid::Axis::Vertical,
+                ratio: ratio(1, 0.2),
+                a: Box::new(pane_grid::Configuration::Pane(Pane::new(
+                    PaneId::ResourceSelector,
+                ))),
+                b: Box::new(pane_grid::Configuration::Pane(Pane::new(
+                    PaneId::ResourceDetails,
+                ))),
+            }),
+        }
+    }
+
+    /// Ratios of the splits, outermost first.
+    pub fn get_split_ratios(&self) -> Vec<f32> {
+        fn collect(node: &pane_grid::Node, ratios: &mut Vec<f32>) {
+            if let pane_grid::Node::Split { ratio, a, b, .. } = node {
+                ratios.push(*ratio);
+                collect(a, ratios);
+                collect(b, ratios);
+            }
         }
+        let mut ratios = vec![];
+        collect(self.panes.layout(), &mut ratios);
+        ratios
+    }
+
+    pub fn set_split_ratios(&mut self, ratios: &[f32]) {
+        self.panes = pane_grid::State::with_configuration(Self::configuration(ratios));
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        self.get_profile(name).is_some()
+    }
+
+    pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
     }
 
     pub fn set_selected_profile(&mut self, profile: String) {
         self.selected_profile = Some(profile);
     }
 
+    pub fn set_caller_identity(&mut self, identity: Option<CallerIdentity>) {
+        self.caller_identity = identity;
+    }
+
+    pub fn set_session_expiry(&mut self, expiry: Option<SystemTime>) {
+        self.session_expiry = expiry;
+    }
+
+    pub fn get_session_expiry(&self) -> Option<SystemTime> {
+        self.session_expiry
+    }
+
     pub fn get_selected_profile(&self) -> Option<String> {
         self.selected_profile.clone()
     }
@@ -126,6 +196,18 @@ impl ExploreTab {
         self.selected_service = Some(service);
     }
 
+    pub fn get_selected_service(&self) -> Option<&'static Service> {
+        self.selected_service
+    }
+
+    pub fn set_all_regions(&mut self, all_regions: bool) {
+        self.all_regions = all_regions;
+    }
+
+    pub fn is_all_regions(&self) -> bool {
+        self.all_regions
+    }
+
     pub fn set_loading_resources(&mut self, loading: bool) {
         self.loading_resources = loading;
     }
@@ -138,11 +220,23 @@ impl ExploreTab {
         self.resource_details.set_resource(Some(resource));
     }
 
+    pub fn set_projects(&mut self, projects: Vec<Project>) {
+        self.projects = projects;
+    }
+
+    pub fn get_resource_details(&self) -> &ResourceDetails {
+        &self.resource_details
+    }
+
+    pub fn get_resource_details_mut(&mut self) -> &mut ResourceDetails {
+        &mut self.resource_details
+    }
+
     pub fn resize_pane(&mut self, event: iced::widget::pane_grid::ResizeEvent) {
         self.panes.resize(event.split, event.ratio);
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let pane_grid = PaneGrid::new(&self.panes, |_pane_number, pane, _is_maximized| {
             pane_grid::Content::new(self.view_content(pane)).style(styles::pane_active)
         })
@@ -157,26 +251,74 @@ impl ExploreTab {
             .into()
     }
 
-    fn view_content(&self, pane: &Pane) -> Element<Message> {
+    fn view_content(&self, pane: &Pane) -> Element<'_, Message> {
         match pane.id {
             PaneId::ServiceSelector => self.render_service_selector(),
             PaneId::ResourceSelector => self.render_resource_selector(),
-            PaneId::ResourceDetails => self.resource_details.view(),
+            PaneId::ResourceDetails => self.render_resource_details(),
+        }
+    }
+
+    fn render_resource_details(&self) -> Element<'_, Message> {
+        let Some(resource) = self.resource_details.get_resource() else {
+            return self.resource_details.view();
+        };
+        if self.projects.is_empty() {
+            return self.resource_details.view();
         }
+
+        let descriptor = resource.descriptor.clone();
+        let add_to_project = pick_list(self.projects.clone(), None::<Project>, move |p| {
+            Message::AddToProject(p.get_id().to_string(), descriptor.clone())
+        })
+        .placeholder("Add to project…");
+        column![
+            row![add_to_project].padding([4, 0]),
+            self.resource_details.view()
+        ]
+        .into()
     }
 
-    fn render_service_selector(&self) -> Element<Message> {
+    fn render_service_selector(&self) -> Element<'_, Message> {
         let profile_selector = pick_list(
-            self.profiles.clone(),
+            self.profiles
+                .iter()
+                .map(|p| p.name.clone())
+                .collect::<Vec<_>>(),
             self.selected_profile.clone(),
             Message::ProfileSelected,
         )
         .placeholder("Profile")
         .width(Length::Fill);
-        let mut c = column![profile_selector]
+        let mut profile_row = row![profile_selector]
+            .spacing(4)
+            .align_y(iced::alignment::Vertical::Center);
+        if let Some(expiry) = self.session_expiry {
+            profile_row = profile_row.push(match expiry.duration_since(SystemTime::now()) {
+                Ok(remaining) => {
+                    text(format!("MFA {}", format_remaining(remaining))).style(text::secondary)
+                }
+                Err(_) => text("MFA expired").style(text::danger),
+            });
+        }
+        let mut c = column![profile_row]
             .width(iced::Length::Fill)
             .height(Length::Fill);
 
+        if let Some(identity) = &self.caller_identity {
+            let account = match &identity.account_alias {
+                Some(alias) => format!("{} ({})", alias, identity.account),
+                None => identity.account.clone(),
+            };
+            c = c.push(
+                column![
+                    text(account).font(fonts::get_bold_font()),
+                    text(identity.arn.clone()).style(text::secondary),
+                ]
+                .padding([4, 0]),
+            );
+        }
+
         if self.selected_profile.is_some() {
             let region_selector = pick_list(
                 self.regions.clone(),
@@ -203,13 +345,23 @@ impl ExploreTab {
             .height(Length::Fill);
             c = c.push(service_selector);
         }
+
+        let lists_globally = self
+            .selected_service
+            .and_then(|s| registry().get(s))
+            .is_some_and(|p| p.lists_globally());
+        if lists_globally {
+            c = c.push(
+                checkbox("In all regions", self.all_regions).on_toggle(Message::AllRegionsToggled),
+            );
+        }
         container(c)
             .width(iced::Length::Fill)
             .height(iced::Length::Fill)
             .into()
     }
 
-    fn render_resource_selector(&self) -> Element<Message> {
+    fn render_resource_selector(&self) -> Element<'_, Message> {
         if self.loading_resources {
             return container(
                 Linear::new()
@@ -1,38 +1,87 @@
 use iced::{
-    widget::{column, container, scrollable, text, Column},
-    Element,
+    widget::{button, column, container, progress_bar, row, text},
+    Element, Length,
 };
 
-use crate::{message::Message, state::State};
+use crate::{
+    message::Message,
+    models::transfer::{Transfer, TransferStatus},
+    state::State,
+    view::{
+        errors_panel::ErrorsPanel, icons::ICONS, log_console::LogConsole, styles,
+        units::format_bytes,
+    },
+};
 
-pub struct Footer {}
+pub struct Footer {
+    pub errors_panel: ErrorsPanel,
+    pub log_console: LogConsole,
+}
 
 impl Footer {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            errors_panel: ErrorsPanel::new(),
+            log_console: LogConsole::new(),
+        }
     }
 
-    pub fn view(&self, state: &State) -> Element<Message> {
-        let mut logs: Column<'_, Message> = column![].align_x(iced::alignment::Alignment::Start);
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, Message> {
+        let mut c = column![];
 
-        for log in state.get_logs().iter() {
-            logs = logs.push(text(log.to_string()));
+        for transfer in state.get_transfers() {
+            c = c.push(self.render_transfer(transfer));
         }
 
-        let s = scrollable(logs)
-            .direction(scrollable::Direction::Vertical(
-                scrollable::Scrollbar::new()
-                    .width(15)
-                    .margin(5)
-                    .scroller_width(10)
-                    .anchor(scrollable::Anchor::Start),
-            ))
-            .width(iced::Length::Fill)
-            .height(iced::Length::Fixed(100.0));
+        c = c.push(self.errors_panel.view(state));
+
+        c = c.push(self.log_console.view());
 
-        container(s)
+        container(c)
             .width(iced::Length::Fill)
             .height(iced::Length::Shrink)
             .into()
     }
+
+    fn render_transfer<'a>(&'a self, transfer: &'a Transfer) -> Element<'a, Message> {
+        let total = transfer.total.unwrap_or(0);
+        let progress = match total {
+            0 => 0.0,
+            total => transfer.transferred as f32 / total as f32,
+        };
+
+        let status = match &transfer.status {
+            TransferStatus::Running => format!(
+                "{} / {}",
+                format_bytes(transfer.transferred),
+                transfer.total.map(format_bytes).unwrap_or("?".to_string())
+            ),
+            TransferStatus::Completed => "Completed".to_string(),
+            TransferStatus::Cancelled => "Cancelled".to_string(),
+            TransferStatus::Failed(e) => format!("Failed: {}", e),
+        };
+
+        let action = if transfer.is_running() {
+            button(text("Cancel"))
+                .on_press(Message::TransferCancelRequested(transfer.id.clone()))
+                .style(styles::tool_button)
+        } else {
+            button(ICONS.x_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::TransferDismissed(transfer.id.clone()))
+                .style(styles::tool_button)
+        };
+
+        row![
+            text(transfer.kind.to_string()).width(Length::Fixed(64.0)),
+            text(transfer.label.clone()).width(Length::Fill),
+            progress_bar(0.0..=1.0, progress)
+                .width(Length::Fixed(160.0))
+                .height(Length::Fixed(12.0)),
+            text(status).width(Length::Fixed(200.0)),
+            action,
+        ]
+        .spacing(8)
+        .align_y(iced::alignment::Vertical::Center)
+        .into()
+    }
 }
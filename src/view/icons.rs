@@ -14,12 +14,57 @@ impl Icons {
         Self {}
     }
 
+    pub fn arrow_clockwise(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F116}", size, width, height).into()
+    }
+
+    pub fn arrow_down(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F128}", size, width, height).into()
+    }
+
+    pub fn arrow_up(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F148}", size, width, height).into()
+    }
+
+    pub fn bucket(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F1DA}", size, width, height).into()
+    }
+
+    pub fn check_lg(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F633}", size, width, height).into()
+    }
+
     pub fn chevron_down(
         &self,
         size: f32,
         width: iced::Length,
         height: iced::Length,
-    ) -> Element<Message> {
+    ) -> Element<'_, Message> {
         bootstrap_text("\u{F282}", size, width, height).into()
     }
 
@@ -28,7 +73,7 @@ impl Icons {
         size: f32,
         width: iced::Length,
         height: iced::Length,
-    ) -> Element<Message> {
+    ) -> Element<'_, Message> {
         bootstrap_text("\u{F285}", size, width, height).into()
     }
 
@@ -37,20 +82,97 @@ impl Icons {
         size: f32,
         width: iced::Length,
         height: iced::Length,
-    ) -> Element<Message> {
+    ) -> Element<'_, Message> {
         bootstrap_text("\u{F2D1}", size, width, height).into()
     }
 
+    pub fn download(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F30A}", size, width, height).into()
+    }
+
+    pub fn file_earmark(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F392}", size, width, height).into()
+    }
+
+    pub fn folder(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F3D7}", size, width, height).into()
+    }
+
     pub fn folder_plus(
         &self,
         size: f32,
         width: iced::Length,
         height: iced::Length,
-    ) -> Element<Message> {
+    ) -> Element<'_, Message> {
         bootstrap_text("\u{F3D3}", size, width, height).into()
     }
 
-    pub fn list(&self, size: f32, width: iced::Length, height: iced::Length) -> Element<Message> {
+    pub fn lightning(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F46F}", size, width, height).into()
+    }
+
+    pub fn list(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
         bootstrap_text("\u{F479}", size, width, height).into()
     }
+
+    pub fn pencil(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F4CB}", size, width, height).into()
+    }
+
+    pub fn trash(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F5DE}", size, width, height).into()
+    }
+
+    pub fn upload(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F603}", size, width, height).into()
+    }
+
+    pub fn x_lg(
+        &self,
+        size: f32,
+        width: iced::Length,
+        height: iced::Length,
+    ) -> Element<'_, Message> {
+        bootstrap_text("\u{F659}", size, width, height).into()
+    }
 }
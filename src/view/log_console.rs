@@ -0,0 +1,204 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, checkbox, column, container, pick_list, row, scrollable, text, text_input, tooltip,
+        Column,
+    },
+    Element, Length, Task,
+};
+use once_cell::sync::Lazy;
+use tracing::Level;
+
+use crate::{
+    message::Message,
+    models::log_entry::{LogEntry, LogFilter},
+    view::{styles, units::format_time_of_day},
+};
+
+/// Older entries are dropped beyond this.
+const MAX_LOG_ENTRIES: usize = 5000;
+/// Only the newest matching entries are rendered.
+const MAX_SHOWN_ENTRIES: usize = 500;
+
+/// Nothing more verbose than DEBUG is recorded.
+const LEVELS: [Level; 4] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+static SCROLLABLE_ID: Lazy<scrollable::Id> = Lazy::new(scrollable::Id::unique);
+
+pub struct LogConsole {
+    /// Newest last.
+    entries: VecDeque<LogEntry>,
+    /// Received while paused, shown once resumed.
+    held_back: Vec<LogEntry>,
+    filter: LogFilter,
+    paused: bool,
+    auto_scroll: bool,
+    /// Where the logs are mirrored to, if anywhere.
+    log_file: Option<PathBuf>,
+}
+
+impl LogConsole {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            held_back: Vec::new(),
+            filter: LogFilter::default(),
+            paused: false,
+            auto_scroll: true,
+            log_file: None,
+        }
+    }
+
+    /// Scrolls to the newest entry if one of `entries` is shown and the
+    /// console follows the logs.
+    pub fn push(&mut self, entries: Vec<LogEntry>) -> Task<Message> {
+        if self.paused {
+            self.held_back.extend(entries);
+            let excess = self.held_back.len().saturating_sub(MAX_LOG_ENTRIES);
+            self.held_back.drain(..excess);
+            return Task::none();
+        }
+        let shown = entries.iter().any(|e| self.filter.matches(e));
+        self.entries.extend(entries);
+        while self.entries.len() > MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.scroll_to_end_if(shown)
+    }
+
+    pub fn set_level(&mut self, level: Level) {
+        self.filter.level = level;
+    }
+
+    pub fn set_modules(&mut self, modules: String) {
+        self.filter.modules = modules;
+    }
+
+    pub fn set_search(&mut self, search: String) {
+        self.filter.search = search;
+    }
+
+    pub fn toggle_paused(&mut self) -> Task<Message> {
+        self.paused = !self.paused;
+        if self.paused {
+            return Task::none();
+        }
+        let held_back = std::mem::take(&mut self.held_back);
+        self.push(held_back)
+    }
+
+    pub fn set_auto_scroll(&mut self, auto_scroll: bool) -> Task<Message> {
+        self.auto_scroll = auto_scroll;
+        self.scroll_to_end_if(true)
+    }
+
+    pub fn set_log_file(&mut self, log_file: Option<PathBuf>) {
+        self.log_file = log_file;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.held_back.clear();
+    }
+
+    fn scroll_to_end_if(&self, condition: bool) -> Task<Message> {
+        match condition && self.auto_scroll {
+            true => scrollable::snap_to(SCROLLABLE_ID.clone(), scrollable::RelativeOffset::END),
+            false => Task::none(),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut list: Column<'_, Message> = column![];
+        let matching: Vec<_> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| self.filter.matches(e))
+            .take(MAX_SHOWN_ENTRIES)
+            .collect();
+        for entry in matching.into_iter().rev() {
+            list = list.push(self.render_entry(entry));
+        }
+
+        let s = scrollable(list)
+            .id(SCROLLABLE_ID.clone())
+            .direction(scrollable::Direction::Vertical(
+                scrollable::Scrollbar::new()
+                    .width(15)
+                    .margin(5)
+                    .scroller_width(10),
+            ))
+            .width(Length::Fill)
+            .height(Length::Fixed(100.0));
+
+        column![self.render_toolbar(), s].into()
+    }
+
+    fn render_toolbar(&self) -> Element<'_, Message> {
+        let pause = match self.paused {
+            true if !self.held_back.is_empty() => format!("Resume ({} new)", self.held_back.len()),
+            true => "Resume".to_string(),
+            false => "Pause".to_string(),
+        };
+        let log_file =
+            checkbox("Write to file", self.log_file.is_some()).on_toggle(Message::LogFileToggled);
+        let log_file: Element<'_, Message> = match &self.log_file {
+            Some(path) => tooltip(
+                log_file,
+                container(text(path.display().to_string()))
+                    .padding(4)
+                    .style(styles::toast),
+                tooltip::Position::Top,
+            )
+            .into(),
+            None => log_file.into(),
+        };
+
+        row![
+            text("Logs"),
+            pick_list(
+                LEVELS,
+                Some(self.filter.level),
+                Message::LogLevelFilterChanged
+            ),
+            text_input("Modules, e.g. awsome, -aws_config", &self.filter.modules)
+                .on_input(Message::LogModuleFilterChanged)
+                .width(Length::Fixed(240.0)),
+            text_input("Search", &self.filter.search)
+                .on_input(Message::LogSearchChanged)
+                .width(Length::Fixed(240.0)),
+            button(text(pause))
+                .on_press(Message::LogsPauseToggled)
+                .style(styles::tool_button),
+            checkbox("Auto-scroll", self.auto_scroll).on_toggle(Message::LogsAutoScrollToggled),
+            log_file,
+            button(text("Clear"))
+                .on_press(Message::LogsCleared)
+                .style(styles::tool_button),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    fn render_entry<'a>(&'a self, entry: &'a LogEntry) -> Element<'a, Message> {
+        let level = text(entry.level.to_string()).width(Length::Fixed(48.0));
+        let level = match entry.level {
+            Level::ERROR => level.style(text::danger),
+            Level::WARN => level.style(text::primary),
+            Level::INFO => level,
+            _ => level.style(text::secondary),
+        };
+        row![
+            text(format_time_of_day(entry.time)).width(Length::Fixed(96.0)),
+            level,
+            text(entry.target.clone()).width(Length::Fixed(200.0)),
+            text(entry.text()).width(Length::Fill),
+        ]
+        .spacing(8)
+        .into()
+    }
+}
@@ -1,15 +1,17 @@
 use iced::{
     padding,
-    widget::{button, column, container, row, text, Space},
+    widget::{button, center, column, container, opaque, row, stack, text, Space},
     Element, Length, Theme,
 };
 
 use crate::{
     message::Message,
+    models::{sso::SsoDeviceAuthorization, workspace::settings::ProtectionMode},
     state::State,
     view::{
-        explore_tab::ExploreTab, footer::Footer, icons::ICONS, pane_type::PaneType,
-        projects_tab::ProjectsTab, styles,
+        explore_tab::ExploreTab, footer::Footer, icons::ICONS, mfa_prompt::MfaPrompt,
+        pane_type::PaneType, projects_tab::ProjectsTab, styles,
+        workspace_switcher::WorkspaceSwitcher,
     },
 };
 
@@ -17,6 +19,9 @@ pub struct MainTab {
     pub explore_tab: ExploreTab,
     pub projects_tab: ProjectsTab,
     pub footer: Footer,
+    pub workspace_switcher: WorkspaceSwitcher,
+    sso_login: Option<SsoDeviceAuthorization>,
+    mfa_prompt: Option<MfaPrompt>,
 }
 
 impl MainTab {
@@ -25,9 +30,28 @@ impl MainTab {
             explore_tab: ExploreTab::new(),
             projects_tab: ProjectsTab::new(),
             footer: Footer::new(),
+            workspace_switcher: WorkspaceSwitcher::new(),
+            sso_login: None,
+            mfa_prompt: None,
         }
     }
 
+    pub fn get_mfa_prompt_mut(&mut self) -> Option<&mut MfaPrompt> {
+        self.mfa_prompt.as_mut()
+    }
+
+    pub fn set_mfa_prompt(&mut self, prompt: Option<MfaPrompt>) {
+        self.mfa_prompt = prompt;
+    }
+
+    pub fn take_mfa_prompt(&mut self) -> Option<MfaPrompt> {
+        self.mfa_prompt.take()
+    }
+
+    pub fn set_sso_login(&mut self, sso_login: Option<SsoDeviceAuthorization>) {
+        self.sso_login = sso_login;
+    }
+
     pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, Message> {
         let r = row![
             self.render_side_drawer(state),
@@ -35,28 +59,171 @@ impl MainTab {
         ]
         .padding(padding::top(4));
 
-        let c = column![self.render_header(state), r, self.footer.view(state)];
+        let mut c = column![self.render_header(state)];
+        if let Some(banner) = self.render_protection_banner(state) {
+            c = c.push(banner);
+        }
+        if state.has_external_change() {
+            c = c.push(self.render_external_change(state));
+        }
+        if let Some(sso_login) = &self.sso_login {
+            c = c.push(self.render_sso_login(sso_login));
+        }
+        let c = c.push(r).push(self.footer.view(state));
 
-        let cont: Element<Message> = container(c).into();
+        let mut layers = stack![container(c)];
+        if let Some(toasts) = self.footer.errors_panel.view_toasts(state) {
+            layers = layers.push(toasts);
+        }
+        if let Some(prompt) = &self.mfa_prompt {
+            layers = layers.push(opaque(center(prompt.view()).style(styles::modal_backdrop)));
+        }
+        let cont: Element<Message> = layers.into();
         //let cont = cont.explain(Color::from_rgb(255.0, 0.0, 0.0));
         cont
     }
 
-    fn render_header(&self, state: &State) -> Element<Message> {
+    fn render_header(&self, state: &State) -> Element<'_, Message> {
         container(
             row![
                 self.render_menu_button(),
                 self.render_spacer(),
-                self.render_current_active_pane_name(state)
+                self.render_current_active_pane_name(state),
+                Space::with_width(Length::Fill),
+                self.workspace_switcher.view(state.workspace.get_path()),
             ]
+            .padding(padding::right(4))
+            .align_y(iced::alignment::Vertical::Center)
             .width(Length::Fill)
             .height(Length::Shrink),
         )
-        .style(styles::header)
+        .style(styles::header(
+            self.active_protection(state)
+                .is_some_and(|(_, mode)| mode != ProtectionMode::Unverified),
+        ))
+        .into()
+    }
+
+    /// The profile whose resources the active pane is showing.
+    fn active_profile(&self, state: &State) -> Option<String> {
+        match state.get_active_pane() {
+            PaneType::Explore => self.explore_tab.get_selected_profile(),
+            PaneType::Projects => state
+                .workspace
+                .appearance
+                .selected_resource
+                .as_ref()
+                .map(|r| r.profile.clone()),
+        }
+    }
+
+    fn active_protection(&self, state: &State) -> Option<(String, ProtectionMode)> {
+        let profile = self.active_profile(state)?;
+        let mode = state.protection_for(&profile)?;
+        Some((profile, mode))
+    }
+
+    fn render_protection_banner(&self, state: &State) -> Option<Element<'_, Message>> {
+        let (profile, mode) = self.active_protection(state)?;
+        if mode == ProtectionMode::Unverified {
+            return Some(
+                container(text(format!(
+                    "Checking the account of profile {}: {}",
+                    profile, mode
+                )))
+                .width(Length::Fill)
+                .padding(4)
+                .style(styles::pane_active)
+                .into(),
+            );
+        }
+        let account = match state.get_caller_identity(&profile) {
+            Some(identity) => format!(" (account {})", identity.account_label()),
+            None => String::new(),
+        };
+        Some(
+            container(text(format!(
+                "Protected profile {}{}: {}",
+                profile, account, mode
+            )))
+            .width(Length::Fill)
+            .padding(4)
+            .style(styles::protection_banner)
+            .into(),
+        )
+    }
+
+    fn render_external_change(&self, state: &State) -> Element<'_, Message> {
+        let name = state
+            .workspace
+            .get_path()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        container(
+            row![
+                text(format!(
+                    "The workspace file {} has been changed outside the app.",
+                    name
+                ))
+                .width(Length::Fill),
+                button(text("Reload"))
+                    .on_press(Message::WorkspaceReloadRequested)
+                    .style(styles::tool_button),
+                button(text("Merge"))
+                    .on_press(Message::WorkspaceMergeRequested)
+                    .style(styles::tool_button),
+                button(text("Keep mine"))
+                    .on_press(Message::WorkspaceExternalChangeDismissed)
+                    .style(styles::tool_button),
+            ]
+            .spacing(4)
+            .align_y(iced::alignment::Vertical::Center),
+        )
+        .width(Length::Fill)
+        .padding(4)
+        .style(styles::pane_active)
+        .into()
+    }
+
+    fn render_sso_login<'a>(
+        &'a self,
+        sso_login: &'a SsoDeviceAuthorization,
+    ) -> Element<'a, Message> {
+        let url = sso_login
+            .verification_uri_complete
+            .as_ref()
+            .unwrap_or(&sso_login.verification_uri);
+        container(
+            row![
+                column![
+                    text(format!(
+                        "Sign in to use the profile {}: open {} and enter the code",
+                        sso_login.login.profile, sso_login.verification_uri
+                    )),
+                    text(&sso_login.user_code).size(20),
+                ]
+                .width(Length::Fill),
+                button(text("Copy URL"))
+                    .on_press(Message::CopyToClipboard(url.clone()))
+                    .style(styles::tool_button),
+                button(text("Copy code"))
+                    .on_press(Message::CopyToClipboard(sso_login.user_code.clone()))
+                    .style(styles::tool_button),
+                button(text("Cancel"))
+                    .on_press(Message::SsoLoginCancelled)
+                    .style(styles::tool_button),
+            ]
+            .spacing(4)
+            .align_y(iced::alignment::Vertical::Center),
+        )
+        .width(Length::Fill)
+        .padding(4)
+        .style(styles::pane_active)
         .into()
     }
 
-    fn render_menu_button(&self) -> Element<Message> {
+    fn render_menu_button(&self) -> Element<'_, Message> {
         let icon = ICONS.list(24.0, Length::Fixed(32.0), Length::Fixed(32.0));
         button(icon)
             .width(Length::Shrink)
@@ -66,11 +233,11 @@ impl MainTab {
             .into()
     }
 
-    fn render_spacer(&self) -> Element<Message> {
+    fn render_spacer(&self) -> Element<'_, Message> {
         container(Space::new(8.0, 8.0)).into()
     }
 
-    fn render_current_active_pane_name(&self, state: &State) -> Element<Message> {
+    fn render_current_active_pane_name(&self, state: &State) -> Element<'_, Message> {
         text(match state.get_active_pane() {
             PaneType::Explore => "Explore",
             PaneType::Projects => "Projects",
@@ -80,7 +247,7 @@ impl MainTab {
         .into()
     }
 
-    fn render_side_drawer(&self, state: &State) -> Element<Message> {
+    fn render_side_drawer(&self, state: &State) -> Element<'_, Message> {
         container(column![
             self.render_explore_button(state),
             self.render_projects_button(state)
@@ -94,7 +261,7 @@ impl MainTab {
         .into()
     }
 
-    fn render_explore_button(&self, state: &State) -> Element<Message> {
+    fn render_explore_button(&self, state: &State) -> Element<'_, Message> {
         let icon = ICONS.compass(24.0, Length::Fixed(32.0), Length::Fixed(32.0));
         let button_content = match state.is_side_drawer_open() {
             true => row![
@@ -116,7 +283,7 @@ impl MainTab {
             .into()
     }
 
-    fn render_projects_button(&self, state: &State) -> Element<Message> {
+    fn render_projects_button(&self, state: &State) -> Element<'_, Message> {
         let icon = ICONS.list(24.0, Length::Fixed(32.0), Length::Fixed(32.0));
         let button_content = match state.is_side_drawer_open() {
             true => row![
@@ -0,0 +1,101 @@
+use iced::{
+    widget::{button, column, container, row, text, text_input},
+    Element, Length,
+};
+
+use crate::{message::Message, models::mfa::MfaChallenge, view::styles};
+
+/// Asks for the TOTP code of an assume-role profile that requires MFA.
+pub struct MfaPrompt {
+    challenge: MfaChallenge,
+    code: String,
+    submitting: bool,
+    error: Option<String>,
+    /// Sent again once the session has started.
+    retry: Message,
+}
+
+impl MfaPrompt {
+    pub fn new(challenge: MfaChallenge, retry: Message) -> Self {
+        Self {
+            challenge,
+            code: String::new(),
+            submitting: false,
+            error: None,
+            retry,
+        }
+    }
+
+    pub fn get_profile(&self) -> &str {
+        &self.challenge.profile
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn set_code(&mut self, code: String) {
+        self.code = code;
+    }
+
+    pub fn set_submitting(&mut self, submitting: bool) {
+        self.submitting = submitting;
+        if submitting {
+            self.error = None;
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.submitting = false;
+        self.error = Some(error);
+    }
+
+    pub fn into_retry(self) -> Message {
+        self.retry
+    }
+
+    fn is_code_valid(&self) -> bool {
+        let code = self.code.trim();
+        code.len() == 6 && code.chars().all(|c| c.is_ascii_digit())
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let submit =
+            (self.is_code_valid() && !self.submitting).then_some(Message::MfaCodeSubmitted);
+
+        let mut c = column![
+            text(format!("MFA code for {}", self.challenge.profile)).size(16),
+            text(&self.challenge.mfa_serial).style(text::secondary),
+            text_input("123456", &self.code)
+                .on_input(Message::MfaCodeChanged)
+                .on_submit_maybe(submit.clone()),
+        ]
+        .spacing(8);
+
+        if let Some(error) = &self.error {
+            c = c.push(text(error).style(text::danger));
+        }
+
+        c = c.push(
+            row![
+                button(text(if self.submitting {
+                    "Verifying..."
+                } else {
+                    "OK"
+                }))
+                .on_press_maybe(submit)
+                .style(styles::tool_button),
+                button(text("Cancel"))
+                    .on_press(Message::MfaPromptCancelled)
+                    .style(styles::tool_button),
+            ]
+            .spacing(4),
+        );
+
+        container(c)
+            .width(Length::Fixed(320.0))
+            .padding(16)
+            .style(styles::pane_active)
+            .into()
+    }
+}
@@ -1,14 +1,19 @@
 pub mod explore_tab;
+pub mod icons;
 pub mod main_tab;
+pub mod mfa_prompt;
 pub mod pane_type;
 pub mod projects_tab;
 pub mod resource_details;
+pub mod resources_table;
+pub mod workspace_switcher;
 
 mod bootstrap_text;
 mod easing;
+mod errors_panel;
 mod fonts;
 mod footer;
-mod icons;
 mod linear;
-mod resources_table;
+mod log_console;
 mod styles;
+mod units;
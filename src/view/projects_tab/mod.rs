@@ -1,7 +1,12 @@
 mod project_service_selector;
 
+use std::collections::BTreeMap;
+
 use iced::{
-    widget::{container, pane_grid, scrollable::AbsoluteOffset, PaneGrid},
+    alignment::Vertical,
+    widget::{
+        column, container, pane_grid, pick_list, row, scrollable::AbsoluteOffset, text, PaneGrid,
+    },
     Element, Length, Task,
 };
 
@@ -48,6 +53,8 @@ pub struct ProjectsTab {
     pub project_service_selector: ProjectServiceSelector,
     pub resources_table: ResourcesTable,
     pub resource_details: ResourceDetails,
+    /// The environment shown of each project, by project ID.
+    selected_environments: BTreeMap<String, String>,
 }
 
 impl ProjectsTab {
@@ -77,6 +84,7 @@ impl ProjectsTab {
             project_service_selector: ProjectServiceSelector::new(),
             resources_table: ResourcesTable::new(),
             resource_details: ResourceDetails::new(),
+            selected_environments: BTreeMap::new(),
         }
     }
 
@@ -85,11 +93,15 @@ impl ProjectsTab {
         self.project_service_selector.set_projects(&projects);
     }
 
+    pub fn set_selected_environments(&mut self, selected_environments: BTreeMap<String, String>) {
+        self.selected_environments = selected_environments;
+    }
+
     pub fn sync_resources_table_header_offset(&mut self, offset: AbsoluteOffset) -> Task<Message> {
         self.resources_table.sync_header_offset(offset)
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let pane_grid = PaneGrid::new(&self.panes, |_pane_number, pane, _is_maximized| {
             pane_grid::Content::new(self.view_content(pane)).style(styles::pane_active)
         })
@@ -104,11 +116,44 @@ impl ProjectsTab {
             .into()
     }
 
-    fn view_content(&self, pane: &Pane) -> Element<Message> {
+    fn view_content(&self, pane: &Pane) -> Element<'_, Message> {
         match pane.id {
             PaneId::ProjectServiceSelector => self.project_service_selector.view(),
-            PaneId::ResourceTable => self.resources_table.view(),
+            PaneId::ResourceTable => self.render_resources_table(),
             PaneId::ResourceDetails => self.resource_details.view(),
         }
     }
+
+    /// Offers to switch the environment above the table of a project that
+    /// has environments.
+    fn render_resources_table(&self) -> Element<'_, Message> {
+        let Some(project) = self
+            .project_service_selector
+            .get_selected_project()
+            .filter(|p| !p.environments.is_empty())
+        else {
+            return self.resources_table.view();
+        };
+
+        let id = project.get_id().to_string();
+        let selected = project
+            .get_environment(self.selected_environments.get(&id).map(|e| e.as_str()))
+            .map(|e| e.name.clone());
+        let names: Vec<String> = project
+            .environments
+            .iter()
+            .map(|e| e.name.clone())
+            .collect();
+        let environment = pick_list(names, selected, move |env| {
+            Message::ProjectEnvironmentSelected(id.clone(), env)
+        });
+        column![
+            row![text("Environment"), environment]
+                .spacing(8)
+                .padding([4, 0])
+                .align_y(Vertical::Center),
+            self.resources_table.view()
+        ]
+        .into()
+    }
 }
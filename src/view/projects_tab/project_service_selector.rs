@@ -1,37 +1,52 @@
 use iced::{
     alignment,
-    widget::{button, column, container, row, text},
-    Element, Length, Padding,
+    widget::{button, column, container, row, text, text_input},
+    Element, Length, Padding, Task,
 };
+use once_cell::sync::Lazy;
 
 use crate::{
     message::Message,
     models::{service::Service, workspace::Project},
+    providers::registry,
     view::{icons::ICONS, styles},
 };
 
+static NAME_INPUT_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+
 struct ProjectItem {
     index: usize,
     project: Project,
-    _selected: bool,
     open: bool,
 }
 
+/// What a project item needs to know about the selector.
+struct ItemContext<'a> {
+    selected_project: &'a Option<Project>,
+    selected_service: &'a Option<Service>,
+    /// The name being typed, if the project is being renamed.
+    new_name: Option<&'a str>,
+    deleting: bool,
+    is_last: bool,
+}
+
 impl ProjectItem {
     pub fn new(index: usize, project: Project) -> Self {
         Self {
             index,
             project,
-            _selected: false,
             open: false,
         }
     }
 
-    pub fn view(
-        &self,
-        selected_project: &Option<Project>,
-        selected_service: &Option<Service>,
-    ) -> Element<Message> {
+    fn view<'a>(&'a self, context: ItemContext<'a>) -> Element<'a, Message> {
+        let ItemContext {
+            selected_project,
+            selected_service,
+            new_name,
+            deleting,
+            is_last,
+        } = context;
         let mut c = column![].height(Length::Shrink);
         let toggle_mark = if self.open {
             ICONS.chevron_down(12.0, Length::Fixed(16.0), Length::Fixed(16.0))
@@ -39,24 +54,36 @@ impl ProjectItem {
             ICONS.chevron_right(12.0, Length::Fixed(16.0), Length::Fixed(16.0))
         };
         let is_selected_project = selected_project
-            .clone()
-            .filter(|p| p == &self.project)
-            .is_some();
+            .as_ref()
+            .is_some_and(|p| p.get_id() == self.project.get_id());
         let project_style = if is_selected_project {
             styles::selected_project
         } else {
             styles::project
         };
 
-        c = c.push(
-            button(
-                row![toggle_mark, text(&self.project.name)]
-                    .width(Length::Fill)
-                    .align_y(alignment::Alignment::Center),
-            )
-            .on_press(Message::ProjectSelected(self.index, self.project.clone()))
-            .style(project_style),
-        );
+        let header: Element<'_, Message> = match new_name {
+            Some(name) => self.render_name_input(name),
+            None => {
+                let mut r = row![button(
+                    row![toggle_mark, text(&self.project.name)]
+                        .width(Length::Fill)
+                        .align_y(alignment::Alignment::Center),
+                )
+                .on_press(Message::ProjectSelected(self.index, self.project.clone()))
+                .width(Length::Fill)
+                .style(project_style)]
+                .align_y(alignment::Alignment::Center);
+                if is_selected_project {
+                    r = r.push(self.render_tools(is_last));
+                }
+                r.into()
+            }
+        };
+        c = c.push(header);
+        if deleting {
+            c = c.push(self.render_delete_confirmation());
+        }
         if self.open {
             for service in self.project.get_services().iter() {
                 let is_selected_service =
@@ -71,14 +98,24 @@ impl ProjectItem {
                         text(" ")
                             .width(Length::Fixed(16.0))
                             .height(Length::Fixed(16.0)),
-                        button(text(format!("  {}", service)))
-                            .on_press(Message::ProjectServiceSelected(
-                                self.index,
-                                self.project.clone(),
-                                service.clone(),
-                            ))
-                            .width(Length::Fill)
-                            .style(service_style),
+                        button(
+                            row![
+                                registry()
+                                    .get(service)
+                                    .map(|p| p.icon(10.0))
+                                    .unwrap_or_else(|| text("").width(Length::Fixed(16.0)).into()),
+                                text(service.to_string()),
+                            ]
+                            .spacing(4)
+                            .align_y(alignment::Alignment::Center),
+                        )
+                        .on_press(Message::ProjectServiceSelected(
+                            self.index,
+                            self.project.clone(),
+                            service.clone(),
+                        ))
+                        .width(Length::Fill)
+                        .style(service_style),
                     ]
                     .height(Length::Shrink),
                 );
@@ -91,12 +128,86 @@ impl ProjectItem {
             .height(Length::Shrink)
             .into()
     }
+
+    fn render_name_input<'a>(&'a self, name: &'a str) -> Element<'a, Message> {
+        row![
+            text_input("Project name", name)
+                .id(NAME_INPUT_ID.clone())
+                .on_input(Message::ProjectNameChanged)
+                .on_submit(Message::ProjectRenameSubmitted)
+                .width(Length::Fill),
+            button(ICONS.check_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::ProjectRenameSubmitted)
+                .style(styles::tool_button),
+            button(ICONS.x_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::ProjectRenameCancelled)
+                .style(styles::tool_button),
+        ]
+        .spacing(2)
+        .align_y(alignment::Alignment::Center)
+        .into()
+    }
+
+    fn render_tools(&self, is_last: bool) -> Element<'_, Message> {
+        let id = self.project.get_id().to_string();
+        let tool = |icon: Element<'static, Message>, message: Option<Message>| {
+            button(icon)
+                .on_press_maybe(message)
+                .style(styles::tool_button)
+        };
+        row![
+            tool(
+                ICONS.pencil(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                Some(Message::ProjectRenameStarted(id.clone())),
+            ),
+            tool(
+                ICONS.arrow_up(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                (self.index > 0).then(|| Message::ProjectMoved(id.clone(), self.index - 1)),
+            ),
+            tool(
+                ICONS.arrow_down(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                (!is_last).then(|| Message::ProjectMoved(id.clone(), self.index + 1)),
+            ),
+            tool(
+                ICONS.trash(10.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                Some(Message::ProjectDeleteRequested(id.clone())),
+            ),
+        ]
+        .into()
+    }
+
+    fn render_delete_confirmation(&self) -> Element<'_, Message> {
+        row![
+            text(format!(
+                "Delete \"{}\" and its {} resources?",
+                self.project.name,
+                self.project.resources.len()
+            ))
+            .width(Length::Fill),
+            button(text("Delete"))
+                .on_press(Message::ProjectDeleteConfirmed(
+                    self.project.get_id().to_string()
+                ))
+                .style(button::danger),
+            button(text("Cancel"))
+                .on_press(Message::ProjectDeleteCancelled)
+                .style(styles::tool_button),
+        ]
+        .spacing(4)
+        .padding([4, 16])
+        .align_y(alignment::Alignment::Center)
+        .into()
+    }
 }
 
 pub struct ProjectServiceSelector {
     project_items: Vec<ProjectItem>,
     selected_project: Option<Project>,
     selected_service: Option<Service>,
+    /// The ID of the project being renamed and its new name.
+    renaming: Option<(String, String)>,
+    /// The ID of the project whose deletion awaits confirmation.
+    deleting: Option<String>,
 }
 
 impl ProjectServiceSelector {
@@ -105,22 +216,105 @@ impl ProjectServiceSelector {
             project_items: vec![],
             selected_project: None,
             selected_service: None,
+            renaming: None,
+            deleting: None,
         }
     }
 
+    /// Keeps the open projects and the selection, as far as the projects
+    /// still exist.
     pub fn set_projects(&mut self, projects: &[Project]) {
+        let open: Vec<_> = self
+            .project_items
+            .iter()
+            .filter(|pi| pi.open)
+            .map(|pi| pi.project.get_id().to_string())
+            .collect();
         self.project_items = projects
             .iter()
             .enumerate()
-            .map(|(index, p)| ProjectItem::new(index, p.clone()))
+            .map(|(index, p)| ProjectItem {
+                open: open.iter().any(|id| id == p.get_id()),
+                ..ProjectItem::new(index, p.clone())
+            })
             .collect();
+
+        let selected = self
+            .selected_project
+            .as_ref()
+            .and_then(|s| projects.iter().find(|p| p.get_id() == s.get_id()));
+        match selected {
+            Some(project) => self.selected_project = Some(project.clone()),
+            None => {
+                self.selected_project = None;
+                self.selected_service = None;
+            }
+        }
+    }
+
+    pub fn get_selected_project(&self) -> Option<Project> {
+        self.selected_project.clone()
+    }
+
+    pub fn get_selected_service(&self) -> Option<Service> {
+        self.selected_service.clone()
+    }
+
+    pub fn start_renaming(&mut self, id: &str) -> Task<Message> {
+        let Some(pi) = self
+            .project_items
+            .iter()
+            .find(|pi| pi.project.get_id() == id)
+        else {
+            return Task::none();
+        };
+        self.renaming = Some((id.to_string(), pi.project.name.clone()));
+        self.deleting = None;
+        text_input::focus(NAME_INPUT_ID.clone())
+    }
+
+    pub fn set_new_name(&mut self, name: String) {
+        if let Some((_, new_name)) = &mut self.renaming {
+            *new_name = name;
+        }
+    }
+
+    /// The ID of the project and its new name, unless the name is blank.
+    pub fn finish_renaming(&mut self) -> Option<(String, String)> {
+        let (id, name) = self.renaming.as_ref()?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let renamed = (id.clone(), name.to_string());
+        self.renaming = None;
+        Some(renamed)
+    }
+
+    pub fn cancel_renaming(&mut self) {
+        self.renaming = None;
+    }
+
+    pub fn set_deleting(&mut self, id: Option<String>) {
+        self.deleting = id;
+        self.renaming = None;
+    }
+
+    pub fn open_project(&mut self, id: &str) {
+        if let Some(pi) = self
+            .project_items
+            .iter_mut()
+            .find(|pi| pi.project.get_id() == id)
+        {
+            pi.open = true;
+        }
     }
 
     pub fn toggle_project(&mut self, project: &Project) {
         if let Some(pi) = self
             .project_items
             .iter_mut()
-            .find(|pi| &pi.project == project)
+            .find(|pi| pi.project.get_id() == project.get_id())
         {
             pi.open = !pi.open;
         }
@@ -138,12 +332,25 @@ impl ProjectServiceSelector {
         self.selected_service = service;
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let buttons = self.render_buttons();
         let mut c = column![buttons];
 
         for p in self.project_items.iter() {
-            c = c.push(p.view(&self.selected_project, &self.selected_service));
+            let id = p.project.get_id();
+            c = c.push(
+                p.view(ItemContext {
+                    selected_project: &self.selected_project,
+                    selected_service: &self.selected_service,
+                    new_name: self
+                        .renaming
+                        .as_ref()
+                        .filter(|(renamed, _)| renamed == id)
+                        .map(|(_, name)| name.as_str()),
+                    deleting: self.deleting.as_deref() == Some(id),
+                    is_last: p.index + 1 == self.project_items.len(),
+                }),
+            );
         }
 
         container(c)
@@ -152,7 +359,7 @@ impl ProjectServiceSelector {
             .into()
     }
 
-    pub fn render_buttons(&self) -> Element<Message> {
+    pub fn render_buttons(&self) -> Element<'_, Message> {
         let project_add_icon = ICONS.folder_plus(10.0, Length::Fixed(16.0), Length::Fixed(16.0));
         let project_add_button = button(project_add_icon)
             .on_press(Message::AddProject)
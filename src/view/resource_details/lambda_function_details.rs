@@ -4,7 +4,11 @@ use iced::{
     Element, Length,
 };
 
-use crate::{message::Message, models::resource::LambdaFunctionInfo, view::fonts};
+use crate::{
+    message::Message,
+    models::{lambda_function::LambdaFunctionInfo, resource::Resource},
+    view::{fonts, resource_details::DetailsView},
+};
 
 pub struct LambdaFunctionDetails {}
 
@@ -13,7 +17,7 @@ impl LambdaFunctionDetails {
         Self {}
     }
 
-    pub fn render(&self, f: &LambdaFunctionInfo) -> iced::Element<Message> {
+    pub fn render(&self, f: &LambdaFunctionInfo) -> iced::Element<'_, Message> {
         scrollable(
             column![
                 self.string_prop("Name", &f.0.function_name),
@@ -85,7 +89,7 @@ impl LambdaFunctionDetails {
         .into()
     }
 
-    fn render_vpc_config(&self, f: &LambdaFunctionInfo) -> Element<Message> {
+    fn render_vpc_config(&self, f: &LambdaFunctionInfo) -> Element<'_, Message> {
         let Some(vpc_config) = f.0.vpc_config.as_ref() else {
             return column![
                 row![
@@ -126,7 +130,7 @@ impl LambdaFunctionDetails {
         .into()
     }
 
-    fn render_environment_variables(&self, f: &LambdaFunctionInfo) -> Element<Message> {
+    fn render_environment_variables(&self, f: &LambdaFunctionInfo) -> Element<'_, Message> {
         let mut c = column![self.render_label("Environment variables: ")];
 
         let Some(env) = f.0.environment.as_ref() else {
@@ -154,7 +158,7 @@ impl LambdaFunctionDetails {
         v.as_ref().map(|v| v.join(", "))
     }
 
-    fn string_prop(&self, label: &str, value: &Option<String>) -> Element<Message> {
+    fn string_prop(&self, label: &str, value: &Option<String>) -> Element<'_, Message> {
         let value = match value {
             Some(v) => text_input("", v).width(Length::Fill).on_input(do_nothing),
             None => text_input("-", "").font(fonts::get_default_font()).size(12),
@@ -168,7 +172,7 @@ impl LambdaFunctionDetails {
         label: &str,
         value: &Option<impl num_traits::Num + std::fmt::Display + std::default::Default>,
         unit: Option<&str>,
-    ) -> Element<Message> {
+    ) -> Element<'_, Message> {
         let value = match value {
             Some(v) => text_input("", &v.to_string())
                 .on_input(do_nothing)
@@ -185,7 +189,7 @@ impl LambdaFunctionDetails {
             .into()
     }
 
-    fn bool_prop(&self, label: &str, value: &Option<bool>) -> Element<Message> {
+    fn bool_prop(&self, label: &str, value: &Option<bool>) -> Element<'_, Message> {
         let value = match value {
             Some(v) => text_input("", if *v { "true" } else { "false" }).on_input(do_nothing),
             None => text_input("-", ""),
@@ -195,7 +199,7 @@ impl LambdaFunctionDetails {
             .into()
     }
 
-    fn render_label(&self, label: &str) -> Element<Message> {
+    fn render_label(&self, label: &str) -> Element<'_, Message> {
         text(label.to_string())
             .font(fonts::get_bold_font())
             .width(Length::Fill)
@@ -206,6 +210,25 @@ impl LambdaFunctionDetails {
     }
 }
 
+impl DetailsView for LambdaFunctionDetails {
+    fn set_resource(&mut self, _resource: Option<&Resource>) {}
+
+    fn view<'a>(&'a self, resource: &'a Resource) -> Element<'a, Message> {
+        match resource.data::<LambdaFunctionInfo>() {
+            Some(f) => self.render(f),
+            None => text("Not a Lambda function").into(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
 fn do_nothing(_: String) -> Message {
     Message::DoNothing
 }
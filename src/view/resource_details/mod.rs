@@ -1,42 +1,110 @@
 pub mod lambda_function_details;
 pub mod s3_bucket_details;
 
+use std::{any::Any, collections::HashMap};
+
 use iced::{
     widget::{container, text},
     Element,
 };
 
-use crate::{message::Message, models::resource::Resource};
+use crate::{
+    message::Message,
+    models::{
+        resource::Resource,
+        service::Service,
+        workspace::{settings::ProtectionMode, ResourceDescriptor},
+    },
+    providers::registry,
+};
 
-use lambda_function_details::LambdaFunctionDetails;
 use s3_bucket_details::S3BucketDetails;
 
+/// The details view of one service. Views keep their state while other
+/// resources are shown, and are handed the resource to render.
+pub trait DetailsView {
+    /// `None` when a resource of another service, or nothing, is shown.
+    fn set_resource(&mut self, resource: Option<&Resource>);
+
+    fn set_protection(&mut self, _protection: Option<ProtectionMode>) {}
+
+    fn view<'a>(&'a self, resource: &'a Resource) -> Element<'a, Message>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
 pub struct ResourceDetails {
     resource: Option<Resource>,
-    lambda_function_details: LambdaFunctionDetails,
-    s3_bucket_details: S3BucketDetails,
+    views: HashMap<Service, Box<dyn DetailsView>>,
 }
 
 impl ResourceDetails {
     pub fn new() -> Self {
         Self {
             resource: None,
-            lambda_function_details: LambdaFunctionDetails::new(),
-            s3_bucket_details: S3BucketDetails::new(),
+            views: registry()
+                .providers()
+                .map(|p| (p.service().clone(), p.details_view()))
+                .collect(),
         }
     }
 
     pub fn set_resource(&mut self, resource: Option<Resource>) {
+        for (service, view) in &mut self.views {
+            view.set_resource(
+                resource
+                    .as_ref()
+                    .filter(|r| &r.descriptor.service == service),
+            );
+        }
         self.resource = resource;
     }
 
-    pub fn view(&self) -> Element<Message> {
-        match &self.resource {
-            Some(resource) => match resource {
-                Resource::LambdaFunction(f) => self.lambda_function_details.render(f),
-                Resource::S3Bucket(b) => self.s3_bucket_details.render(b),
-            },
-            None => container(text("No resource selected")).into(),
+    pub fn get_resource(&self) -> Option<&Resource> {
+        self.resource.as_ref()
+    }
+
+    /// Applies the protection of the shown resource's profile.
+    pub fn set_protection(&mut self, protection_for: impl Fn(&str) -> Option<ProtectionMode>) {
+        let protection = self
+            .resource
+            .as_ref()
+            .and_then(|r| protection_for(&r.descriptor.profile));
+        for view in self.views.values_mut() {
+            view.set_protection(protection);
+        }
+    }
+
+    pub fn s3_bucket_details(&self, bucket: &ResourceDescriptor) -> Option<&S3BucketDetails> {
+        self.views
+            .values()
+            .find_map(|v| v.as_any().downcast_ref::<S3BucketDetails>())
+            .filter(|d| d.shows(bucket))
+    }
+
+    pub fn s3_bucket_details_mut(
+        &mut self,
+        bucket: &ResourceDescriptor,
+    ) -> Option<&mut S3BucketDetails> {
+        self.views
+            .values_mut()
+            .find_map(|v| v.as_any_mut().downcast_mut::<S3BucketDetails>())
+            .filter(|d| d.shows(bucket))
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(resource) = &self.resource else {
+            return container(text("No resource selected")).into();
+        };
+        match self.views.get(&resource.descriptor.service) {
+            Some(view) => view.view(resource),
+            None => container(text(format!(
+                "{} resources cannot be shown",
+                resource.descriptor.service
+            )))
+            .into(),
         }
     }
 }
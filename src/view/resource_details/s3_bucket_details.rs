@@ -1,24 +1,930 @@
+use std::time::Duration;
+
+use aws_sdk_s3::types::StorageClass;
 use iced::{
-    widget::{column, text},
-    Element,
+    alignment::{Horizontal, Vertical},
+    widget::{
+        button, checkbox, column, container, image, pick_list, row, scrollable, text, text_input,
+        Space,
+    },
+    Element, Length,
+};
+
+use crate::{
+    message::Message,
+    models::{
+        resource::Resource,
+        s3_bucket::BucketInfo,
+        s3_bucket_config::{BucketSetting, S3BucketConfig},
+        s3_object::{
+            parent_prefix, PreviewContent, S3ObjectEntry, S3ObjectPreview, S3ObjectVersion,
+            S3VersionAction, ServerSideEncryptionOption, UploadOptions, STORAGE_CLASSES,
+        },
+        s3_presign::{PresignExpiry, PresignMethod, PresignRequest, PresignedUrl},
+        workspace::{settings::ProtectionMode, ResourceDescriptor},
+    },
+    view::{
+        easing, fonts, icons::ICONS, linear::Linear, resource_details::DetailsView, styles,
+        units::format_bytes,
+    },
 };
 
-use crate::{message::Message, models::resource::BucketInfo};
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum S3BucketTab {
+    Objects,
+    Configuration,
+}
+
+/// A mutating action that waits for the user's confirmation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum S3PendingAction {
+    Version(S3VersionAction),
+    Upload,
+    PresignPut(PresignRequest),
+}
 
-pub struct S3BucketDetails {}
+struct Preview {
+    object: S3ObjectPreview,
+    image: Option<image::Handle>,
+}
+
+pub struct S3BucketDetails {
+    bucket: Option<ResourceDescriptor>,
+    tab: S3BucketTab,
+    config: Option<S3BucketConfig>,
+    prefix: String,
+    entries: Vec<S3ObjectEntry>,
+    loading_entries: bool,
+    show_versions: bool,
+    pending_action: Option<S3PendingAction>,
+    confirmation_input: String,
+    protection: Option<ProtectionMode>,
+    selected_key: Option<String>,
+    preview: Option<Preview>,
+    download_directory: String,
+    presign_method: PresignMethod,
+    presign_expiry: PresignExpiry,
+    presign_content_type: String,
+    presigned_url: Option<PresignedUrl>,
+    upload_path: String,
+    upload_content_type: String,
+    upload_storage_class: Option<StorageClass>,
+    upload_encryption: ServerSideEncryptionOption,
+    upload_kms_key_id: String,
+}
 
 impl S3BucketDetails {
     pub fn new() -> Self {
-        Self {}
+        let download_directory = directories::UserDirs::new()
+            .and_then(|dirs| {
+                dirs.download_dir()
+                    .map(|d| d.to_path_buf())
+                    .or_else(|| Some(dirs.home_dir().to_path_buf()))
+            })
+            .map(|d| d.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Self {
+            bucket: None,
+            tab: S3BucketTab::Objects,
+            config: None,
+            prefix: String::new(),
+            entries: vec![],
+            loading_entries: false,
+            show_versions: false,
+            pending_action: None,
+            confirmation_input: String::new(),
+            protection: None,
+            selected_key: None,
+            preview: None,
+            download_directory,
+            presign_method: PresignMethod::default(),
+            presign_expiry: PresignExpiry::default(),
+            presign_content_type: String::new(),
+            presigned_url: None,
+            upload_path: String::new(),
+            upload_content_type: String::new(),
+            upload_storage_class: None,
+            upload_encryption: ServerSideEncryptionOption::None,
+            upload_kms_key_id: String::new(),
+        }
+    }
+
+    pub fn set_bucket(&mut self, bucket: Option<ResourceDescriptor>) {
+        if self.bucket == bucket {
+            return;
+        }
+        self.bucket = bucket;
+        self.config = None;
+        self.prefix = String::new();
+        self.entries = vec![];
+        self.show_versions = false;
+        self.pending_action = None;
+        self.confirmation_input = String::new();
+        self.selected_key = None;
+        self.preview = None;
+    }
+
+    pub fn shows(&self, bucket: &ResourceDescriptor) -> bool {
+        self.bucket.as_ref() == Some(bucket)
+    }
+
+    pub fn set_tab(&mut self, tab: S3BucketTab) {
+        self.tab = tab;
+    }
+
+    pub fn set_config(&mut self, config: S3BucketConfig) {
+        self.config = Some(config);
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
     }
 
-    pub fn render(&self, b: &BucketInfo) -> Element<Message> {
+    pub fn set_prefix(&mut self, prefix: String) {
+        self.prefix = prefix;
+        self.entries = vec![];
+        self.loading_entries = true;
+        self.selected_key = None;
+        self.preview = None;
+    }
+
+    pub fn is_showing_versions(&self) -> bool {
+        self.show_versions
+    }
+
+    pub fn set_show_versions(&mut self, show_versions: bool) {
+        self.show_versions = show_versions;
+    }
+
+    pub fn set_protection(&mut self, protection: Option<ProtectionMode>) {
+        self.protection = protection;
+        if protection.is_some_and(|p| p.blocks_changes()) {
+            self.pending_action = None;
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.protection.is_some_and(|p| p.blocks_changes())
+    }
+
+    pub fn set_pending_action(&mut self, action: Option<S3PendingAction>) {
+        self.pending_action = action;
+        self.confirmation_input = String::new();
+    }
+
+    pub fn set_confirmation_input(&mut self, input: String) {
+        self.confirmation_input = input;
+    }
+
+    /// On a protected bucket, the bucket name must have been typed in.
+    fn is_confirmation_valid(&self) -> bool {
+        match self.protection {
+            None => true,
+            Some(ProtectionMode::Confirm) => {
+                self.bucket.as_ref().map(|b| b.id.as_str()) == Some(self.confirmation_input.trim())
+            }
+            Some(ProtectionMode::Unverified | ProtectionMode::ReadOnly) => false,
+        }
+    }
+
+    /// Returns the pending action if it may run now.
+    pub fn take_confirmed_action(&mut self) -> Option<S3PendingAction> {
+        if !self.is_confirmation_valid() {
+            return None;
+        }
+        self.confirmation_input = String::new();
+        self.pending_action.take()
+    }
+
+    fn is_versioning_enabled(&self) -> bool {
+        matches!(
+            self.config.as_ref().map(|c| &c.versioning),
+            Some(BucketSetting::Configured(_))
+        )
+    }
+
+    pub fn set_entries(&mut self, prefix: &str, entries: Vec<S3ObjectEntry>) {
+        // A slow listing for a prefix the user already left must not
+        // overwrite the current one.
+        if self.prefix != prefix {
+            return;
+        }
+        self.entries = entries;
+        self.loading_entries = false;
+    }
+
+    pub fn set_selected_key(&mut self, key: Option<String>) {
+        self.selected_key = key;
+        self.preview = None;
+        self.presigned_url = None;
+    }
+
+    pub fn set_preview(&mut self, preview: S3ObjectPreview) {
+        if self.selected_key.as_ref() != Some(&preview.key) {
+            return;
+        }
+        let image = match &preview.content {
+            PreviewContent::Image(bytes) => Some(image::Handle::from_bytes(bytes.clone())),
+            _ => None,
+        };
+        self.preview = Some(Preview {
+            object: preview,
+            image,
+        });
+    }
+
+    pub fn get_download_directory(&self) -> &str {
+        &self.download_directory
+    }
+
+    pub fn set_download_directory(&mut self, directory: String) {
+        self.download_directory = directory;
+    }
+
+    pub fn set_presign_method(&mut self, method: PresignMethod) {
+        self.presign_method = method;
+        self.presigned_url = None;
+    }
+
+    pub fn set_presign_expiry(&mut self, expiry: PresignExpiry) {
+        self.presign_expiry = expiry;
+        self.presigned_url = None;
+    }
+
+    pub fn set_presign_content_type(&mut self, content_type: String) {
+        self.presign_content_type = content_type;
+        self.presigned_url = None;
+    }
+
+    pub fn get_presign_request(&self) -> Option<PresignRequest> {
+        Some(PresignRequest {
+            key: self.selected_key.clone()?,
+            method: self.presign_method,
+            expiry: self.presign_expiry,
+            content_type: match self.presign_method {
+                PresignMethod::Get => None,
+                PresignMethod::Put => non_empty(&self.presign_content_type),
+            },
+        })
+    }
+
+    pub fn set_presigned_url(&mut self, url: PresignedUrl) {
+        if self.selected_key.as_ref() != Some(&url.key) {
+            return;
+        }
+        self.presigned_url = Some(url);
+    }
+
+    pub fn get_upload_path(&self) -> &str {
+        &self.upload_path
+    }
+
+    pub fn set_upload_path(&mut self, path: String) {
+        self.upload_path = path;
+    }
+
+    pub fn set_upload_content_type(&mut self, content_type: String) {
+        self.upload_content_type = content_type;
+    }
+
+    pub fn set_upload_storage_class(&mut self, storage_class: StorageClass) {
+        self.upload_storage_class = Some(storage_class);
+    }
+
+    pub fn set_upload_encryption(&mut self, encryption: ServerSideEncryptionOption) {
+        self.upload_encryption = encryption;
+    }
+
+    pub fn set_upload_kms_key_id(&mut self, key_id: String) {
+        self.upload_kms_key_id = key_id;
+    }
+
+    pub fn get_upload_options(&self) -> UploadOptions {
+        UploadOptions {
+            content_type: non_empty(&self.upload_content_type),
+            storage_class: self.upload_storage_class.clone(),
+            encryption: self.upload_encryption,
+            kms_key_id: non_empty(&self.upload_kms_key_id),
+        }
+    }
+
+    pub fn render(&self, b: &BucketInfo) -> Element<'_, Message> {
+        let rd = &b.descriptor;
         let mut c = column![];
         c = c.push(text("S3 Bucket Details").size(24));
         c = c.push(text(format!(
             "Name: {}",
-            b.0.name.as_ref().unwrap_or(&"Unnamed".to_string())
+            b.bucket.name.as_ref().unwrap_or(&"Unnamed".to_string())
         )));
+        c = c.push(self.render_tabs(rd));
+        match self.tab {
+            S3BucketTab::Objects => {
+                if self.is_read_only() {
+                    c = c.push(
+                        text(format!(
+                            "Read-only: the profile {} is protected",
+                            rd.profile
+                        ))
+                        .style(text::danger),
+                    );
+                }
+                c = c.push(self.render_toolbar(rd));
+                if let Some(action) = &self.pending_action {
+                    c = c.push(self.render_action_confirmation(rd, action));
+                }
+                c = c.push(self.render_entries(rd));
+                if let Some(key) = &self.selected_key {
+                    c = c.push(self.render_selected_object(rd, key));
+                }
+                c = c.push(self.render_upload_form(rd));
+            }
+            S3BucketTab::Configuration => c = c.push(self.render_config()),
+        }
+
+        scrollable(c.padding(4).spacing(8)).into()
+    }
+
+    fn render_tabs(&self, rd: &ResourceDescriptor) -> Element<'_, Message> {
+        let tab_button = |label: &'static str, tab: S3BucketTab| {
+            let style = if self.tab == tab {
+                styles::selected_object_entry
+            } else {
+                styles::object_entry
+            };
+            button(text(label))
+                .on_press(Message::S3BucketTabSelected(rd.clone(), tab))
+                .style(style)
+        };
+
+        row![
+            tab_button("Objects", S3BucketTab::Objects),
+            tab_button("Configuration", S3BucketTab::Configuration),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn render_config(&self) -> Element<'_, Message> {
+        let Some(config) = &self.config else {
+            return container(
+                Linear::new()
+                    .easing(&easing::STANDARD)
+                    .cycle_duration(Duration::from_secs_f32(2.0))
+                    .width(Length::Fill),
+            )
+            .into();
+        };
+
+        let mut c = column![].spacing(8);
+        for (label, setting) in config.settings() {
+            let value = text(setting.to_string()).font(fonts::get_default_font());
+            let value = match setting {
+                BucketSetting::Configured(_) => value,
+                BucketSetting::NotConfigured => value.style(text::secondary),
+                BucketSetting::AccessDenied | BucketSetting::Failed(_) => value.style(text::danger),
+            };
+            c = c.push(column![self.render_label(label), value].spacing(4));
+        }
+        c.into()
+    }
+
+    fn render_toolbar(&self, rd: &ResourceDescriptor) -> Element<'_, Message> {
+        let up_button = button(ICONS.arrow_up(12.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+            .on_press_maybe(
+                (!self.prefix.is_empty())
+                    .then(|| Message::S3PrefixSelected(rd.clone(), parent_prefix(&self.prefix))),
+            )
+            .style(styles::tool_button);
+        let refresh_button =
+            button(ICONS.arrow_clockwise(12.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::S3PrefixSelected(rd.clone(), self.prefix.clone()))
+                .style(styles::tool_button);
+
+        let mut r = row![
+            up_button,
+            refresh_button,
+            text(format!("s3://{}/{}", rd.id, self.prefix))
+                .height(Length::Fixed(24.0))
+                .align_y(Vertical::Center)
+                .width(Length::Fill),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+
+        // Suspended buckets still keep their old versions, so the toggle is
+        // offered whenever versioning has ever been configured.
+        if self.is_versioning_enabled() || self.show_versions {
+            r = r.push(checkbox("Show versions", self.show_versions).on_toggle({
+                let rd = rd.clone();
+                move |show| Message::S3ShowVersionsToggled(rd.clone(), show)
+            }));
+        }
+
+        r.into()
+    }
+
+    fn render_action_confirmation(
+        &self,
+        rd: &ResourceDescriptor,
+        action: &S3PendingAction,
+    ) -> Element<'_, Message> {
+        let description = match action {
+            S3PendingAction::Version(action) => action.to_string(),
+            S3PendingAction::Upload => format!(
+                "Upload {} to s3://{}/{}",
+                self.upload_path, rd.id, self.prefix
+            ),
+            S3PendingAction::PresignPut(request) => format!(
+                "Create a URL that lets anyone write to s3://{}/{} for {}",
+                rd.id, request.key, request.expiry
+            ),
+        };
+
+        let mut r = row![text(format!("{}?", description))
+            .style(text::danger)
+            .width(Length::Fill)]
+        .spacing(4)
+        .align_y(Vertical::Center);
+
+        if self.protection == Some(ProtectionMode::Confirm) {
+            r = r.push(
+                text_input(
+                    &format!("Type {} to confirm", rd.id),
+                    &self.confirmation_input,
+                )
+                .on_input({
+                    let rd = rd.clone();
+                    move |input| Message::S3ConfirmationInputChanged(rd.clone(), input)
+                })
+                .width(Length::Fixed(240.0)),
+            );
+        }
+
+        r = r.push(
+            button(text("Confirm"))
+                .on_press_maybe(
+                    self.is_confirmation_valid()
+                        .then(|| Message::S3ActionConfirmed(rd.clone())),
+                )
+                .style(button::danger),
+        );
+        r = r.push(
+            button(text("Cancel"))
+                .on_press(Message::S3ActionCancelled(rd.clone()))
+                .style(styles::tool_button),
+        );
+
+        container(r).style(styles::pane_active).padding(4).into()
+    }
+
+    fn render_version<'a>(
+        &'a self,
+        rd: &ResourceDescriptor,
+        version: &'a S3ObjectVersion,
+    ) -> Element<'a, Message> {
+        let icon = if version.is_delete_marker {
+            ICONS.x_lg(12.0, Length::Fixed(16.0), Length::Fixed(16.0))
+        } else {
+            ICONS.file_earmark(12.0, Length::Fixed(16.0), Length::Fixed(16.0))
+        };
+        let badge = match (version.is_latest, version.is_delete_marker) {
+            (true, true) => "Latest, delete marker",
+            (false, true) => "Delete marker",
+            (true, false) => "Latest",
+            (false, false) => "",
+        };
+
+        let mut r = row![
+            icon,
+            text(version.file_name()).width(Length::Fill),
+            text(version.version_id.clone())
+                .font(fonts::get_default_font())
+                .style(text::secondary)
+                .width(Length::Fixed(280.0)),
+            text(badge).width(Length::Fixed(140.0)),
+            text(
+                version
+                    .size
+                    .map(|s| format_bytes(s as u64))
+                    .unwrap_or_default()
+            )
+            .width(Length::Fixed(80.0))
+            .align_x(Horizontal::Right),
+            text(
+                version
+                    .last_modified
+                    .map(|d| d.to_string())
+                    .unwrap_or_default()
+            )
+            .width(Length::Fixed(180.0)),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+
+        if version.is_delete_marker {
+            r = r.push(
+                button(text("Remove marker"))
+                    .on_press_maybe((!self.is_read_only()).then(|| {
+                        Message::S3VersionActionRequested(
+                            rd.clone(),
+                            S3VersionAction::RemoveDeleteMarker {
+                                key: version.key.clone(),
+                                version_id: version.version_id.clone(),
+                            },
+                        )
+                    }))
+                    .style(styles::tool_button),
+            );
+        } else {
+            r = r.push(
+                button(ICONS.download(12.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                    .on_press(Message::S3DownloadRequested(
+                        rd.clone(),
+                        version.key.clone(),
+                        Some(version.version_id.clone()),
+                    ))
+                    .style(styles::tool_button),
+            );
+            if !version.is_latest {
+                r = r.push(
+                    button(text("Restore"))
+                        .on_press_maybe((!self.is_read_only()).then(|| {
+                            Message::S3VersionActionRequested(
+                                rd.clone(),
+                                S3VersionAction::Restore {
+                                    key: version.key.clone(),
+                                    version_id: version.version_id.clone(),
+                                },
+                            )
+                        }))
+                        .style(styles::tool_button),
+                );
+            }
+        }
+
+        container(r).padding([2, 4]).into()
+    }
+
+    fn render_entries(&self, rd: &ResourceDescriptor) -> Element<'_, Message> {
+        if self.loading_entries {
+            return container(
+                Linear::new()
+                    .easing(&easing::STANDARD)
+                    .cycle_duration(Duration::from_secs_f32(2.0))
+                    .width(Length::Fill),
+            )
+            .into();
+        }
+
+        if self.entries.is_empty() {
+            return text("No objects").into();
+        }
+
+        let mut list = column![];
+        for entry in &self.entries {
+            let item: Element<Message> = match entry {
+                S3ObjectEntry::Prefix(prefix) => button(
+                    row![
+                        ICONS.folder(12.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                        text(prefix.strip_prefix(&self.prefix).unwrap_or(prefix)),
+                    ]
+                    .spacing(4),
+                )
+                .on_press(Message::S3PrefixSelected(rd.clone(), prefix.clone()))
+                .style(styles::object_entry)
+                .width(Length::Fill)
+                .into(),
+                S3ObjectEntry::Version(version) => self.render_version(rd, version),
+                S3ObjectEntry::Object(object) => {
+                    let style = if self.selected_key.as_ref() == Some(&object.key) {
+                        styles::selected_object_entry
+                    } else {
+                        styles::object_entry
+                    };
+                    button(
+                        row![
+                            ICONS.file_earmark(12.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                            text(object.file_name()).width(Length::Fill),
+                            text(
+                                object
+                                    .size
+                                    .map(|s| format_bytes(s as u64))
+                                    .unwrap_or_default()
+                            )
+                            .width(Length::Fixed(80.0))
+                            .align_x(Horizontal::Right),
+                            text(object.last_modified.clone().unwrap_or_default())
+                                .width(Length::Fixed(180.0)),
+                        ]
+                        .spacing(4),
+                    )
+                    .on_press(Message::S3ObjectSelected(rd.clone(), object.key.clone()))
+                    .style(style)
+                    .width(Length::Fill)
+                    .into()
+                }
+            };
+            list = list.push(item);
+        }
+
+        container(scrollable(list).height(Length::Fixed(240.0)))
+            .style(styles::pane_active)
+            .into()
+    }
+
+    fn render_selected_object(&self, rd: &ResourceDescriptor, key: &str) -> Element<'_, Message> {
+        let mut c = column![self.render_label("Selected object"), text(key.to_string())];
+
+        let download = row![
+            text_input("Download directory", &self.download_directory)
+                .on_input({
+                    let rd = rd.clone();
+                    move |dir| Message::S3DownloadDirectoryChanged(rd.clone(), dir)
+                })
+                .width(Length::Fill),
+            button(
+                row![
+                    ICONS.download(12.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                    text("Download"),
+                ]
+                .spacing(4)
+            )
+            .on_press(Message::S3DownloadRequested(
+                rd.clone(),
+                key.to_string(),
+                None,
+            ))
+            .style(styles::tool_button),
+        ]
+        .spacing(4);
+        c = c.push(download);
+        c = c.push(self.render_presign_form(rd));
+
+        let Some(preview) = &self.preview else {
+            return c.push(text("Loading preview...")).spacing(4).into();
+        };
+
+        c = c.push(text(format!(
+            "Size: {}    Content type: {}",
+            preview
+                .object
+                .size
+                .map(|s| format_bytes(s as u64))
+                .unwrap_or("-".to_string()),
+            preview.object.content_type.as_deref().unwrap_or("-"),
+        )));
+
+        let content: Element<Message> = match &preview.object.content {
+            PreviewContent::Text(t) | PreviewContent::Json(t) => scrollable(
+                text(t.to_string())
+                    .font(fonts::get_default_font())
+                    .width(Length::Fill),
+            )
+            .height(Length::Fixed(320.0))
+            .into(),
+            PreviewContent::Image(_) => match &preview.image {
+                Some(handle) => image(handle.clone()).height(Length::Fixed(320.0)).into(),
+                None => text("Unable to display the image").into(),
+            },
+            PreviewContent::Binary => text("Binary content; no preview available").into(),
+            PreviewContent::TooLarge => text("The object is too large to preview").into(),
+        };
+        c = c.push(container(content).style(styles::pane_active).padding(4));
+
+        c.spacing(4).into()
+    }
+
+    fn render_presign_form(&self, rd: &ResourceDescriptor) -> Element<'_, Message> {
+        let mut form = row![
+            pick_list(PresignMethod::ALL, Some(self.presign_method), {
+                let rd = rd.clone();
+                move |method| Message::S3PresignMethodSelected(rd.clone(), method)
+            }),
+            pick_list(PresignExpiry::ALL, Some(self.presign_expiry), {
+                let rd = rd.clone();
+                move |expiry| Message::S3PresignExpirySelected(rd.clone(), expiry)
+            }),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+
+        if self.presign_method == PresignMethod::Put {
+            form = form.push(
+                text_input("Content type (optional)", &self.presign_content_type)
+                    .on_input({
+                        let rd = rd.clone();
+                        move |ct| Message::S3PresignContentTypeChanged(rd.clone(), ct)
+                    })
+                    .width(Length::Fill),
+            );
+        } else {
+            form = form.push(Space::with_width(Length::Fill));
+        }
+
+        form = form.push(
+            button(text("Copy presigned URL"))
+                .on_press_maybe(
+                    // A PUT URL lets anyone holding it write to the bucket.
+                    (self.presign_method == PresignMethod::Get || !self.is_read_only())
+                        .then(|| Message::S3PresignRequested(rd.clone())),
+                )
+                .style(styles::tool_button),
+        );
+
+        let mut c = column![form].spacing(4);
+
+        if let Some(url) = &self.presigned_url {
+            let mut details = column![
+                row![
+                    text_input("", &url.url)
+                        .font(fonts::get_default_font())
+                        .width(Length::Fill),
+                    button(text("Copy"))
+                        .on_press(Message::CopyToClipboard(url.url.clone()))
+                        .style(styles::tool_button),
+                ]
+                .spacing(4),
+                text(format!("{} URL, expires at {}", url.method, url.expires_at)),
+                text(format!("Signed by {}", url.signed_by)).style(text::secondary),
+            ]
+            .spacing(4);
+            for (name, value) in &url.headers {
+                details = details.push(text(format!("Required header: {}: {}", name, value)));
+            }
+            if let Some(expiry) = &url.credentials_expire_at {
+                details = details.push(
+                    text(format!(
+                        "The signing credentials are temporary and expire at {}; the URL stops working then",
+                        expiry
+                    ))
+                    .style(text::danger),
+                );
+            }
+            c = c.push(container(details).style(styles::pane_active).padding(4));
+        }
+
+        c.into()
+    }
+
+    fn render_upload_form(&self, rd: &ResourceDescriptor) -> Element<'_, Message> {
+        let mut c = column![
+            self.render_label(&format!("Upload to s3://{}/{}", rd.id, self.prefix)),
+            text_input("Local file or directory", &self.upload_path).on_input({
+                let rd = rd.clone();
+                move |path| Message::S3UploadPathChanged(rd.clone(), path)
+            }),
+            row![
+                text_input(
+                    "Content type (guessed from extension)",
+                    &self.upload_content_type
+                )
+                .on_input({
+                    let rd = rd.clone();
+                    move |ct| Message::S3UploadContentTypeChanged(rd.clone(), ct)
+                }),
+                pick_list(STORAGE_CLASSES, self.upload_storage_class.clone(), {
+                    let rd = rd.clone();
+                    move |sc| Message::S3UploadStorageClassSelected(rd.clone(), sc)
+                })
+                .placeholder("Storage class"),
+                pick_list(
+                    ServerSideEncryptionOption::ALL,
+                    Some(self.upload_encryption),
+                    {
+                        let rd = rd.clone();
+                        move |sse| Message::S3UploadEncryptionSelected(rd.clone(), sse)
+                    }
+                ),
+            ]
+            .spacing(4),
+        ]
+        .spacing(4);
+
+        if self.upload_encryption == ServerSideEncryptionOption::AwsKms {
+            c = c.push(
+                text_input(
+                    "KMS key ID (AWS managed key if empty)",
+                    &self.upload_kms_key_id,
+                )
+                .on_input({
+                    let rd = rd.clone();
+                    move |id| Message::S3UploadKmsKeyIdChanged(rd.clone(), id)
+                }),
+            );
+        }
+
+        c = c.push(
+            button(
+                row![
+                    ICONS.upload(12.0, Length::Fixed(16.0), Length::Fixed(16.0)),
+                    text("Upload"),
+                ]
+                .spacing(4),
+            )
+            .on_press_maybe(
+                (!self.upload_path.is_empty() && !self.is_read_only())
+                    .then(|| Message::S3UploadRequested(rd.clone())),
+            )
+            .style(styles::tool_button),
+        );
+
         c.into()
     }
+
+    fn render_label(&self, label: &str) -> Element<'_, Message> {
+        text(label.to_string())
+            .font(fonts::get_bold_font())
+            .width(Length::Fill)
+            .size(10.0)
+            .align_x(Horizontal::Left)
+            .align_y(Vertical::Center)
+            .into()
+    }
+}
+
+impl DetailsView for S3BucketDetails {
+    fn set_resource(&mut self, resource: Option<&Resource>) {
+        let bucket = resource
+            .and_then(|r| r.data::<BucketInfo>())
+            .map(|b| b.descriptor.clone());
+        self.set_bucket(bucket);
+    }
+
+    fn set_protection(&mut self, protection: Option<ProtectionMode>) {
+        S3BucketDetails::set_protection(self, protection);
+    }
+
+    fn view<'a>(&'a self, resource: &'a Resource) -> Element<'a, Message> {
+        match resource.data::<BucketInfo>() {
+            Some(b) => self.render(b),
+            None => text("Not an S3 bucket").into(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(protection: Option<ProtectionMode>) -> S3BucketDetails {
+        let mut details = S3BucketDetails::new();
+        details.set_bucket(Some(ResourceDescriptor {
+            profile: "prod".to_string(),
+            region: "eu-west-1".to_string(),
+            service: crate::providers::s3::SERVICE.clone(),
+            id: "logs".to_string(),
+        }));
+        details.set_protection(protection);
+        details
+    }
+
+    fn presign_put() -> S3PendingAction {
+        S3PendingAction::PresignPut(PresignRequest {
+            key: "a.txt".to_string(),
+            method: PresignMethod::Put,
+            expiry: PresignExpiry::default(),
+            content_type: None,
+        })
+    }
+
+    #[test]
+    fn put_presigns_wait_for_the_bucket_name_to_be_typed() {
+        let mut details = details(Some(ProtectionMode::Confirm));
+        details.set_pending_action(Some(presign_put()));
+
+        assert_eq!(details.take_confirmed_action(), None);
+        details.set_confirmation_input("other".to_string());
+        assert_eq!(details.take_confirmed_action(), None);
+        details.set_confirmation_input(" logs ".to_string());
+        assert_eq!(details.take_confirmed_action(), Some(presign_put()));
+        assert_eq!(details.take_confirmed_action(), None);
+    }
+
+    #[test]
+    fn protected_buckets_drop_pending_actions() {
+        let mut details = details(Some(ProtectionMode::Confirm));
+        details.set_pending_action(Some(presign_put()));
+        details.set_protection(Some(ProtectionMode::Unverified));
+        details.set_confirmation_input("logs".to_string());
+
+        assert_eq!(details.take_confirmed_action(), None);
+
+        let mut unprotected = self::details(None);
+        unprotected.set_pending_action(Some(presign_put()));
+        assert_eq!(unprotected.take_confirmed_action(), Some(presign_put()));
+    }
 }
@@ -1,6 +1,8 @@
+use std::collections::{BTreeMap, HashMap};
+
 use iced::{
     widget::{
-        container, mouse_area,
+        button, container, mouse_area,
         scrollable::{self, AbsoluteOffset},
         text,
     },
@@ -11,17 +13,35 @@ use iced_table::table;
 use crate::{
     message::Message,
     models::{
+        caller_identity::CallerIdentity,
         service::Service,
         workspace::{Project, ResourceDescriptor},
     },
+    providers::registry,
+    view::{icons::ICONS, styles},
 };
 
+const MIN_COLUMN_WIDTH: f32 = 24.0;
+
 pub struct ResourcesTable {
     header_id: iced::widget::scrollable::Id,
     body_id: iced::widget::scrollable::Id,
     footer_id: iced::widget::scrollable::Id,
     columns: Vec<Column>,
-    rows: Vec<ResourceDescriptor>,
+    /// The service whose columns are shown.
+    service: Option<Service>,
+    /// Widths of the columns resized by the user, by service.
+    column_widths: BTreeMap<Service, Vec<f32>>,
+    rows: Vec<ResourceRow>,
+    caller_identities: HashMap<String, CallerIdentity>,
+}
+
+struct ResourceRow {
+    /// The ID of the project the row belongs to.
+    project_id: String,
+    descriptor: ResourceDescriptor,
+    /// The account alias or ID of the row's profile, once it is known.
+    account: Option<String>,
 }
 
 impl ResourcesTable {
@@ -34,8 +54,12 @@ impl ResourcesTable {
                 Column::new(ColumnKind::Name),
                 Column::new(ColumnKind::Profile),
                 Column::new(ColumnKind::Region),
+                Column::new(ColumnKind::Remove),
             ],
+            service: None,
+            column_widths: BTreeMap::new(),
             rows: vec![],
+            caller_identities: HashMap::new(),
         }
     }
 
@@ -54,16 +78,32 @@ impl ResourcesTable {
             return;
         };
 
+        // Keep the column widths while the service stays the same.
+        if self.service.as_ref() != Some(&service) {
+            if let Some(provider) = registry().get(&service) {
+                self.columns = provider
+                    .table_columns()
+                    .into_iter()
+                    .chain([ColumnKind::Remove])
+                    .map(Column::new)
+                    .collect();
+            }
+            self.service = Some(service.clone());
+            self.apply_column_widths();
+        }
+
         self.rows = project
             .resources
             .iter()
             .filter_map(|r| {
                 if r.service == service {
-                    Some(ResourceDescriptor {
-                        profile: r.profile.clone(),
-                        region: r.region.clone(),
-                        service: r.service.clone(),
-                        id: r.get_display_name(),
+                    Some(ResourceRow {
+                        project_id: project.get_id().to_string(),
+                        descriptor: r.clone(),
+                        account: self
+                            .caller_identities
+                            .get(&r.profile)
+                            .map(|i| i.account_label().to_string()),
                     })
                 } else {
                     None
@@ -72,6 +112,27 @@ impl ResourcesTable {
             .collect();
     }
 
+    /// Profiles and regions of the rows whose account is not known yet.
+    pub fn get_profiles_without_identity(&self) -> Vec<(String, String)> {
+        let mut profiles: Vec<(String, String)> = vec![];
+        for row in &self.rows {
+            let profile = &row.descriptor.profile;
+            if row.account.is_none() && !profiles.iter().any(|(p, _)| p == profile) {
+                profiles.push((profile.clone(), row.descriptor.region.clone()));
+            }
+        }
+        profiles
+    }
+
+    pub fn set_caller_identity(&mut self, profile: &str, identity: CallerIdentity) {
+        for row in &mut self.rows {
+            if row.descriptor.profile == profile {
+                row.account = Some(identity.account_label().to_string());
+            }
+        }
+        self.caller_identities.insert(profile.to_string(), identity);
+    }
+
     pub fn sync_header_offset(&self, offset: AbsoluteOffset) -> Task<Message> {
         Task::batch(vec![
             scrollable::scroll_to(self.header_id.clone(), offset),
@@ -91,9 +152,42 @@ impl ResourcesTable {
                 column.width += offset;
             }
         }
+        if let Some(service) = &self.service {
+            self.column_widths.insert(
+                service.clone(),
+                self.columns.iter().map(|c| c.width).collect(),
+            );
+        }
+    }
+
+    pub fn get_column_widths(&self) -> BTreeMap<Service, Vec<f32>> {
+        self.column_widths.clone()
+    }
+
+    pub fn set_column_widths(&mut self, column_widths: BTreeMap<Service, Vec<f32>>) {
+        self.column_widths = column_widths;
+        self.apply_column_widths();
     }
 
-    pub fn view(&self) -> Element<Message> {
+    /// Widths saved for a different set of columns, e.g. by another
+    /// version of the provider, are ignored.
+    fn apply_column_widths(&mut self) {
+        let Some(widths) = self
+            .service
+            .as_ref()
+            .and_then(|s| self.column_widths.get(s))
+        else {
+            return;
+        };
+        if widths.len() != self.columns.len() {
+            return;
+        }
+        for (column, width) in self.columns.iter_mut().zip(widths) {
+            column.width = width.max(MIN_COLUMN_WIDTH);
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
         container(
             table(
                 self.header_id.clone(),
@@ -122,8 +216,10 @@ impl Column {
     fn new(kind: ColumnKind) -> Self {
         let width = match kind {
             ColumnKind::Name => 320.0,
-            ColumnKind::Profile => 120.0,
+            ColumnKind::Profile => 200.0,
             ColumnKind::Region => 150.0,
+            ColumnKind::Custom { width, .. } => width,
+            ColumnKind::Remove => 40.0,
         };
         Self {
             kind,
@@ -133,20 +229,33 @@ impl Column {
     }
 }
 
-enum ColumnKind {
+/// A column of the resources table. Providers may add columns whose cells
+/// are derived from the row's descriptor.
+#[derive(Clone, Copy)]
+pub enum ColumnKind {
     Name,
     Profile,
     Region,
+    Custom {
+        header: &'static str,
+        width: f32,
+        value: fn(&ResourceDescriptor) -> String,
+    },
+    /// Removes the row from the project. The table adds it after the
+    /// provider's columns.
+    Remove,
 }
 
 impl<'a> iced_table::table::Column<'a, Message, Theme, Renderer> for Column {
-    type Row = ResourceDescriptor;
+    type Row = ResourceRow;
 
     fn header(&'a self, _col_index: usize) -> Element<'a, Message> {
         let content = match self.kind {
             ColumnKind::Name => "Name",
             ColumnKind::Profile => "Profile",
             ColumnKind::Region => "Region",
+            ColumnKind::Custom { header, .. } => header,
+            ColumnKind::Remove => "",
         };
 
         container(text(content)).center_y(24).into()
@@ -156,18 +265,36 @@ impl<'a> iced_table::table::Column<'a, Message, Theme, Renderer> for Column {
         &'a self,
         col_index: usize,
         row_index: usize,
-        row: &'a ResourceDescriptor,
+        row: &'a ResourceRow,
     ) -> Element<'a, Message> {
+        let rd = &row.descriptor;
+        if let ColumnKind::Remove = self.kind {
+            return container(
+                button(ICONS.trash(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                    .on_press(Message::RemoveFromProject(
+                        row.project_id.clone(),
+                        rd.clone(),
+                    ))
+                    .style(styles::tool_button),
+            )
+            .center_y(24)
+            .into();
+        }
         let content = match self.kind {
-            ColumnKind::Name => text(row.id.clone()),
-            ColumnKind::Profile => text(row.profile.clone()),
-            ColumnKind::Region => text(row.region.clone()),
+            ColumnKind::Name => text(rd.get_display_name()),
+            ColumnKind::Profile => match &row.account {
+                Some(account) => text(format!("{} ({})", rd.profile, account)),
+                None => text(rd.profile.clone()),
+            },
+            ColumnKind::Region => text(rd.region.clone()),
+            ColumnKind::Custom { value, .. } => text(value(rd)),
+            ColumnKind::Remove => text(""),
         };
         container(
             mouse_area(content).on_press(Message::ResourcesTableCellClicked(
                 col_index,
                 row_index,
-                row.clone(),
+                rd.clone(),
             )),
         )
         .center_y(24)
@@ -177,7 +304,7 @@ impl<'a> iced_table::table::Column<'a, Message, Theme, Renderer> for Column {
     fn footer(
         &'a self,
         _col_index: usize,
-        _rows: &'a [ResourceDescriptor],
+        _rows: &'a [ResourceRow],
     ) -> Option<Element<'a, Message>> {
         Some(container(text("")).center_y(24).into())
     }
@@ -3,20 +3,57 @@ use iced::{
     Border, Theme,
 };
 
-pub fn header(theme: &Theme) -> container::Style {
+/// Tinted red while a protected profile is active.
+pub fn header(protected: bool) -> impl Fn(&Theme) -> container::Style {
+    move |theme| {
+        let palette = theme.extended_palette();
+
+        container::Style {
+            background: Some(match protected {
+                true => palette.danger.weak.color.into(),
+                false => palette.background.base.color.into(),
+            }),
+            border: Border {
+                width: 1.0,
+                color: palette.background.weak.color,
+                ..Border::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub fn protection_banner(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.danger.base.color.into()),
+        text_color: Some(palette.danger.base.text),
+        ..Default::default()
+    }
+}
+
+pub fn toast(theme: &Theme) -> container::Style {
     let palette = theme.extended_palette();
 
     container::Style {
         background: Some(palette.background.base.color.into()),
         border: Border {
             width: 1.0,
-            color: palette.background.weak.color,
-            ..Border::default()
+            radius: 4.0.into(),
+            color: palette.danger.base.color,
         },
         ..Default::default()
     }
 }
 
+pub fn modal_backdrop(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(iced::Color::from_rgba(0.0, 0.0, 0.0, 0.5).into()),
+        ..Default::default()
+    }
+}
+
 pub fn menu_button(theme: &Theme, status: button::Status) -> button::Style {
     let palette = theme.extended_palette();
 
@@ -167,6 +204,40 @@ pub fn service(theme: &Theme, status: button::Status) -> button::Style {
     }
 }
 
+pub fn selected_object_entry(theme: &Theme, status: button::Status) -> button::Style {
+    let palette = theme.extended_palette();
+
+    match status {
+        button::Status::Hovered => button::Style {
+            background: Some(palette.primary.strong.color.into()),
+            text_color: palette.primary.strong.text,
+            ..Default::default()
+        },
+        _ => button::Style {
+            background: Some(palette.primary.base.color.into()),
+            text_color: palette.primary.base.text,
+            ..Default::default()
+        },
+    }
+}
+
+pub fn object_entry(theme: &Theme, status: button::Status) -> button::Style {
+    let palette = theme.extended_palette();
+
+    match status {
+        button::Status::Hovered => button::Style {
+            background: Some(palette.background.weak.color.into()),
+            text_color: palette.background.weak.text,
+            ..Default::default()
+        },
+        _ => button::Style {
+            background: Some(palette.background.base.color.into()),
+            text_color: palette.background.base.text,
+            ..Default::default()
+        },
+    }
+}
+
 pub fn service_selection_list(
     theme: &Theme,
     _status: iced_aw::style::status::Status,
@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use iced::{
+    alignment::Vertical,
+    widget::{button, pick_list, row, text, text_input},
+    Element, Length, Task,
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+    message::Message,
+    models::workspace::store::{list_workspaces, workspaces_dir, RecentWorkspaces, WorkspaceEntry},
+    view::{icons::ICONS, styles},
+};
+
+static INPUT_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+
+/// What the text typed into the switcher is for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WorkspaceAction {
+    /// Creates an empty workspace with the typed name.
+    New,
+    /// Copies the current workspace to one with the typed name.
+    Duplicate,
+    /// Opens the workspace file at the typed path.
+    Open,
+}
+
+pub struct WorkspaceSwitcher {
+    /// The workspaces in the config directory.
+    workspaces: Vec<WorkspaceEntry>,
+    /// Most recent first, including files outside the config directory.
+    recent: Vec<WorkspaceEntry>,
+    input: Option<(WorkspaceAction, String)>,
+}
+
+impl WorkspaceSwitcher {
+    pub fn new() -> Self {
+        Self {
+            workspaces: vec![],
+            recent: vec![],
+            input: None,
+        }
+    }
+
+    /// Lists the workspaces again, e.g. after one has been created.
+    pub fn refresh(&mut self) {
+        self.workspaces = match workspaces_dir() {
+            Ok(dir) => list_workspaces(&dir),
+            Err(e) => {
+                tracing::warn!(error = ?e, "Failed to list the workspaces");
+                vec![]
+            }
+        };
+        self.recent = RecentWorkspaces::load().entries();
+    }
+
+    pub fn start_input(&mut self, action: WorkspaceAction) -> Task<Message> {
+        self.input = Some((action, String::new()));
+        text_input::focus(INPUT_ID.clone())
+    }
+
+    pub fn set_input(&mut self, value: String) {
+        if let Some((_, current)) = &mut self.input {
+            *current = value;
+        }
+    }
+
+    /// The action and its trimmed input, unless nothing was typed.
+    pub fn finish_input(&mut self) -> Option<(WorkspaceAction, String)> {
+        let (action, value) = self.input.take()?;
+        let value = value.trim();
+        match value.is_empty() {
+            true => None,
+            false => Some((action, value.to_string())),
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        self.input = None;
+    }
+
+    pub fn view(&self, current: Option<&Path>) -> Element<'_, Message> {
+        if let Some((action, value)) = &self.input {
+            return self.render_input(*action, value);
+        }
+
+        let current = current.map(|p| WorkspaceEntry::new(p.to_path_buf()));
+        let recent: Vec<_> = self
+            .recent
+            .iter()
+            .filter(|e| current.as_ref().is_none_or(|c| c.path != e.path))
+            .cloned()
+            .collect();
+        let tool = |label: &'static str, action: WorkspaceAction| {
+            button(text(label))
+                .on_press(Message::WorkspaceInputStarted(action))
+                .style(styles::tool_button)
+        };
+        row![
+            pick_list(self.workspaces.clone(), current, |e| {
+                Message::WorkspaceSelected(e.path)
+            })
+            .placeholder("Workspace")
+            .width(Length::Fixed(200.0)),
+            pick_list(recent, None::<WorkspaceEntry>, |e| {
+                Message::WorkspaceSelected(e.path)
+            })
+            .placeholder("Recent")
+            .width(Length::Fixed(120.0)),
+            tool("New", WorkspaceAction::New),
+            tool("Duplicate", WorkspaceAction::Duplicate),
+            tool("Open…", WorkspaceAction::Open),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    fn render_input<'a>(&'a self, action: WorkspaceAction, value: &'a str) -> Element<'a, Message> {
+        let placeholder = match action {
+            WorkspaceAction::New => "Name of the new workspace",
+            WorkspaceAction::Duplicate => "Name of the copy",
+            WorkspaceAction::Open => "Path to a workspace file",
+        };
+        row![
+            text_input(placeholder, value)
+                .id(INPUT_ID.clone())
+                .on_input(Message::WorkspaceInputChanged)
+                .on_submit(Message::WorkspaceInputSubmitted)
+                .width(Length::Fixed(360.0)),
+            button(ICONS.check_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::WorkspaceInputSubmitted)
+                .style(styles::tool_button),
+            button(ICONS.x_lg(10.0, Length::Fixed(16.0), Length::Fixed(16.0)))
+                .on_press(Message::WorkspaceInputCancelled)
+                .style(styles::tool_button),
+        ]
+        .spacing(2)
+        .align_y(Vertical::Center)
+        .into()
+    }
+}